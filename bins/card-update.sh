@@ -20,9 +20,18 @@ ORACLE_URL=$(curl 'https://api.scryfall.com/bulk-data' | python3 -c "import sys,
 curl $ORACLE_URL -o "$ORACLE_CARDS"
 #  3. Generate data/all_cards.landlord using the oracle cards
 RUST_BACKTRACE=1 RUST_LOG=info cargo run --release --bin scryfall2landlord "$ORACLE_CARDS" "data/all_cards.landlord"
-#  4. Was a new artifact generated? If so and this is the CI pipeline, then test it and upload the input file to S3
-git diff --exit-code --quiet
-if [ $? -eq 1 ] && [ "$CI" -eq 1 ]; then
+#     and the smaller databases of the arena-only and minimal-fields features
+RUST_LOG=info cargo run --release --bin scryfall2landlord "$ORACLE_CARDS" "data/arena_cards.landlord" --arena-only
+RUST_LOG=info cargo run --release --bin scryfall2landlord "$ORACLE_CARDS" "data/all_cards.minimal.landlord" --minimal-fields
+RUST_LOG=info cargo run --release --bin scryfall2landlord "$ORACLE_CARDS" "data/arena_cards.minimal.landlord" --arena-only --minimal-fields
+#  4. Was a new artifact generated? If so, record the snapshot date, see landlord::version_info
+git diff --exit-code --quiet data/all_cards.landlord
+CHANGED=$?
+if [ $CHANGED -eq 1 ]; then
+    echo "$DATE" > data/all_cards.date
+fi
+#  5. If this is the CI pipeline, then test the new artifact and upload the input file to S3
+if [ $CHANGED -eq 1 ] && [ "$CI" -eq 1 ]; then
     # Changes
     cargo test --all
     python3 --version
@@ -32,7 +41,8 @@ if [ $? -eq 1 ] && [ "$CI" -eq 1 ]; then
     aws s3 cp "$ORACLE_CARDS" "s3://mtgoncurve-scryfall-archive/$INPUT"
     git config --local user.name "Card Update Bot"
     git config --local user.email "bot@mtgoncurve.com"
-    git commit -am "Update all_cards.landlord ($ORACLE_CARDS)"
+    git add data/*.landlord data/all_cards.date
+    git commit -m "Update all_cards.landlord ($ORACLE_CARDS)"
     git push origin master
 fi
 rm "$ORACLE_CARDS"
@@ -0,0 +1,311 @@
+extern crate landlord;
+extern crate serde_json;
+
+use landlord::deck::Deck;
+use landlord::manifest::{run_manifest_file, ManifestResult};
+use landlord::mulligan::London;
+use landlord::result::{CardResult, SimulationResult};
+use landlord::simulation::{Simulation, SimulationConfig};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "Usage: landlord sim <deck.txt> [options]
+       landlord batch <manifest.json> [--format table|json]
+
+Options:
+    --iterations N                   number of simulated games [default: 10000]
+    --draws N                        cards drawn after the opening hand [default: 10]
+    --on-the-draw                    simulate games on the draw
+    --mulligan never|london|commander
+                                     mulligan strategy [default: never]
+    --seed N                         random seed, to reproduce a simulation
+    --format table|json              output format [default: table]";
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+struct SimArgs {
+    deck_path: String,
+    iterations: usize,
+    draws: usize,
+    on_the_play: bool,
+    mulligan: String,
+    seed: Option<u64>,
+    format: Format,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("sim") => parse_sim_args(&args[1..]).and_then(|args| sim(&args)),
+        Some("batch") => {
+            parse_batch_args(&args[1..]).and_then(|(path, format)| batch(&path, &format))
+        }
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("Unknown command {}\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// Returns the options of the sim command from the arguments that follow it
+fn parse_sim_args(args: &[String]) -> Result<SimArgs, String> {
+    let mut deck_path = None;
+    let mut sim_args = SimArgs {
+        deck_path: String::new(),
+        iterations: 10000,
+        draws: 10,
+        on_the_play: true,
+        mulligan: "never".to_string(),
+        seed: None,
+        format: Format::Table,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--iterations" => sim_args.iterations = parse_number(arg, value()?)?,
+            "--draws" => sim_args.draws = parse_number(arg, value()?)?,
+            "--seed" => sim_args.seed = Some(parse_number(arg, value()?)?),
+            "--on-the-draw" => sim_args.on_the_play = false,
+            "--mulligan" => sim_args.mulligan = value()?.clone(),
+            "--format" => sim_args.format = parse_format(args.next())?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if deck_path.is_none() => deck_path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    sim_args.deck_path = deck_path.ok_or_else(|| USAGE.to_string())?;
+    if sim_args.iterations == 0 {
+        return Err("--iterations must be positive".to_string());
+    }
+    mulligan(&sim_args.mulligan)?;
+    Ok(sim_args)
+}
+
+/// Returns the manifest path and the output format of the batch command from the
+/// arguments that follow it
+fn parse_batch_args(args: &[String]) -> Result<(String, Format), String> {
+    let mut manifest_path = None;
+    let mut format = Format::Table;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = parse_format(args.next())?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if manifest_path.is_none() => manifest_path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    let manifest_path = manifest_path.ok_or_else(|| USAGE.to_string())?;
+    Ok((manifest_path, format))
+}
+
+fn parse_format(value: Option<&String>) -> Result<Format, String> {
+    match value.map(String::as_str) {
+        Some("table") => Ok(Format::Table),
+        Some("json") => Ok(Format::Json),
+        Some(format) => Err(format!("Unknown format {}", format)),
+        None => Err("Missing value for --format".to_string()),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a number for {}, got {}", option, value))
+}
+
+/// Returns the mulligan strategy named `name`
+fn mulligan(name: &str) -> Result<London, String> {
+    match name {
+        "never" => Ok(London::never()),
+        "london" => {
+            let mut mulligan = London::commander();
+            mulligan.free_mulligans = 0;
+            Ok(mulligan)
+        }
+        "commander" => Ok(London::commander()),
+        _ => Err(format!("Unknown mulligan strategy {}", name)),
+    }
+}
+
+fn sim(args: &SimArgs) -> Result<(), String> {
+    let deck_str = fs::read_to_string(&args.deck_path)
+        .map_err(|e| format!("Error reading {}: {}", args.deck_path, e))?;
+    let deck = Deck::from_list(&deck_str).map_err(|e| format!("Error parsing deck: {}", e.0))?;
+    if deck.is_empty() {
+        return Err("Error parsing deck: the deck is empty".to_string());
+    }
+    let mulligan = mulligan(&args.mulligan)?;
+    let sim = Simulation::from_config(&SimulationConfig {
+        on_the_play: args.on_the_play,
+        seed: args.seed,
+        ..SimulationConfig::new(&deck, &mulligan, args.iterations, args.draws)
+    });
+    let output = SimulationResult::new(&sim, &deck);
+    match args.format {
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?;
+            println!("{}", json);
+        }
+        Format::Table => print!("{}", table(&output)),
+    }
+    Ok(())
+}
+
+fn batch(manifest_path: &str, format: &Format) -> Result<(), String> {
+    let results = run_manifest_file(Path::new(manifest_path))?;
+    match format {
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?;
+            println!("{}", json);
+        }
+        Format::Table => print!("{}", batch_table(&results)),
+    }
+    Ok(())
+}
+
+/// Returns a human readable table of `results`, with the average on curve probabilities
+/// of the nonland cards of each deck, weighted by their number of copies
+fn batch_table(results: &[ManifestResult]) -> String {
+    let name_width = results
+        .iter()
+        .map(|run| run.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut table = format!(
+        "{:<width$}  {:>7}  {:>7}  {:>7}  {}\n",
+        "Name",
+        "Games",
+        "P(mana)",
+        "P(play)",
+        "Seed",
+        width = name_width
+    );
+    for run in results {
+        let copies: usize = run.result.cards.iter().map(|card| card.copies).sum();
+        let average = |p: fn(&CardResult) -> f64| {
+            let total: f64 = run
+                .result
+                .cards
+                .iter()
+                .map(|card| p(card) * card.copies as f64)
+                .sum();
+            100.0 * total / copies.max(1) as f64
+        };
+        table.push_str(&format!(
+            "{:<width$}  {:>7}  {:>6.1}%  {:>6.1}%  {}\n",
+            run.name,
+            run.result.run_count,
+            average(|card| card.p_mana),
+            average(|card| card.p_play),
+            run.result.seed,
+            width = name_width
+        ));
+    }
+    table
+}
+
+/// Returns `output` as a human readable table
+fn table(output: &SimulationResult) -> String {
+    let name_width = output
+        .cards
+        .iter()
+        .map(|card| card.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut table = format!(
+        "{:<width$}  {:>4}  {:>7}  {:>7}  {:>9}  {}\n",
+        "Card",
+        "Turn",
+        "P(mana)",
+        "P(play)",
+        "P(mana)+1",
+        "Blocking color",
+        width = name_width
+    );
+    for card in &output.cards {
+        table.push_str(&format!(
+            "{:<width$}  {:>4}  {:>6.1}%  {:>6.1}%  {:>8.1}%  {}\n",
+            card.name,
+            card.turn,
+            100.0 * card.p_mana,
+            100.0 * card.p_play,
+            100.0 * card.p_mana_next_turn,
+            card.blocking_color.as_deref().unwrap_or(""),
+            width = name_width
+        ));
+    }
+    let mulligans = &output.mulligans;
+    table.push_str(&format!(
+        "\n{} games, {} mulligans, {:.2} lands in kept hands on average, seed {}\n",
+        output.run_count, mulligans.mulligans, mulligans.average_kept_land_count, output.seed
+    ));
+    match output.verified {
+        Some(true) => table.push_str("Verified against exact probabilities\n"),
+        Some(false) => table.push_str("Warning: disagrees with exact probabilities\n"),
+        None => {}
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_sim_args_defaults() {
+        let sim_args = parse_sim_args(&args(&["deck.txt"])).unwrap();
+        assert_eq!(sim_args.deck_path, "deck.txt");
+        assert_eq!(sim_args.iterations, 10000);
+        assert!(sim_args.on_the_play);
+        assert_eq!(sim_args.format, Format::Table);
+    }
+
+    #[test]
+    fn parse_sim_args_options() {
+        let sim_args = parse_sim_args(&args(&[
+            "--iterations",
+            "50000",
+            "deck.txt",
+            "--on-the-draw",
+            "--mulligan",
+            "london",
+            "--seed",
+            "7",
+            "--format",
+            "json",
+        ]))
+        .unwrap();
+        assert_eq!(sim_args.iterations, 50000);
+        assert!(!sim_args.on_the_play);
+        assert_eq!(sim_args.mulligan, "london");
+        assert_eq!(sim_args.seed, Some(7));
+        assert_eq!(sim_args.format, Format::Json);
+        assert!(parse_sim_args(&args(&["deck.txt", "--iterations", "many"])).is_err());
+        assert!(parse_sim_args(&args(&["deck.txt", "--mulligan", "paris"])).is_err());
+        assert!(parse_sim_args(&args(&["--seed", "1"])).is_err());
+    }
+}
@@ -8,8 +8,9 @@ extern crate landlord;
 
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use landlord::card::{Card, Legality};
+use landlord::card::Card;
 use landlord::collection::Collection;
+use landlord::functions::CardFunctions;
 use landlord::scryfall::ScryfallCard;
 use std::env;
 use std::fs::File;
@@ -44,10 +45,21 @@ impl From<bincode::Error> for Error {
 
 fn main() -> Result<(), Error> {
     let _ = env_logger::try_init();
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() > 2, "Expected 2 arguments, URI and output path");
+    // --arena-only keeps the cards that can be played on Arena, and --minimal-fields drops
+    // the fields the simulator doesn't need, for the smaller databases embedded by the
+    // landlord features of the same names
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().partition(|arg| arg.starts_with("--"));
+    let arena_only = flags.iter().any(|flag| flag == "--arena-only");
+    let minimal_fields = flags.iter().any(|flag| flag == "--minimal-fields");
+    assert!(
+        args.len() > 2,
+        "Expected 2 arguments, URI and output path, and optionally a card functions output path, \
+         and the optional flags --arena-only and --minimal-fields"
+    );
     let uri_string = &args[1];
     let out_path_string = &args[2];
+    let functions_path_string = args.get(3);
 
     let uri_path = Path::new(uri_string);
     info!("Loading JSON file @ {}", uri_string);
@@ -56,13 +68,12 @@ fn main() -> Result<(), Error> {
     let json_val = serde_json::from_str(&json_file_contents)?;
     info!("Deserializing Scryfall JSON");
     let mut scryfall_cards: Vec<ScryfallCard> = serde_json::from_value(json_val)?;
-    // Filter out any cards that are not legal in all formats
-    // This should filter out any tokens
+    // Filter out tokens, emblems, art cards, and any cards that are not legal in all formats
     // See https://github.com/mtgoncurve/landlord/issues/4
-    scryfall_cards = scryfall_cards
-        .into_iter()
-        .filter(|c| !c.legalities.values().all(|l| l == &Legality::NotLegal))
-        .collect();
+    scryfall_cards.retain(|c| c.class().is_playable());
+    if arena_only {
+        scryfall_cards.retain(|c| c.is_on_arena());
+    }
     /*
     // TODO(jshrake):
     // The following commented out code attempts to whittle down the defaul cards
@@ -104,8 +115,21 @@ fn main() -> Result<(), Error> {
         }
     }
     scryfall_cards.extend(card_faces);
+    if let Some(functions_path_string) = functions_path_string {
+        info!("Writing card functions to {}", functions_path_string);
+        let functions = CardFunctions::from_scryfall(&scryfall_cards);
+        let file = File::create(functions_path_string)?;
+        serde_json::to_writer(file, &functions)?;
+    }
     info!("Generating landlord output");
-    let landlord_cards: Vec<Card> = scryfall_cards.into_iter().map(|c| c.into()).collect();
+    let mut landlord_cards: Vec<Card> = scryfall_cards.into_iter().map(|c| c.into()).collect();
+    if minimal_fields {
+        // Card images and oracle ids are only used by UIs and collection tools
+        for card in &mut landlord_cards {
+            card.image_uri.clear();
+            card.oracle_id.clear();
+        }
+    }
     let collection = Collection::from_cards(landlord_cards);
     info!("Running bincode::serialize on output");
     let encoded_collection = bincode::serialize(&collection)?;
@@ -113,6 +137,7 @@ fn main() -> Result<(), Error> {
     let file: File = OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(true)
         .open(out_path_string)
         .unwrap();
     let mut e = GzEncoder::new(file, Compression::default());
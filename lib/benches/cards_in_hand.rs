@@ -3,6 +3,7 @@ extern crate criterion;
 
 use criterion::Criterion;
 use landlord::deck::Deck;
+use landlord::hand::CantripPolicy;
 use landlord::mulligan::London;
 use landlord::simulation::{Simulation, SimulationConfig};
 
@@ -50,6 +51,9 @@ fn criterion_function(c: &mut Criterion) {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: false,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
     });
     c.bench_function("48388 card_observations", |b| {
         b.iter(|| {
@@ -0,0 +1,30 @@
+//! Selects the card database embedded by lib/src/data.rs. The `arena-only` and
+//! `minimal-fields` features embed the smaller databases generated by bins/card-update.sh,
+//! and fail the build until those are generated
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let arena_only = env::var_os("CARGO_FEATURE_ARENA_ONLY").is_some();
+    let minimal_fields = env::var_os("CARGO_FEATURE_MINIMAL_FIELDS").is_some();
+    let file = match (arena_only, minimal_fields) {
+        (false, false) => "all_cards.landlord",
+        (true, false) => "arena_cards.landlord",
+        (false, true) => "all_cards.minimal.landlord",
+        (true, true) => "arena_cards.minimal.landlord",
+    };
+    let data = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("..")
+        .join("data");
+    let path = data.join(file);
+    if !path.exists() {
+        panic!(
+            "{} is missing, run make card-update to generate it or build without the arena-only and minimal-fields features",
+            path.display()
+        );
+    }
+    // The data directory changes when card-update.sh adds a database
+    println!("cargo:rerun-if-changed={}", data.display());
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-env=LANDLORD_CARD_DATA={}", path.display());
+}
@@ -0,0 +1,29 @@
+/*
+ * C bindings for landlord, built with the `ffi` feature. See lib/src/ffi.rs.
+ *
+ * Every function returns a nul terminated UTF-8 JSON string, either {"ok": ...}
+ * or {"error": "..."}, that must be released with landlord_free_result.
+ */
+#ifndef LANDLORD_H
+#define LANDLORD_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Parses a deck list and returns the deck */
+char *landlord_parse_deck(const char *deck_str);
+
+/* Simulates a deck list with a JSON configuration, e.g.
+ * {"run_count": 10000, "draw_count": 10, "on_the_play": true}, and returns
+ * the versioned simulation result, see SimulationResult in the result module */
+char *landlord_simulate(const char *deck_str, const char *config_json);
+
+/* Releases a string returned by any other landlord_ function */
+void landlord_free_result(char *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LANDLORD_H */
@@ -0,0 +1,145 @@
+//! # Opening hand advisor
+//!
+//! Answers "should I keep this hand?" for a specific opening hand by simulating games
+//! that start from it and comparing them with games that ship it for a 6 card hand.
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::manabase::manabase_score;
+use crate::mulligan::{London, Mulligan};
+use crate::simulation::{run_rng, Simulation, SimulationConfig};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+/// KeepAdvice is the recommendation for an opening hand, see `advise_keep`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeepAdvice {
+  /// True if keeping the hand is expected to cast at least as many spells on curve
+  pub keep: bool,
+  /// The castability of the deck when keeping the hand, see `manabase::manabase_score`
+  pub keep_castability: f64,
+  /// The castability of the deck when shipping the hand and keeping the next 6 cards
+  pub ship_castability: f64,
+  /// `keep_castability - ship_castability`
+  pub delta: f64,
+}
+
+/// Returns the advice to keep or mulligan the opening `hand` of `config.deck`. Games that
+/// keep the hand draw the rest of the library in a random order, and games that ship it
+/// draw a new 7 card hand and put the card with the highest turn on the bottom. The
+/// mulligan strategy of `config` is not used. Returns an error if `config.run_count` is 0,
+/// if the hand is empty or if a card of the hand isn't in the deck
+pub fn advise_keep<M: Mulligan>(
+  config: &SimulationConfig<M>,
+  hand: &[&Card],
+) -> Result<KeepAdvice, String> {
+  if config.run_count == 0 {
+    return Err("run_count must be positive".to_string());
+  }
+  if hand.is_empty() {
+    return Err("the hand is empty".to_string());
+  }
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let mut library = config.deck.flatten();
+  for card in hand {
+    let i = library
+      .iter()
+      .position(|c| c.hash == card.hash)
+      .ok_or_else(|| format!("{} is not in the deck", card.name))?;
+    library.swap_remove(i);
+  }
+  let window = std::cmp::min(library.len(), Simulation::simulated_draw_count(config));
+  let hands = (0..config.run_count)
+    .map(|run| {
+      let mut rng = run_rng(seed, run);
+      let mut index_range: Vec<usize> = (0..library.len()).collect();
+      let draws: Vec<&Card> = index_range
+        .partial_shuffle(&mut rng, window)
+        .0
+        .iter()
+        .map(|i| library[*i])
+        .collect();
+      let mut kept = Hand::from_opening_and_draws(hand, &draws);
+      let other_order_kept = Simulation::play_cantrips(config, &mut kept);
+      (kept, other_order_kept)
+    })
+    .collect();
+  let keep = Simulation::from_hands(config, seed, hands);
+  let ship_mulligan = London::always(hand.len() - 1);
+  let ship = Simulation::from_config(&SimulationConfig {
+    on_the_play: config.on_the_play,
+    extra_draws: config.extra_draws,
+    seed: Some(seed),
+    cantrip_policy: config.cantrip_policy,
+    ..SimulationConfig::new(
+      config.deck,
+      &ship_mulligan,
+      config.run_count,
+      config.draw_count,
+    )
+  });
+  let keep_castability = manabase_score(&keep, config.deck);
+  let ship_castability = manabase_score(&ship, config.deck);
+  let delta = keep_castability - ship_castability;
+  Ok(KeepAdvice {
+    keep: delta >= 0.0,
+    keep_castability,
+    ship_castability,
+    delta,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::advisor::*;
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::Never;
+
+  #[test]
+  fn advise_keep_or_ship() {
+    let deck = decklist!(
+      "
+      24 Swamp
+      28 Thoughtseize
+      8 Vraska's Contempt
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 2000,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(7),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let spell = card!("Thoughtseize");
+    let swamp = card!("Swamp");
+    let no_lands = [spell, spell, spell, spell, spell, spell, spell];
+    let advice = advise_keep(&config, &no_lands).unwrap();
+    assert!(!advice.keep);
+    assert!(advice.delta < 0.0);
+    let three_lands = [
+      swamp,
+      swamp,
+      swamp,
+      spell,
+      spell,
+      spell,
+      card!("Vraska's Contempt"),
+    ];
+    let advice = advise_keep(&config, &three_lands).unwrap();
+    assert!(advice.keep);
+    assert!(advice.keep_castability > advice.ship_castability);
+    let missing = [card!("Island"), spell, spell, spell, spell, spell, spell];
+    assert!(advise_keep(&config, &missing).is_err());
+    assert!(advise_keep(&config, &[]).is_err());
+    let no_runs = SimulationConfig {
+      run_count: 0,
+      ..config
+    };
+    assert!(advise_keep(&no_runs, &three_lands).is_err());
+  }
+}
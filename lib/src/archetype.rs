@@ -0,0 +1,251 @@
+//! # Deck archetype classification
+//!
+//! Labels a deck as aggro, midrange, control or combo from the shape of its curve, its land
+//! count and its creature density, or from user supplied signature cards, so that trackers
+//! can group match history by archetype. The card data doesn't record creature types for
+//! nonland cards, so creatures are the cards with `CardKind::Creature` or the `creature` tag.
+use crate::card::{Card, CardKind, ManaColor};
+use crate::deck::Deck;
+
+/// The tag of the creatures of a deck, see `Deck::tag`
+pub const CREATURE_TAG: &str = "creature";
+
+/// The tag of the combo pieces of a deck, see `Deck::tag`
+pub const COMBO_TAG: &str = "combo";
+
+/// The highest average mana value of the nonland cards of an aggro deck
+const AGGRO_MAX_AVERAGE_CMC: f64 = 2.5;
+
+/// The highest fraction of lands in an aggro deck, e.g. 22 lands in 60 cards
+const AGGRO_MAX_LAND_RATIO: f64 = 0.38;
+
+/// The lowest fraction of creatures among the nonland cards of an aggro deck
+const AGGRO_MIN_CREATURE_DENSITY: f64 = 0.5;
+
+/// The lowest average mana value of the nonland cards of a control deck
+const CONTROL_MIN_AVERAGE_CMC: f64 = 3.2;
+
+/// The highest fraction of creatures among the nonland cards of a control deck
+const CONTROL_MAX_CREATURE_DENSITY: f64 = 0.25;
+
+/// The lowest fraction of colored mana symbols for a color to be a primary color
+const PRIMARY_COLOR_MIN_SHARE: f64 = 0.15;
+
+/// The number of key cards of a deck without signature cards or combo pieces
+const KEY_CARD_COUNT: usize = 3;
+
+/// The colors, in WUBRG order
+const COLORS: [ManaColor; 5] = [
+  ManaColor::White,
+  ManaColor::Blue,
+  ManaColor::Black,
+  ManaColor::Red,
+  ManaColor::Green,
+];
+
+/// Strategy is the broad game plan of a deck
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strategy {
+  Aggro,
+  Midrange,
+  Control,
+  Combo,
+}
+
+/// Signature names an archetype by the cards that identify it, like "Mono Red" by
+/// Monastery Swiftspear and Kumano Faces Kakkazan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signature {
+  pub name: String,
+  pub strategy: Strategy,
+  pub cards: Vec<String>,
+  /// The number of distinct signature cards a deck must contain to match
+  pub min_cards: usize,
+}
+
+/// Archetype is the classification of a deck, see `classify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Archetype {
+  /// The name of the matched signature, if any
+  pub name: Option<String>,
+  pub strategy: Strategy,
+  /// The primary colors of the deck, most colored mana symbols first
+  pub colors: Vec<ManaColor>,
+  /// The names of the cards that define the deck: the matched signature cards, the combo
+  /// pieces, or else the nonland cards with the most copies
+  pub key_cards: Vec<String>,
+  /// The average mana value of the nonland cards
+  pub average_cmc: f64,
+  /// The fraction of creatures among the nonland cards, or None if no card of the deck is
+  /// known to be a creature
+  pub creature_density: Option<f64>,
+}
+
+/// Returns the archetype of `deck`. The first of `signatures` that the deck matches names it,
+/// and otherwise the strategy follows from the curve, land count and creature density
+pub fn classify(deck: &Deck, signatures: &[Signature]) -> Archetype {
+  let nonlands: Vec<(&Card, usize)> = deck
+    .cards
+    .iter()
+    .filter(|cc| !cc.card.is_land())
+    .map(|cc| (&cc.card, cc.count))
+    .collect();
+  let nonland_count: usize = nonlands.iter().map(|(_, count)| count).sum();
+  let average_cmc = if nonland_count > 0 {
+    let total: usize = nonlands
+      .iter()
+      .map(|(card, count)| card.cmc() as usize * count)
+      .sum();
+    total as f64 / nonland_count as f64
+  } else {
+    0.0
+  };
+  let creatures = deck.cards_with_tag(CREATURE_TAG);
+  let creature_count: usize = nonlands
+    .iter()
+    .filter(|(card, _)| card.kind == CardKind::Creature || creatures.contains(card))
+    .map(|(_, count)| count)
+    .sum();
+  let creature_density = if creature_count > 0 {
+    Some(creature_count as f64 / nonland_count as f64)
+  } else {
+    None
+  };
+  let land_ratio = if deck.is_empty() {
+    0.0
+  } else {
+    (deck.len() - nonland_count) as f64 / deck.len() as f64
+  };
+  let signature = signatures.iter().find(|signature| {
+    let found = signature
+      .cards
+      .iter()
+      .filter(|name| deck.card_from_name(name).is_some())
+      .count();
+    found >= std::cmp::max(1, signature.min_cards)
+  });
+  let combo_pieces = deck.cards_with_tag(COMBO_TAG);
+  let (name, strategy, key_cards) = if let Some(signature) = signature {
+    let key_cards = signature
+      .cards
+      .iter()
+      .filter_map(|name| deck.card_from_name(name))
+      .map(|card| card.name.clone())
+      .collect();
+    (Some(signature.name.clone()), signature.strategy, key_cards)
+  } else if !combo_pieces.is_empty() {
+    let key_cards = combo_pieces.iter().map(|card| card.name.clone()).collect();
+    (None, Strategy::Combo, key_cards)
+  } else {
+    let strategy = if average_cmc <= AGGRO_MAX_AVERAGE_CMC
+      && land_ratio <= AGGRO_MAX_LAND_RATIO
+      && creature_density.is_none_or(|density| density >= AGGRO_MIN_CREATURE_DENSITY)
+    {
+      Strategy::Aggro
+    } else if average_cmc >= CONTROL_MIN_AVERAGE_CMC
+      || creature_density.is_some_and(|density| density <= CONTROL_MAX_CREATURE_DENSITY)
+    {
+      Strategy::Control
+    } else {
+      Strategy::Midrange
+    };
+    let mut most_copies = nonlands.clone();
+    most_copies.sort_by(|(a, a_count), (b, b_count)| {
+      b_count
+        .cmp(a_count)
+        .then(b.cmc().cmp(&a.cmc()))
+        .then(a.name.cmp(&b.name))
+    });
+    let key_cards = most_copies
+      .iter()
+      .take(KEY_CARD_COUNT)
+      .map(|(card, _)| card.name.clone())
+      .collect();
+    (None, strategy, key_cards)
+  };
+  Archetype {
+    name,
+    strategy,
+    colors: primary_colors(&nonlands),
+    key_cards,
+    average_cmc,
+    creature_density,
+  }
+}
+
+/// Returns the colors with at least `PRIMARY_COLOR_MIN_SHARE` of the colored mana symbols
+/// of `nonlands`, most symbols first
+fn primary_colors(nonlands: &[(&Card, usize)]) -> Vec<ManaColor> {
+  let mut counts: Vec<(ManaColor, usize)> = COLORS
+    .iter()
+    .map(|color| {
+      let count = nonlands
+        .iter()
+        .map(|(card, copies)| card.mana_cost.count(*color) as usize * copies)
+        .sum();
+      (*color, count)
+    })
+    .collect();
+  let total: usize = counts.iter().map(|(_, count)| count).sum();
+  let min_count = PRIMARY_COLOR_MIN_SHARE * total as f64;
+  counts.retain(|(_, count)| *count > 0 && *count as f64 >= min_count);
+  counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+  counts.into_iter().map(|(color, _)| color).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::archetype::*;
+
+  #[test]
+  fn classify_by_curve_and_creatures() {
+    let burn = decklist!(
+      "
+      20 Mountain
+      16 Shock
+      12 Lightning Strike
+      12 Light Up the Stage
+      "
+    );
+    let archetype = classify(&burn, &[]);
+    assert_eq!(archetype.strategy, Strategy::Aggro);
+    assert_eq!(archetype.colors, vec![ManaColor::Red]);
+    assert_eq!(archetype.key_cards[0], "Shock");
+    assert_eq!(archetype.creature_density, None);
+    let control = decklist!(
+      "
+      13 Island
+      13 Swamp
+      4 Opt
+      8 Vraska's Contempt
+      8 Hero's Downfall
+      4 Murderous Rider [creature]
+      10 Sinister Sabotage
+      "
+    );
+    let archetype = classify(&control, &[]);
+    assert_eq!(archetype.strategy, Strategy::Control);
+    assert_eq!(archetype.colors.len(), 2);
+    assert_eq!(archetype.creature_density, Some(4.0 / 34.0));
+    let signatures = [Signature {
+      name: "Dimir Control".to_string(),
+      strategy: Strategy::Control,
+      cards: vec![
+        "Sinister Sabotage".to_string(),
+        "Thief of Sanity".to_string(),
+      ],
+      min_cards: 1,
+    }];
+    let archetype = classify(&control, &signatures);
+    assert_eq!(archetype.name.as_deref(), Some("Dimir Control"));
+    assert_eq!(archetype.key_cards, vec!["Sinister Sabotage"]);
+    let combo = decklist!(
+      "
+      24 Island
+      32 Opt
+      4 Sinister Sabotage [combo]
+      "
+    );
+    assert_eq!(classify(&combo, &[]).strategy, Strategy::Combo);
+  }
+}
@@ -2,14 +2,64 @@
 //!
 pub use crate::card::mana_cost::*;
 pub use crate::scryfall::{GameFormat, Legality, Object, Rarity, SetCode};
+use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+/// The name prefix of the Arena Alchemy rebalanced version of a card, e.g. "A-Alrund's Epiphany"
+pub const REBALANCED_PREFIX: &str = "A-";
+
+/// CardVersion selects between the original and the Arena Alchemy rebalanced version of cards
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardVersion {
+    Original,
+    Rebalanced,
+}
+
+/// CardClass classifies the entries of the card data. Only playable cards can be put in a
+/// deck, the other entries are game objects and printings that share the card data
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardClass {
+    Playable,
+    Token,
+    Emblem,
+    /// An art series card, with the art of a card on both faces
+    ArtCard,
+    /// A checklist, substitute card, or other entry not legal in any format
+    NonCollectible,
+}
+
+impl CardClass {
+    /// Returns true if cards of the class can be put in a deck
+    pub fn is_playable(self) -> bool {
+        self == CardClass::Playable
+    }
+}
+
+/// CardFilter selects the entries of the card data that name lookups and deck list
+/// parsing return
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardFilter {
+    /// Only playable cards
+    #[default]
+    Playable,
+    /// Every entry, including tokens, emblems, and art cards
+    All,
+}
+
+impl CardFilter {
+    /// Returns true if the filter keeps `card`
+    pub fn keeps(self, card: &Card) -> bool {
+        self == CardFilter::All || card.is_playable()
+    }
+}
+
 // TODO: [image_uri] Consider storing only the suffix and concatenate with the hostname on the UI side
 // TODO: [mana_cost_string] Remove mana_cost_string and generate the string from a ManaCost
 // TODO: [mana_cost] Remove mana_cost and use all_mana_costs[0]
 // NOTE: PartialEq and Eq are implemented below
 /// Card represents a Magic: The Gathering card
 #[derive(Default, Debug, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Card {
     /// String representing the card name
     pub name: String,
@@ -42,6 +92,7 @@ pub struct Card {
 /// CardKind represents an internal card type representation.
 /// It is a superset of the [official card types](https://mtg.gamepedia.com/Card_type)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub enum CardKind {
     // Lands
     BasicLand = 0,
@@ -80,6 +131,140 @@ impl Card {
     pub fn in_standard(&self) -> bool {
         self.set.in_standard()
     }
+
+    /// Returns true if the card is from a set that leaves Standard at the next rotation
+    pub fn rotates_next(&self) -> bool {
+        self.set.rotates_next()
+    }
+
+    /// Returns true if the card is legal in the given format, or None if the format is not
+    /// supported
+    /// NOTE: Card data does not carry per-format legalities, so only
+    /// Standard and Standard Brawl are supported (by set code)
+    pub fn is_legal_in(&self, format: &GameFormat) -> Option<bool> {
+        match format {
+            GameFormat::Standard | GameFormat::StandardBrawl => Some(self.in_standard()),
+            _ => None,
+        }
+    }
+
+    /// Returns the Scryfall id of the card printing, parsed from the image URI
+    pub fn scryfall_id(&self) -> Option<&str> {
+        let file = self.image_uri.rsplit('/').next()?;
+        let id = file.split('.').next()?;
+        if id.len() == 36 && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the face of the card image, "front" or "back", parsed from the image URI
+    pub fn image_face(&self) -> Option<&str> {
+        self.image_uri
+            .split('/')
+            .find(|segment| *segment == "front" || *segment == "back")
+    }
+
+    /// Returns the URI of the card image in the given `version`, or None if the
+    /// card has no Scryfall image
+    pub fn image_uri_for(&self, version: ImageVersion) -> Option<String> {
+        let id = self.scryfall_id()?;
+        let face = self.image_face()?;
+        let (path, extension) = version.path_and_extension();
+        Some(format!(
+            "https://cards.scryfall.io/{}/{}/{}/{}/{}.{}",
+            path,
+            face,
+            &id[0..1],
+            &id[1..2],
+            id,
+            extension
+        ))
+    }
+
+    /// Returns the Scryfall API URI of the card printing
+    pub fn scryfall_api_uri(&self) -> Option<String> {
+        self.scryfall_id()
+            .map(|id| format!("https://api.scryfall.com/cards/{}", id))
+    }
+
+    /// Returns the lowercase Scryfall set code of the card, or None if the set is unknown
+    pub fn set_code(&self) -> Option<String> {
+        match self.set {
+            SetCode::Unknown => None,
+            // Arena calls Dominaria DAR, Scryfall calls it DOM
+            SetCode::DAR => Some("dom".to_string()),
+            set => Some(set.to_string().to_lowercase()),
+        }
+    }
+
+    /// Returns true if the card is the Arena Alchemy rebalanced version of another card
+    pub fn is_rebalanced(&self) -> bool {
+        self.name.starts_with(REBALANCED_PREFIX)
+    }
+
+    /// Returns the name of the original version of the card, without the rebalanced prefix
+    /// of each face
+    pub fn original_name(&self) -> String {
+        self.name
+            .split(" // ")
+            .map(|face| face.strip_prefix(REBALANCED_PREFIX).unwrap_or(face))
+            .collect::<Vec<_>>()
+            .join(" // ")
+    }
+
+    /// Returns the name of the Arena Alchemy rebalanced version of the card
+    pub fn rebalanced_name(&self) -> String {
+        self.original_name()
+            .split(" // ")
+            .map(|face| format!("{}{}", REBALANCED_PREFIX, face))
+            .collect::<Vec<_>>()
+            .join(" // ")
+    }
+
+    /// Returns the class of the card. The card data doesn't keep type lines, so only the
+    /// classes that show in the name are found here: art cards name both faces after the
+    /// same card. Tokens and emblems are classified when the data is generated, see
+    /// `ScryfallCard::class`, and are left out of the embedded card data
+    pub fn class(&self) -> CardClass {
+        let mut faces = self.name.split(" // ");
+        match (faces.next(), faces.next()) {
+            (Some(front), Some(back)) if front == back => CardClass::ArtCard,
+            _ => CardClass::Playable,
+        }
+    }
+
+    /// Returns true if the card can be put in a deck
+    pub fn is_playable(&self) -> bool {
+        self.class().is_playable()
+    }
+}
+
+/// ImageVersion is a version of a card image on the Scryfall image CDN,
+/// see https://scryfall.com/docs/api/images
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageVersion {
+    Small,
+    Normal,
+    Large,
+    Png,
+    ArtCrop,
+    BorderCrop,
+}
+
+impl ImageVersion {
+    /// Returns the path segment and file extension of the version
+    fn path_and_extension(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Small => ("small", "jpg"),
+            Self::Normal => ("normal", "jpg"),
+            Self::Large => ("large", "jpg"),
+            Self::Png => ("png", "png"),
+            Self::ArtCrop => ("art_crop", "jpg"),
+            Self::BorderCrop => ("border_crop", "jpg"),
+        }
+    }
 }
 
 impl PartialEq for Card {
@@ -122,6 +307,115 @@ impl CardKind {
     }
 }
 
+/// CardBuilder builds a `Card` without the card database, for tests and tools with custom
+/// cards. The hash, mana costs and turn are derived the same way as for the cards of the
+/// database
+///
+/// ```
+/// use landlord::card::{CardBuilder, CardKind, ManaColor};
+///
+/// let spell = CardBuilder::new("Custom Shock").mana_cost("{R}").kind(CardKind::Instant).build();
+/// assert_eq!(spell.turn, 1);
+/// let land = CardBuilder::new("Custom Dual")
+///     .kind(CardKind::TapLand)
+///     .produces(&[ManaColor::Blue, ManaColor::Red])
+///     .build();
+/// assert!(land.is_land());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CardBuilder {
+    card: Card,
+    produces: Vec<ManaColor>,
+    turn: Option<u8>,
+}
+
+impl CardBuilder {
+    /// Returns a builder for a card named `name`, of unknown kind and without a mana cost
+    pub fn new(name: &str) -> Self {
+        Self {
+            card: Card {
+                name: name.to_string(),
+                ..Card::default()
+            },
+            produces: Vec::new(),
+            turn: None,
+        }
+    }
+
+    /// Sets the mana cost, in "{2}{R/G}{R/G}" style format. Ignored for lands
+    pub fn mana_cost(mut self, mana_cost: &str) -> Self {
+        self.card.mana_cost_string = mana_cost.to_string();
+        self
+    }
+
+    pub fn kind(mut self, kind: CardKind) -> Self {
+        self.card.kind = kind;
+        self
+    }
+
+    /// Sets the colors of mana a land taps for, `ManaColor::Colorless` for colorless mana
+    pub fn produces(mut self, colors: &[ManaColor]) -> Self {
+        self.produces = colors.to_vec();
+        self
+    }
+
+    /// Sets the turn to play the card, which defaults to its converted mana cost
+    pub fn turn(mut self, turn: u8) -> Self {
+        self.turn = Some(turn);
+        self
+    }
+
+    pub fn rarity(mut self, rarity: Rarity) -> Self {
+        self.card.rarity = rarity;
+        self
+    }
+
+    pub fn set(mut self, set: SetCode) -> Self {
+        self.card.set = set;
+        self
+    }
+
+    pub fn arena_id(mut self, arena_id: u64) -> Self {
+        self.card.arena_id = arena_id;
+        self
+    }
+
+    /// Marks the card as a sub face of a multi-faced card
+    pub fn face(mut self, is_face: bool) -> Self {
+        self.card.is_face = is_face;
+        self
+    }
+
+    pub fn build(self) -> Card {
+        let mut card = self.card;
+        if card.kind.is_land() {
+            let colors = self.produces;
+            let produces = |color| colors.contains(&color) as u8;
+            card.mana_cost = ManaCost::from_rgbuwc(
+                produces(ManaColor::Red),
+                produces(ManaColor::Green),
+                produces(ManaColor::Black),
+                produces(ManaColor::Blue),
+                produces(ManaColor::White),
+                produces(ManaColor::Colorless),
+            );
+            card.all_mana_costs = vec![card.mana_cost];
+        } else {
+            card.all_mana_costs = mana_costs_from_str(&card.mana_cost_string);
+            let first = card.all_mana_costs[0];
+            card.mana_cost =
+                ManaCost::from_rgbuwc(first.r, first.g, first.b, first.u, first.w, first.c);
+        }
+        card.turn = self
+            .turn
+            .unwrap_or_else(|| std::cmp::max(1, card.mana_cost.cmc()));
+        let mut s = DefaultHasher::new();
+        card.name.hash(&mut s);
+        card.hash = s.finish();
+        card
+    }
+}
+
 #[macro_export]
 macro_rules! card {
     ($card_name:expr) => {
@@ -135,6 +429,73 @@ macro_rules! card {
 mod tests {
     use crate::card::*;
 
+    #[test]
+    fn scryfall_uris_from_image_uri() {
+        let mut card = Card::new();
+        card.image_uri = "https://cards.scryfall.io/normal/front/f/e/fede7f75-0dff-4f3e-816d-e52b43e8b33b.jpg?1680048203".to_string();
+        card.set = SetCode::DAR;
+        assert_eq!(
+            card.scryfall_id(),
+            Some("fede7f75-0dff-4f3e-816d-e52b43e8b33b")
+        );
+        assert_eq!(card.image_face(), Some("front"));
+        assert_eq!(
+            card.image_uri_for(ImageVersion::Png).unwrap(),
+            "https://cards.scryfall.io/png/front/f/e/fede7f75-0dff-4f3e-816d-e52b43e8b33b.png"
+        );
+        assert_eq!(
+            card.scryfall_api_uri().unwrap(),
+            "https://api.scryfall.com/cards/fede7f75-0dff-4f3e-816d-e52b43e8b33b"
+        );
+        assert_eq!(card.set_code().as_deref(), Some("dom"));
+        let opt = card!("Opt");
+        assert!(opt.scryfall_id().is_some());
+        assert!(opt.image_uri_for(ImageVersion::ArtCrop).is_some());
+        assert_eq!(Card::new().scryfall_id(), None);
+        assert_eq!(Card::new().set_code(), None);
+    }
+
+    #[test]
+    fn scryfall_creatures_are_classified_from_type_line() {
+        let cards: Vec<crate::scryfall::ScryfallCard> = serde_json::from_str(
+            r#"[
+              {"name": "Llanowar Elves", "object": "card", "mana_cost": "{G}", "type_line": "Creature — Elf Druid"},
+              {"name": "Shock", "object": "card", "mana_cost": "{R}", "type_line": "Instant"}
+            ]"#,
+        )
+        .unwrap();
+        let cards: Vec<Card> = cards.into_iter().map(|c| c.into()).collect();
+        assert_eq!(cards[0].kind, CardKind::Creature);
+        assert_eq!(cards[1].kind, CardKind::Unknown);
+    }
+
+    #[test]
+    fn card_builder_matches_database() {
+        let opt = CardBuilder::new("Opt")
+            .mana_cost("{U}")
+            .kind(CardKind::Instant)
+            .build();
+        let expected = card!("Opt");
+        assert_eq!(opt.hash, expected.hash);
+        assert_eq!(opt.mana_cost, expected.mana_cost);
+        assert_eq!(opt.all_mana_costs, expected.all_mana_costs);
+        assert_eq!(opt.turn, expected.turn);
+        let vents = CardBuilder::new("Steam Vents")
+            .kind(CardKind::ShockLand)
+            .produces(&[ManaColor::Blue, ManaColor::Red])
+            .build();
+        assert!(vents.is_land());
+        assert_eq!(vents.mana_cost, card!("Steam Vents").mana_cost);
+        let carnival = CardBuilder::new("Carnival").mana_cost("{B/R}").build();
+        assert_eq!(carnival.all_mana_costs.len(), 2);
+        assert_eq!(carnival.turn, 1);
+        let late = CardBuilder::new("Late Drop")
+            .mana_cost("{1}")
+            .turn(4)
+            .build();
+        assert_eq!(late.turn, 4);
+    }
+
     #[test]
     fn card_field_of_ruin() {
         let card = card!("Field of Ruin");
@@ -1086,4 +1447,15 @@ mod tests {
         assert_eq!(card.is_land(), false);
         assert_eq!(card.kind, CardKind::Unknown);
     }
+
+    #[test]
+    fn card_classes() {
+        assert_eq!(card!("Opt").class(), CardClass::Playable);
+        assert!(card!("Fable of the Mirror-Breaker // Reflection of Kiki-Jiki").is_playable());
+        let mut art_card = Card::new();
+        art_card.name = "Opt // Opt".to_string();
+        assert_eq!(art_card.class(), CardClass::ArtCard);
+        assert!(!CardFilter::default().keeps(&art_card));
+        assert!(CardFilter::All.keeps(&art_card));
+    }
 }
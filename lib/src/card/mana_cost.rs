@@ -4,6 +4,7 @@ use std::collections::HashSet;
 #[derive(
   Default, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct ManaCost {
   pub bits: u8,
   pub r: u8,
@@ -83,6 +84,33 @@ impl ManaCost {
     self.r + self.w + self.b + self.u + self.g + self.c
   }
 
+  /// Returns the amount of `color` mana in the cost
+  pub fn count(self, color: ManaColor) -> u8 {
+    match color {
+      ManaColor::Red => self.r,
+      ManaColor::Green => self.g,
+      ManaColor::Black => self.b,
+      ManaColor::Blue => self.u,
+      ManaColor::White => self.w,
+      ManaColor::Colorless => self.c,
+    }
+  }
+
+  /// Returns the mana cost with its `color` requirement turned into generic mana,
+  /// e.g. {1}{R}{R} becomes {3} for `ManaColor::Red`
+  pub fn with_generic(mut self, color: ManaColor) -> Self {
+    let amount = match color {
+      ManaColor::Red => std::mem::replace(&mut self.r, 0),
+      ManaColor::Green => std::mem::replace(&mut self.g, 0),
+      ManaColor::Black => std::mem::replace(&mut self.b, 0),
+      ManaColor::Blue => std::mem::replace(&mut self.u, 0),
+      ManaColor::White => std::mem::replace(&mut self.w, 0),
+      ManaColor::Colorless => 0,
+    };
+    self.c += amount;
+    self.update_bits()
+  }
+
   #[inline]
   pub fn update_bits(mut self) -> Self {
     self.bits = Self::calculate_signature_rgbuwc(self.r, self.g, self.b, self.u, self.w, self.c);
@@ -225,6 +253,18 @@ mod tests {
     assert_eq!(res[0].bits, 0);
   }
 
+  #[test]
+  fn with_generic() {
+    let cost = mana_costs_from_str("{1}{R}{R}{G}")[0];
+    assert_eq!(cost.count(ManaColor::Red), 2);
+    let generic = cost.with_generic(ManaColor::Red);
+    assert_eq!(generic.count(ManaColor::Red), 0);
+    assert_eq!(generic.c, 3);
+    assert_eq!(generic.g, 1);
+    assert_eq!(generic.cmc(), cost.cmc());
+    assert_eq!(generic.bits, ManaCost::G_BITS | ManaCost::C_BITS);
+  }
+
   #[test]
   fn simple_test_0() {
     let res = mana_costs_from_str("{1}{U}");
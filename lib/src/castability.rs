@@ -0,0 +1,96 @@
+//! # Per card castability
+//!
+//! Reports, for each nonland card in a deck, how often it can be cast on curve and
+//! one turn later, along with the color requirement that most often blocks it.
+use crate::card::ManaColor;
+use crate::deck::Deck;
+use crate::simulation::{Observations, Simulation};
+
+/// CardCastability is the castability of a single nonland card, see `castability_report`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardCastability {
+  pub name: String,
+  /// The turn on which the card is on curve
+  pub turn: usize,
+  pub on_curve: Observations,
+  pub on_curve_plus_one: Observations,
+  /// The color requirement that most often keeps the card from being cast on curve,
+  /// or `None` if colors never do
+  pub blocking_color: Option<ManaColor>,
+  /// See `Simulation::color_blocks_for_card_by_turn`
+  pub color_blocks: Vec<(ManaColor, usize)>,
+}
+
+/// Returns the castability of each nonland card in `deck`, in deck order, from the
+/// hands of `sim`, which should be a simulation of `deck`
+pub fn castability_report(sim: &Simulation, deck: &Deck) -> Vec<CardCastability> {
+  deck
+    .cards
+    .iter()
+    .map(|cc| &cc.card)
+    .filter(|card| !card.is_land())
+    .map(|card| {
+      let turn = card.turn as usize;
+      let color_blocks = sim.color_blocks_for_card_by_turn(card, turn);
+      let blocking_color = color_blocks
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map(|(color, _)| *color);
+      CardCastability {
+        name: card.name.clone(),
+        turn,
+        on_curve: sim.observations_for_card_by_turn(card, turn),
+        on_curve_plus_one: sim.observations_for_card_by_turn(card, turn + 1),
+        blocking_color,
+        color_blocks,
+      }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::castability::*;
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::Never;
+  use crate::simulation::*;
+
+  #[test]
+  fn castability_blocked_by_black() {
+    let deck = decklist!(
+      "
+      4 Vraska's Contempt
+      4 Opt
+      16 Island
+      4 Swamp
+      32 Ornithopter
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 2000,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(5),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let report = castability_report(&sim, &deck);
+    assert_eq!(report.len(), 3);
+    let contempt = report
+      .iter()
+      .find(|c| c.name == "Vraska's Contempt")
+      .unwrap();
+    assert_eq!(contempt.turn, 4);
+    assert!(contempt.on_curve_plus_one.mana >= contempt.on_curve.mana);
+    assert_eq!(contempt.blocking_color, Some(ManaColor::Black));
+    // Opt only needs a single blue source, which the lands almost always provide
+    let opt = report.iter().find(|c| c.name == "Opt").unwrap();
+    assert_eq!(opt.color_blocks.len(), 1);
+    let ornithopter = report.iter().find(|c| c.name == "Ornithopter").unwrap();
+    assert!(ornithopter.color_blocks.is_empty());
+    assert_eq!(ornithopter.blocking_color, None);
+  }
+}
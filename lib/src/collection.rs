@@ -1,11 +1,14 @@
 //! # Collection
 //!
-use crate::card::{Card, SetCode};
-use std::collections::HashMap;
+use crate::card::{Card, CardFilter, CardVersion, GameFormat, Rarity, SetCode};
+use crate::deck::{Deck, DeckBuilder};
+use crate::diagnostics::{DiagnosticKind, Diagnostics};
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 
 /// A Collection represents a deck or a library of cards
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Collection {
   pub cards: Vec<Card>,
 }
@@ -54,6 +57,61 @@ impl Collection {
     m
   }
 
+  /// Returns the deck of the cards with the Arena ids of `counts`, pairs of Arena id and
+  /// number of copies such as the collection of an Arena account, in the given `version`
+  /// when the collection has it. Arena ids without a card are skipped and reported in the
+  /// diagnostics, e.g. the ids of cards released after the card data
+  pub fn deck_from_arena_counts(
+    &self,
+    counts: &[(u64, usize)],
+    version: CardVersion,
+  ) -> (Deck, Diagnostics) {
+    let by_arena_id = self.group_by_arena_id();
+    let mut builder = DeckBuilder::new();
+    let mut diagnostics = Diagnostics::new();
+    for (arena_id, count) in counts {
+      let card = by_arena_id
+        .get(arena_id)
+        .filter(|_| *arena_id != 0)
+        .map(|card| self.card_version(card, version).unwrap_or(card));
+      match card {
+        Some(card) => builder = builder.insert_count(card.clone(), *count),
+        None => diagnostics.push(
+          DiagnosticKind::UnknownArenaId,
+          None,
+          format!("Unknown Arena id {} ({} copies)", arena_id, count),
+        ),
+      }
+    }
+    (builder.build(), diagnostics)
+  }
+
+  /// Returns the Arena ids of the cards on Arena keyed by Scryfall id, see `Card::scryfall_id`,
+  /// the reverse of `card_from_arena_id` for companion apps that drive the Arena client
+  pub fn arena_ids_by_scryfall_id(&self) -> HashMap<&str, u64> {
+    let mut m = HashMap::new();
+    for card in self.cards.iter().filter(|card| card.arena_id != 0) {
+      if let Some(id) = card.scryfall_id() {
+        m.insert(id, card.arena_id);
+      }
+    }
+    m
+  }
+
+  /// Returns the Arena id of the card named `name`, or None if the card is not on Arena
+  pub fn arena_id_from_name(&self, name: &str) -> Option<u64> {
+    let card = self.card_from_name(name)?;
+    if card.arena_id != 0 {
+      return Some(card.arena_id);
+    }
+    // Other printings of the card may be on Arena
+    self
+      .cards
+      .iter()
+      .find(|other| other.name == card.name && other.arena_id != 0)
+      .map(|other| other.arena_id)
+  }
+
   /// Returns a new collection of cards
   pub fn from_cards(mut cards: Vec<Card>) -> Self {
     // sort for binary_search used in card_from_name
@@ -62,14 +120,273 @@ impl Collection {
     Self { cards }
   }
 
-  /// Returns a card from the card name
+  /// Returns a playable card from the card name. With the `localization` feature, the printed
+  /// names of the installed localized names resolve to their English cards, see `LocalizedNames`
   pub fn card_from_name(&self, name: &str) -> Option<&Card> {
+    self.card_from_name_with_filter(name, CardFilter::Playable)
+  }
+
+  /// Returns a card from the card name, if `filter` keeps it
+  pub fn card_from_name_with_filter(&self, name: &str, filter: CardFilter) -> Option<&Card> {
+    let card = self.card_from_english_name(name);
+    #[cfg(feature = "localization")]
+    let card = card.or_else(|| {
+      let english_name = crate::localization::installed_english_name(name)?;
+      self.card_from_english_name(&english_name)
+    });
+    card.filter(|card| filter.keeps(card))
+  }
+
+  /// Returns a card from the English card name
+  fn card_from_english_name(&self, name: &str) -> Option<&Card> {
     let name_lowercase = name.to_lowercase();
     let res = self
       .cards
       .binary_search_by(|probe| probe.name.to_lowercase().cmp(&name_lowercase));
     res.map(|idx| &self.cards[idx]).ok()
   }
+
+  /// Returns the `version` of `card`, or None if the collection doesn't have it
+  pub fn card_version(&self, card: &Card, version: CardVersion) -> Option<&Card> {
+    match version {
+      CardVersion::Original => self.card_from_name(&card.original_name()),
+      CardVersion::Rebalanced => self.card_from_name(&card.rebalanced_name()),
+    }
+  }
+
+  /// Returns the card with the Arena id `arena_id`, in the given `version` when the
+  /// collection has it. Rebalanced cards have their own Arena ids, so either id of a card
+  /// finds both of its versions
+  pub fn card_from_arena_id(&self, arena_id: u64, version: CardVersion) -> Option<&Card> {
+    let card = self
+      .cards
+      .iter()
+      .find(|card| card.arena_id == arena_id && arena_id != 0)?;
+    Some(self.card_version(card, version).unwrap_or(card))
+  }
+
+  /// Returns up to `limit` playable cards whose name contains `query`, ignoring case,
+  /// with names that start with `query` first. Cards with the same name are only returned once
+  pub fn search(&self, query: &str, limit: usize) -> Vec<&Card> {
+    let query = query.to_lowercase();
+    let mut prefixed = Vec::new();
+    let mut contained = Vec::new();
+    let mut seen_names = HashSet::new();
+    for card in self.cards.iter().filter(|card| card.is_playable()) {
+      let name = card.name.to_lowercase();
+      if !name.contains(&query) || !seen_names.insert(&card.name) {
+        continue;
+      }
+      if name.starts_with(&query) {
+        prefixed.push(card);
+      } else {
+        contained.push(card);
+      }
+    }
+    prefixed.extend(contained);
+    prefixed.truncate(limit);
+    prefixed
+  }
+
+  /// Returns up to `limit` playable card names closest to `name` by edit distance, ignoring case,
+  /// for "did you mean" suggestions when `name` doesn't resolve. Only names within
+  /// `MAX_SUGGESTION_DISTANCE` edits per 4 characters of `name` are returned, closest first
+  pub fn suggest_names(&self, name: &str, limit: usize) -> Vec<&str> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let max_distance = std::cmp::max(1, MAX_SUGGESTION_DISTANCE * name.len() / 4);
+    let mut seen_names = HashSet::new();
+    let mut suggestions: Vec<(usize, &str)> = self
+      .cards
+      .iter()
+      .filter(|card| card.is_playable() && seen_names.insert(&card.name))
+      .filter_map(|card| {
+        let candidate: Vec<char> = card.name.to_lowercase().chars().collect();
+        let distance = edit_distance(&name, &candidate, max_distance)?;
+        Some((distance, card.name.as_str()))
+      })
+      .collect();
+    suggestions.sort();
+    suggestions.truncate(limit);
+    suggestions.into_iter().map(|(_, name)| name).collect()
+  }
+
+  /// Returns the percentage (0 to 100) of rare and mythic playsets completed by `owned`,
+  /// considering only cards in the collection that are legal in `format`.
+  /// Each card contributes up to 4 owned copies toward its playset.
+  /// Returns an error for the formats `Card::is_legal_in` does not support
+  pub fn playset_completion(&self, owned: &Deck, format: GameFormat) -> Result<f64, String> {
+    const PLAYSET_SIZE: usize = 4;
+    let mut playsets = 0;
+    let mut owned_copies = 0;
+    for card in &self.cards {
+      // Ignore card faces, which duplicate the parent card object
+      if card.is_face {
+        continue;
+      }
+      if card.rarity != Rarity::Rare && card.rarity != Rarity::Mythic {
+        continue;
+      }
+      let is_legal = card
+        .is_legal_in(&format)
+        .ok_or_else(|| format!("Legality in {:?} is not supported", format))?;
+      if !is_legal {
+        continue;
+      }
+      playsets += 1;
+      let count = owned
+        .card_count_from_name(&card.name)
+        .map_or(0, |cc| cc.count);
+      owned_copies += std::cmp::min(count, PLAYSET_SIZE);
+    }
+    if playsets == 0 {
+      return Ok(0.0);
+    }
+    Ok(100.0 * owned_copies as f64 / (playsets * PLAYSET_SIZE) as f64)
+  }
+
+  /// Returns the collection after the next Standard rotation, without the cards from
+  /// rotating sets, see `SetCode::rotates_next`
+  pub fn post_rotation(&self) -> Collection {
+    Self {
+      cards: self
+        .cards
+        .iter()
+        .filter(|card| !card.rotates_next())
+        .cloned()
+        .collect(),
+    }
+  }
+
+  /// Returns the collection as CSV, with one row per card and set and the columns
+  /// name, set, collector_number, quantity and foil. Each copy of a card in the
+  /// collection counts towards its quantity
+  pub fn to_csv(&self) -> String {
+    self.write_csv(
+      "name,set,collector_number,quantity,foil",
+      |card, quantity| {
+        vec![
+          card.name.clone(),
+          set_code(card),
+          String::new(),
+          quantity.to_string(),
+          "false".to_string(),
+        ]
+      },
+    )
+  }
+
+  /// Returns the collection in the [Deckbox](https://deckbox.org) CSV import format,
+  /// which names the edition of each card by its full set name, see `Collection::to_csv`
+  pub fn to_deckbox_csv(&self) -> String {
+    self.write_csv("Count,Name,Edition,Card Number,Foil", |card, quantity| {
+      vec![
+        quantity.to_string(),
+        card.name.clone(),
+        card.set.name().to_string(),
+        String::new(),
+        String::new(),
+      ]
+    })
+  }
+
+  /// Returns the collection in the [MTGGoldfish](https://www.mtggoldfish.com) CSV import
+  /// format, see `Collection::to_csv`
+  pub fn to_mtggoldfish_csv(&self) -> String {
+    self.write_csv("Card,Set ID,Set Name,Quantity,Foil", |card, quantity| {
+      vec![
+        card.name.clone(),
+        set_code(card),
+        card.set.name().to_string(),
+        quantity.to_string(),
+        "REGULAR".to_string(),
+      ]
+    })
+  }
+
+  /// Returns a CSV document with the `header` line and a row of `columns` for each card
+  /// and set in the collection, in name order. Collector numbers and finishes are not part
+  /// of the card data, so the writers leave them empty or nonfoil
+  fn write_csv<F>(&self, header: &str, columns: F) -> String
+  where
+    F: Fn(&Card, usize) -> Vec<String>,
+  {
+    let mut quantities: HashMap<(&String, SetCode), (&Card, usize)> = HashMap::new();
+    for card in self.cards.iter().filter(|card| !card.is_face) {
+      quantities
+        .entry((&card.name, card.set))
+        .or_insert((card, 0))
+        .1 += 1;
+    }
+    let mut quantities: Vec<(&Card, usize)> = quantities.values().copied().collect();
+    quantities.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name).then(a.set.cmp(&b.set)));
+    let mut csv = String::new();
+    csv.push_str(header);
+    csv.push('\n');
+    for (card, quantity) in quantities {
+      let row: Vec<String> = columns(card, quantity)
+        .iter()
+        .map(|column| csv_field(column))
+        .collect();
+      csv.push_str(&row.join(","));
+      csv.push('\n');
+    }
+    csv
+  }
+}
+
+/// The most edits per 4 characters of a name for a suggestion, see `Collection::suggest_names`
+const MAX_SUGGESTION_DISTANCE: usize = 1;
+
+/// Returns the edit distance between `a` and `b`, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters, or None if it's over `max_distance`
+fn edit_distance(a: &[char], b: &[char], max_distance: usize) -> Option<usize> {
+  let length_difference = if a.len() > b.len() {
+    a.len() - b.len()
+  } else {
+    b.len() - a.len()
+  };
+  if length_difference > max_distance {
+    return None;
+  }
+  let mut before_previous = vec![0; b.len() + 1];
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  let mut current = vec![0; b.len() + 1];
+  for i in 0..a.len() {
+    current[0] = i + 1;
+    for j in 0..b.len() {
+      let substitution = previous[j] + (a[i] != b[j]) as usize;
+      let mut distance =
+        std::cmp::min(substitution, std::cmp::min(previous[j + 1], current[j]) + 1);
+      if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+        distance = std::cmp::min(distance, before_previous[j - 1] + 1);
+      }
+      current[j + 1] = distance;
+    }
+    if current.iter().min().copied().unwrap_or(0) > max_distance {
+      return None;
+    }
+    std::mem::swap(&mut before_previous, &mut previous);
+    std::mem::swap(&mut previous, &mut current);
+  }
+  Some(previous[b.len()]).filter(|distance| *distance <= max_distance)
+}
+
+/// Returns the set code of `card`, or an empty string if the set is unknown
+fn set_code(card: &Card) -> String {
+  if card.set == SetCode::Unknown {
+    String::new()
+  } else {
+    card.set.to_string()
+  }
+}
+
+/// Returns `field` quoted when it contains a comma, a quote or a line break
+fn csv_field(field: &str) -> String {
+  if field.contains(&[',', '"', '\n'][..]) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
 }
 
 impl Deref for Collection {
@@ -81,4 +398,252 @@ impl Deref for Collection {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+  use crate::collection::*;
+
+  #[test]
+  fn search_by_name() {
+    use crate::data::ALL_CARDS;
+    let results = ALL_CARDS.search("lightning bolt", 5);
+    assert_eq!(results[0].name, "Lightning Bolt");
+    let results = ALL_CARDS.search("BOLT", 50);
+    assert!(results.iter().any(|c| c.name == "Lightning Bolt"));
+    assert!(results
+      .iter()
+      .all(|c| c.name.to_lowercase().contains("bolt")));
+    let mut names: Vec<&String> = results.iter().map(|c| &c.name).collect();
+    names.dedup();
+    assert_eq!(names.len(), results.len());
+    assert_eq!(ALL_CARDS.search("bolt", 2).len(), 2);
+    assert!(ALL_CARDS.search("no card has this name", 5).is_empty());
+  }
+
+  #[test]
+  fn deck_from_arena_counts_reports_unknown_ids() {
+    use crate::data::ALL_CARDS;
+    let opt = card!("Opt");
+    let (deck, diagnostics) =
+      ALL_CARDS.deck_from_arena_counts(&[(opt.arena_id, 4), (1, 2)], CardVersion::Original);
+    assert_eq!(deck.card_count_from_name("Opt").unwrap().count, 4);
+    assert_eq!(deck.len(), 4);
+    let unknown = diagnostics.of_kind(DiagnosticKind::UnknownArenaId);
+    assert_eq!(unknown.len(), 1);
+    assert_eq!(unknown[0].message, "Unknown Arena id 1 (2 copies)");
+  }
+
+  #[test]
+  fn arena_ids_from_names_and_scryfall_ids() {
+    use crate::data::ALL_CARDS;
+    let opt = card!("Opt");
+    assert_ne!(opt.arena_id, 0);
+    assert_eq!(ALL_CARDS.arena_id_from_name("opt"), Some(opt.arena_id));
+    assert_eq!(ALL_CARDS.arena_id_from_name("Not A Card"), None);
+    let by_scryfall_id = ALL_CARDS.arena_ids_by_scryfall_id();
+    assert_eq!(by_scryfall_id[opt.scryfall_id().unwrap()], opt.arena_id);
+    assert_eq!(
+      ALL_CARDS
+        .card_from_arena_id(opt.arena_id, CardVersion::Original)
+        .unwrap()
+        .name,
+      "Opt"
+    );
+  }
+
+  #[test]
+  fn rebalanced_card_versions() {
+    use crate::data::ALL_CARDS;
+    let epiphany = card!("Alrund's Epiphany");
+    assert!(!epiphany.is_rebalanced());
+    let rebalanced = ALL_CARDS
+      .card_version(epiphany, CardVersion::Rebalanced)
+      .unwrap();
+    assert_eq!(rebalanced.name, "A-Alrund's Epiphany");
+    assert!(rebalanced.is_rebalanced());
+    assert_ne!(rebalanced.arena_id, epiphany.arena_id);
+    assert_eq!(
+      ALL_CARDS
+        .card_version(rebalanced, CardVersion::Original)
+        .unwrap()
+        .name,
+      epiphany.name
+    );
+    assert_eq!(
+      ALL_CARDS
+        .card_from_arena_id(rebalanced.arena_id, CardVersion::Original)
+        .unwrap()
+        .name,
+      epiphany.name
+    );
+    assert_eq!(
+      ALL_CARDS
+        .card_from_arena_id(epiphany.arena_id, CardVersion::Rebalanced)
+        .unwrap()
+        .name,
+      rebalanced.name
+    );
+    // Cards without a rebalanced version keep their original version
+    let opt = card!("Opt");
+    assert!(ALL_CARDS
+      .card_version(opt, CardVersion::Rebalanced)
+      .is_none());
+    assert_eq!(
+      ALL_CARDS
+        .card_from_arena_id(opt.arena_id, CardVersion::Rebalanced)
+        .unwrap()
+        .name,
+      "Opt"
+    );
+  }
+
+  #[test]
+  fn nonplayable_cards_are_filtered() {
+    let mut art_card = Card::new();
+    art_card.name = "Opt // Opt".to_string();
+    let mut opt = Card::new();
+    opt.name = "Opt".to_string();
+    let collection = Collection::from_cards(vec![art_card, opt]);
+    assert!(collection.card_from_name("opt // opt").is_none());
+    assert!(collection
+      .card_from_name_with_filter("opt // opt", CardFilter::All)
+      .is_some());
+    assert_eq!(collection.search("opt", 5).len(), 1);
+    assert_eq!(
+      collection.suggest_names("Opt // Opp", 5),
+      Vec::<&str>::new()
+    );
+  }
+
+  #[test]
+  fn suggest_names_for_typos() {
+    use crate::data::ALL_CARDS;
+    assert_eq!(
+      ALL_CARDS.suggest_names("Thoughtsieze", 3)[0],
+      "Thoughtseize"
+    );
+    assert!(ALL_CARDS
+      .suggest_names("lightning blot", 3)
+      .contains(&"Lightning Bolt"));
+    assert!(ALL_CARDS
+      .suggest_names("no card has this name", 3)
+      .is_empty());
+    assert_eq!(edit_distance(&['a', 'b'], &['b', 'a'], 2), Some(1));
+    assert_eq!(edit_distance(&['a', 'b', 'c'], &['a', 'c'], 1), Some(1));
+    assert_eq!(edit_distance(&['a', 'b', 'c'], &['x', 'y', 'z'], 2), None);
+  }
+
+  #[test]
+  fn collection_to_csv() {
+    let opt = card!("Opt");
+    let jace = card!("Jace, the Mind Sculptor");
+    let collection =
+      Collection::from_cards(vec![opt.clone(), jace.clone(), opt.clone(), opt.clone()]);
+    let opt_set = set_code(opt);
+    let jace_set = set_code(jace);
+    assert_eq!(
+      collection.to_csv(),
+      format!(
+        "name,set,collector_number,quantity,foil\n\"Jace, the Mind Sculptor\",{},,1,false\nOpt,{},,3,false\n",
+        jace_set, opt_set
+      )
+    );
+    assert_eq!(
+      collection.to_deckbox_csv().lines().nth(2).unwrap(),
+      format!("3,Opt,{},,", opt.set.name())
+    );
+    assert_eq!(
+      collection.to_mtggoldfish_csv().lines().next().unwrap(),
+      "Card,Set ID,Set Name,Quantity,Foil"
+    );
+    let ambusher = Collection::from_cards(vec![card!("Nightpack Ambusher").clone()]);
+    assert_eq!(
+      ambusher.to_deckbox_csv().lines().nth(1).unwrap(),
+      "1,Nightpack Ambusher,Core Set 2020,,"
+    );
+    assert_eq!(
+      ambusher.to_mtggoldfish_csv().lines().nth(1).unwrap(),
+      "Nightpack Ambusher,M20,Core Set 2020,1,REGULAR"
+    );
+    assert_eq!(Collection::default().to_csv().lines().count(), 1);
+    assert_eq!(csv_field("a \"b\""), "\"a \"\"b\"\"\"");
+  }
+
+  #[test]
+  fn collection_post_rotation() {
+    let collection = Collection::from_cards(vec![
+      card!("Nightpack Ambusher").clone(),
+      card!("Uro, Titan of Nature's Wrath").clone(),
+      card!("Opt").clone(),
+    ]);
+    let post_rotation = collection.post_rotation();
+    assert!(post_rotation.card_from_name("Nightpack Ambusher").is_none());
+    assert!(post_rotation
+      .card_from_name("Uro, Titan of Nature's Wrath")
+      .is_some());
+    assert!(post_rotation.iter().all(|card| !card.set.rotates_next()));
+  }
+
+  #[test]
+  fn playset_completion_0() {
+    let collection = Collection::from_cards(vec![
+      card!("Nightpack Ambusher").clone(),
+      card!("Questing Beast").clone(),
+      card!("Teferi, Hero of Dominaria").clone(),
+      card!("Opt").clone(),
+    ]);
+    let owned = decklist!(
+      "
+      4 Nightpack Ambusher
+      2 Questing Beast
+      4 Teferi, Hero of Dominaria
+      4 Opt
+      "
+    );
+    // Teferi, Hero of Dominaria has rotated out of Standard and Opt is not rare
+    let completion = collection
+      .playset_completion(&owned, GameFormat::Standard)
+      .unwrap();
+    assert!(f64::abs(completion - 75.0) < 0.0001);
+  }
+
+  #[test]
+  fn playset_completion_caps_extra_copies() {
+    let collection = Collection::from_cards(vec![
+      card!("Nightpack Ambusher").clone(),
+      card!("Questing Beast").clone(),
+    ]);
+    let owned = decklist!(
+      "
+      9 Nightpack Ambusher
+      "
+    );
+    let completion = collection
+      .playset_completion(&owned, GameFormat::Standard)
+      .unwrap();
+    assert!(f64::abs(completion - 50.0) < 0.0001);
+  }
+
+  #[test]
+  fn playset_completion_empty() {
+    let collection = Collection::from_cards(vec![card!("Opt").clone()]);
+    let owned = decklist!("4 Opt");
+    assert_eq!(
+      collection.playset_completion(&owned, GameFormat::Standard),
+      Ok(0.0)
+    );
+  }
+
+  #[test]
+  fn playset_completion_unsupported_format() {
+    let collection = Collection::from_cards(vec![card!("Questing Beast").clone()]);
+    let owned = decklist!("4 Questing Beast");
+    assert!(collection
+      .playset_completion(&owned, GameFormat::Vintage)
+      .is_err());
+  }
+
+  #[test]
+  fn collection_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Collection>();
+  }
+}
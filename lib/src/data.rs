@@ -1,18 +1,122 @@
+use crate::card::{Card, SetCode};
 use crate::collection::Collection;
 use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
 use std::io::prelude::*;
+use std::sync::Mutex;
+
+/// The embedded card database. The `arena-only` and `minimal-fields` features embed the
+/// smaller databases generated with the scryfall2landlord flags of the same names instead,
+/// and fail the build until they are generated, see lib/build.rs and bins/card-update.sh.
+/// The tests expect the full database
+const ALL_CARDS_DATA: &[u8] = include_bytes!(env!("LANDLORD_CARD_DATA"));
 
 /// Returns a new collection of all cards from data/all_cards.landlord
 pub fn all_cards() -> Result<Collection, bincode::Error> {
-    let b = include_bytes!("../../data/all_cards.landlord");
-    let mut gz = GzDecoder::new(&b[..]);
+    let mut gz = GzDecoder::new(ALL_CARDS_DATA);
     let mut s: Vec<u8> = Vec::new();
     gz.read_to_end(&mut s).expect("gz decode failed");
     bincode::deserialize(&s)
 }
 
 lazy_static! {
-    pub static ref ALL_CARDS: Collection = all_cards().expect("all_cards() failed");
+    /// The cards of a finished `CardLoader`, used by `ALL_CARDS` instead of decoding them again
+    static ref LOADED_CARDS: Mutex<Option<Collection>> = Mutex::new(None);
+    pub static ref ALL_CARDS: Collection = match LOADED_CARDS.lock().expect("poisoned").take() {
+        Some(collection) => collection,
+        None => all_cards().expect("all_cards() failed"),
+    };
+}
+
+/// CardLoader decodes the card database a few cards at a time, so that consumers like
+/// web pages can report progress and stay responsive between chunks instead of blocking
+/// on a single decode of every card
+pub struct CardLoader {
+    decoder: GzDecoder<&'static [u8]>,
+    total: usize,
+    cards: Vec<Card>,
+}
+
+impl CardLoader {
+    /// Returns a new loader of the card database from data/all_cards.landlord
+    pub fn new() -> Result<Self, bincode::Error> {
+        let mut decoder = GzDecoder::new(ALL_CARDS_DATA);
+        // A collection is serialized as its length followed by each card
+        let total: u64 = bincode::deserialize_from(&mut decoder)?;
+        Ok(Self {
+            decoder,
+            total: total as usize,
+            cards: Vec::with_capacity(total as usize),
+        })
+    }
+
+    /// Decodes up to `count` more cards, and returns them grouped by set
+    pub fn next_chunk(
+        &mut self,
+        count: usize,
+    ) -> Result<BTreeMap<SetCode, Vec<&Card>>, bincode::Error> {
+        let start = self.cards.len();
+        let end = std::cmp::min(self.total, start + count);
+        for _ in start..end {
+            let card: Card = bincode::deserialize_from(&mut self.decoder)?;
+            self.cards.push(card);
+        }
+        let mut chunk = BTreeMap::new();
+        for card in &self.cards[start..end] {
+            chunk.entry(card.set).or_insert_with(Vec::new).push(card);
+        }
+        Ok(chunk)
+    }
+
+    /// Decodes every remaining card, calling `on_progress` with the number of cards loaded
+    /// and the total number of cards after each chunk of `chunk_size` cards
+    pub fn load_with_progress<F>(
+        &mut self,
+        chunk_size: usize,
+        mut on_progress: F,
+    ) -> Result<(), bincode::Error>
+    where
+        F: FnMut(usize, usize),
+    {
+        assert!(chunk_size > 0);
+        while !self.is_done() {
+            self.next_chunk(chunk_size)?;
+            on_progress(self.loaded(), self.total());
+        }
+        Ok(())
+    }
+
+    /// Returns the number of cards decoded so far
+    pub fn loaded(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns the number of cards in the database
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns true once every card is decoded
+    pub fn is_done(&self) -> bool {
+        self.loaded() == self.total
+    }
+
+    /// Returns the collection of the cards decoded so far
+    pub fn into_collection(self) -> Collection {
+        Collection { cards: self.cards }
+    }
+
+    /// Makes the decoded cards the cards of `ALL_CARDS`, so they aren't decoded again, and
+    /// returns true, or returns false if the loader isn't done or `ALL_CARDS` was already used
+    pub fn install(self) -> bool {
+        if !self.is_done() {
+            return false;
+        }
+        *LOADED_CARDS.lock().expect("poisoned") = Some(self.into_collection());
+        lazy_static::initialize(&ALL_CARDS);
+        // The cards are left over if ALL_CARDS was initialized before
+        LOADED_CARDS.lock().expect("poisoned").take().is_none()
+    }
 }
 
 #[cfg(test)]
@@ -33,4 +137,31 @@ mod tests {
         deduped.cards.dedup();
         assert_eq!(deduped.cards.len(), ALL_CARDS.len());
     }
+
+    #[test]
+    fn all_cards_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: T) {}
+        assert_send_sync(&*ALL_CARDS);
+    }
+
+    #[test]
+    fn card_loader_decodes_in_chunks() {
+        let mut loader = CardLoader::new().unwrap();
+        assert_eq!(loader.total(), ALL_CARDS.len());
+        let chunk = loader.next_chunk(100).unwrap();
+        assert_eq!(chunk.values().map(Vec::len).sum::<usize>(), 100);
+        assert!(chunk
+            .iter()
+            .all(|(set, cards)| cards.iter().all(|c| c.set == *set)));
+        let mut progress = Vec::new();
+        loader
+            .load_with_progress(5000, |loaded, total| progress.push((loaded, total)))
+            .unwrap();
+        assert!(loader.is_done());
+        assert_eq!(progress.last(), Some(&(ALL_CARDS.len(), ALL_CARDS.len())));
+        assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(loader.next_chunk(100).unwrap().is_empty());
+        // ALL_CARDS was already decoded by this point
+        assert!(!loader.install());
+    }
 }
@@ -1,24 +1,79 @@
 use crate::card::*;
+use crate::collection::Collection;
 use crate::data::*;
+use crate::diagnostics::{DiagnosticKind, Diagnostics};
+use crate::hand::{Cantrip, DrawEngine, ManaSource, PlayOrder};
+use crate::probability;
+use crate::store::Wildcards;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Deref;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Deck {
   pub title: Option<String>,
   pub url: Option<String>,
   pub cards: Vec<DeckCard>,
   pub format: GameFormat,
   pub card_count: usize,
+  /// Nonland cards that produce mana, see `Deck::add_mana_source`
+  #[serde(default)]
+  pub mana_sources: Vec<ManaSource>,
+  /// Nonland cards that draw additional cards every turn, see `Deck::add_draw_engine`
+  #[serde(default)]
+  pub draw_engines: Vec<DrawEngine>,
+  /// Nonland cards that dig through the library, see `Deck::add_cantrip`
+  #[serde(default)]
+  pub cantrips: Vec<Cantrip>,
+  /// Cards in the command zone, parsed from the "Commander" section of a deck list.
+  /// Commanders are not part of `cards`
+  #[serde(default)]
+  pub commanders: Vec<Card>,
+  /// User tags, like "removal" or "wincons", mapped to the hashes of the cards with the tag.
+  /// Tags are parsed from the brackets of a deck list line, e.g. `4 Shock [removal, burn]`
+  #[serde(default)]
+  pub tags: BTreeMap<String, Vec<u64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct DeckCard {
   pub card: Card,
   pub count: usize,
 }
 
+/// CraftOptions configures how `Deck::missing_cards` counts owned copies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CraftOptions {
+  /// Counts at most this many owned copies of each card, e.g. 4 or `GameFormat::max_copies`,
+  /// for collections that report copies beyond the cap. None counts every owned copy
+  #[serde(default)]
+  pub owned_cap: Option<usize>,
+  /// Cards needed in addition to the deck, e.g. for sideboard plans
+  #[serde(default)]
+  pub extras: Option<Deck>,
+}
+
+/// CountChange is a card whose number of copies differs between two decks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountChange {
+  pub name: String,
+  pub from: usize,
+  pub to: usize,
+}
+
+/// DeckDiff is the difference from a deck to another, see `Deck::diff`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckDiff {
+  /// The copies of the cards the other deck has more of
+  pub added: Deck,
+  /// The copies of the cards the other deck has fewer of
+  pub removed: Deck,
+  /// The cards in both decks with a different number of copies, in name order
+  pub changed: Vec<CountChange>,
+}
+
 #[derive(Debug, Clone)]
 pub struct DeckBuilder {
   pub cards: HashMap<Card, usize>,
@@ -60,9 +115,124 @@ impl DeckBuilder {
   }
 }
 
+/// ArenaDeck is a deck as Arena card ids, the grpIds of the Arena client, see `Deck::to_arena_ids`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArenaDeck {
+  pub commanders: Vec<u64>,
+  /// The Arena id and number of copies of each card, in deck order
+  pub cards: Vec<(u64, usize)>,
+}
+
+/// The number of suggested names of an `UnknownCard`
+const UNKNOWN_CARD_SUGGESTION_COUNT: usize = 3;
+
+/// The bits of the five colors in `ManaCost::bits`, without colorless
+const COLOR_BITS: u8 =
+  ManaCost::R_BITS | ManaCost::G_BITS | ManaCost::B_BITS | ManaCost::U_BITS | ManaCost::W_BITS;
+
 #[derive(Debug)]
 pub struct DeckcodeError(pub String);
 
+/// UnknownCard is a card name of a deck list that doesn't resolve, with the closest names
+/// of the collection, see `Collection::suggest_names`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnknownCard {
+  pub name: String,
+  pub suggestions: Vec<String>,
+}
+
+impl UnknownCard {
+  /// Returns the unknown card `name`, with suggestions from `collection`
+  pub fn new(name: &str, collection: &Collection) -> Self {
+    Self {
+      name: name.to_string(),
+      suggestions: collection
+        .suggest_names(name, UNKNOWN_CARD_SUGGESTION_COUNT)
+        .into_iter()
+        .map(String::from)
+        .collect(),
+    }
+  }
+}
+
+impl From<UnknownCard> for DeckcodeError {
+  fn from(unknown: UnknownCard) -> Self {
+    let mut message = format!("Cannot find card named \"{}\" in collection", unknown.name);
+    if !unknown.suggestions.is_empty() {
+      let quoted: Vec<String> = unknown
+        .suggestions
+        .iter()
+        .map(|s| format!("\"{}\"", s))
+        .collect();
+      message.push_str(&format!(", did you mean {}?", quoted.join(" or ")));
+    }
+    DeckcodeError(message)
+  }
+}
+
+/// DeckListError is the error of `Deck::try_from_list`, which keeps the unknown card
+/// of a name that doesn't resolve apart from the other deck list errors
+#[derive(Debug)]
+pub enum DeckListError {
+  UnknownCard(UnknownCard),
+  Deckcode(DeckcodeError),
+}
+
+impl From<DeckcodeError> for DeckListError {
+  fn from(error: DeckcodeError) -> Self {
+    DeckListError::Deckcode(error)
+  }
+}
+
+impl From<DeckListError> for DeckcodeError {
+  fn from(error: DeckListError) -> Self {
+    match error {
+      DeckListError::UnknownCard(unknown) => unknown.into(),
+      DeckListError::Deckcode(error) => error,
+    }
+  }
+}
+
+/// ComboDraws describes how soon a deck assembles a combo, see `Deck::combo_draws`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ComboDraws {
+  /// The probability to have assembled the combo among the opening hand and the next
+  /// `i` draws, indexed by `i`
+  pub p_assembled: Vec<f64>,
+  /// The expected number of draws after the opening hand to assemble the combo,
+  /// or None if the deck cannot assemble it
+  pub expected_draws: Option<f64>,
+}
+
+/// PipCount counts the colored mana symbols in the mana costs of cards, see `Deck::pip_counts`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipCount {
+  pub w: usize,
+  pub u: usize,
+  pub b: usize,
+  pub r: usize,
+  pub g: usize,
+}
+
+impl PipCount {
+  /// Adds the colored mana symbols of `copies` cards with `mana_cost`
+  pub fn add(&mut self, mana_cost: &ManaCost, copies: usize) {
+    self.w += mana_cost.w as usize * copies;
+    self.u += mana_cost.u as usize * copies;
+    self.b += mana_cost.b as usize * copies;
+    self.r += mana_cost.r as usize * copies;
+    self.g += mana_cost.g as usize * copies;
+  }
+
+  /// Returns the total number of colored mana symbols
+  pub fn total(&self) -> usize {
+    self.w + self.u + self.b + self.r + self.g
+  }
+}
+
+/// A predicate that selects cards, see `Deck::p_at_least_each`
+pub type CardPredicate<'a> = &'a dyn Fn(&Card) -> bool;
+
 impl Deck {
   pub fn new() -> Self {
     Self {
@@ -71,6 +241,11 @@ impl Deck {
       cards: Vec::with_capacity(20),
       format: GameFormat::Standard,
       card_count: 0,
+      mana_sources: Vec::new(),
+      draw_engines: Vec::new(),
+      cantrips: Vec::new(),
+      commanders: Vec::new(),
+      tags: BTreeMap::new(),
     }
   }
 
@@ -166,6 +341,326 @@ impl Deck {
     b.build()
   }
 
+  /// Registers `card` as a nonland mana source that produces `mana_cost` starting
+  /// `delay` turns after it is drawn. Mana sources count towards paying for
+  /// other cards in the simulation, see `Hand::auto_tap_with_sources`
+  pub fn add_mana_source(&mut self, card: &Card, mana_cost: ManaCost, delay: usize) {
+    self.mana_sources.retain(|source| source.hash != card.hash);
+    self
+      .mana_sources
+      .push(ManaSource::new(card, mana_cost, delay));
+  }
+
+  /// Registers `card` as a draw engine that draws `count` additional cards every turn,
+  /// starting `delay` turns after it is cast. The simulation casts the card on the first
+  /// turn on or after its on curve turn by which it is drawn, see `Hand::extra_draws_with_engines`
+  pub fn add_draw_engine(&mut self, card: &Card, count: usize, delay: usize) {
+    self.draw_engines.retain(|engine| engine.hash != card.hash);
+    self.draw_engines.push(DrawEngine::new(card, count, delay));
+  }
+
+  /// Registers `card` as a cantrip that looks at the top `look` cards of the library and
+  /// draws `draws` cards, looking before drawing if `look_first`. Simulations with a
+  /// `CantripPolicy` other than `Off` cast it, see `Hand::play_cantrips`
+  pub fn add_cantrip(&mut self, card: &Card, look: usize, draws: usize, look_first: bool) {
+    self.cantrips.retain(|cantrip| cantrip.hash != card.hash);
+    self
+      .cantrips
+      .push(Cantrip::new(card, look, draws, look_first));
+  }
+
+  /// Registers the well known cantrips of the deck, like Opt and Preordain, see `Cantrip::known`
+  pub fn add_known_cantrips(&mut self) {
+    let known: Vec<Cantrip> = self
+      .cards
+      .iter()
+      .filter_map(|cc| Cantrip::known(&cc.card))
+      .collect();
+    for cantrip in known {
+      self.cantrips.retain(|c| c.hash != cantrip.hash);
+      self.cantrips.push(cantrip);
+    }
+  }
+
+  /// Returns the deck with the `version` of each card, e.g. the Arena Alchemy rebalanced
+  /// versions, keeping the cards without that version. Tags, mana sources, draw engines and
+  /// cantrips follow the replaced cards
+  pub fn with_card_version(&self, version: CardVersion) -> Deck {
+    let mut deck = self.clone();
+    let mut hashes: HashMap<u64, u64> = HashMap::new();
+    let cards = deck
+      .cards
+      .iter_mut()
+      .map(|cc| &mut cc.card)
+      .chain(deck.commanders.iter_mut());
+    for card in cards {
+      if let Some(replacement) = ALL_CARDS.card_version(card, version) {
+        if replacement.hash != card.hash {
+          hashes.insert(card.hash, replacement.hash);
+          *card = replacement.clone();
+        }
+      }
+    }
+    let replace = |hash: &mut u64| *hash = *hashes.get(hash).unwrap_or(hash);
+    deck.tags.values_mut().flatten().for_each(replace);
+    deck
+      .mana_sources
+      .iter_mut()
+      .for_each(|s| replace(&mut s.hash));
+    deck
+      .draw_engines
+      .iter_mut()
+      .for_each(|e| replace(&mut e.hash));
+    deck.cantrips.iter_mut().for_each(|c| replace(&mut c.hash));
+    deck
+      .cards
+      .sort_unstable_by(|a, b| a.card.name.cmp(&b.card.name));
+    deck
+  }
+
+  /// Returns the mana value histogram of the nonland cards in the deck, where index `i`
+  /// holds the number of cards with mana value `i`
+  pub fn curve(&self) -> Vec<usize> {
+    let mut curve = Vec::new();
+    for cc in self.cards.iter().filter(|cc| !cc.card.is_land()) {
+      let cmc = cc.card.mana_cost.cmc() as usize;
+      if curve.len() <= cmc {
+        curve.resize(cmc + 1, 0);
+      }
+      curve[cmc] += cc.count;
+    }
+    curve
+  }
+
+  /// Returns the colored mana symbols in the mana costs of the nonland cards in the deck
+  /// by turn, where index `i` holds the symbols of the cards played on turn `i`
+  pub fn pip_counts(&self) -> Vec<PipCount> {
+    let mut pips = Vec::new();
+    for cc in self.cards.iter().filter(|cc| !cc.card.is_land()) {
+      let turn = cc.card.turn as usize;
+      if pips.len() <= turn {
+        pips.resize(turn + 1, PipCount::default());
+      }
+      pips[turn].add(&cc.card.mana_cost, cc.count);
+    }
+    pips
+  }
+
+  /// Adds `count` copies of `card` to the deck
+  pub fn insert_count(&mut self, card: &Card, count: usize) {
+    match self.cards.iter_mut().find(|cc| cc.card.hash == card.hash) {
+      Some(cc) => cc.count += count,
+      None => {
+        self.cards.push(DeckCard {
+          card: card.clone(),
+          count,
+        });
+        self
+          .cards
+          .sort_unstable_by(|a, b| a.card.name.cmp(&b.card.name));
+      }
+    }
+    self.card_count += count;
+  }
+
+  /// Removes up to `count` copies of `card` from the deck
+  pub fn remove_count(&mut self, card: &Card, count: usize) {
+    if let Some(cc) = self.cards.iter_mut().find(|cc| cc.card.hash == card.hash) {
+      let removed = std::cmp::min(cc.count, count);
+      cc.count -= removed;
+      self.card_count -= removed;
+    }
+    self.cards.retain(|cc| cc.count > 0);
+  }
+
+  /// Returns the cards of the deck, and of `options.extras`, that are not in `owned`,
+  /// with `owned` copies counted as configured by `options`. Basic lands are never missing.
+  /// The rarity counts of the result, like `Deck::rare_count`, are the crafting cost
+  pub fn missing_cards(&self, owned: &Deck, options: &CraftOptions) -> Deck {
+    let mut needed: BTreeMap<&str, (&Card, usize)> = BTreeMap::new();
+    let extras = options.extras.iter().flat_map(|extras| extras.cards.iter());
+    for cc in self.cards.iter().chain(extras) {
+      if cc.card.kind == CardKind::BasicLand {
+        continue;
+      }
+      needed.entry(&cc.card.name).or_insert((&cc.card, 0)).1 += cc.count;
+    }
+    let mut missing = Deck::new();
+    for (name, (card, count)) in needed {
+      let owned_count = owned.card_count_from_name(name).map_or(0, |cc| cc.count);
+      let owned_count = options
+        .owned_cap
+        .map_or(owned_count, |cap| std::cmp::min(owned_count, cap));
+      if count > owned_count {
+        missing.insert_count(card, count - owned_count);
+      }
+    }
+    missing
+  }
+
+  /// Returns the cards to add to and remove from the deck to turn it into `other`
+  pub fn diff(&self, other: &Deck) -> DeckDiff {
+    let mut added = Deck::new();
+    let mut removed = Deck::new();
+    let mut changed = Vec::new();
+    for cc in &self.cards {
+      let to = other
+        .card_count_from_name(&cc.card.name)
+        .map_or(0, |o| o.count);
+      if to < cc.count {
+        removed.insert_count(&cc.card, cc.count - to);
+      }
+      if to != 0 && to != cc.count {
+        changed.push(CountChange {
+          name: cc.card.name.clone(),
+          from: cc.count,
+          to,
+        });
+      }
+    }
+    for cc in &other.cards {
+      let from = self
+        .card_count_from_name(&cc.card.name)
+        .map_or(0, |s| s.count);
+      if from < cc.count {
+        added.insert_count(&cc.card, cc.count - from);
+      }
+    }
+    changed.sort_by(|a, b| a.name.cmp(&b.name));
+    DeckDiff {
+      added,
+      removed,
+      changed,
+    }
+  }
+
+  /// Returns the copies of each card of the deck keyed by card name, adding up the copies
+  /// of cards listed more than once, e.g. with different printings
+  pub fn canonical_counts(&self) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for cc in &self.cards {
+      *counts.entry(cc.card.name.as_str()).or_insert(0) += cc.count;
+    }
+    counts
+  }
+
+  /// Returns the deck list of the deck in a canonical form, independent of the order and
+  /// printings of its cards: the commanders in a "Commander" section, if any, then every
+  /// card sorted by name. The list parses back into the same deck, ignoring its tags
+  pub fn canonical_list(&self) -> String {
+    let mut list = String::new();
+    if !self.commanders.is_empty() {
+      let mut commanders: Vec<&str> = self.commanders.iter().map(|c| c.name.as_str()).collect();
+      commanders.sort_unstable();
+      list.push_str("Commander\n");
+      for commander in commanders {
+        list.push_str(&format!("1 {}\n", commander));
+      }
+      list.push_str("\nDeck\n");
+    }
+    for (name, count) in self.canonical_counts() {
+      list.push_str(&format!("{} {}\n", count, name));
+    }
+    list
+  }
+
+  /// Returns a hash of the canonical deck list, see `canonical_list`, so decks with the same
+  /// cards have the same hash whatever their order and printings. The hash is FNV-1a,
+  /// stable across builds and platforms, so it can key persistent caches of simulation results
+  pub fn canonical_hash(&self) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    self
+      .canonical_list()
+      .bytes()
+      .fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+      })
+  }
+
+  /// Returns true if the deck has the same commanders and the same number of copies of each
+  /// card as `other`, whatever their order and printings
+  pub fn same_cards(&self, other: &Deck) -> bool {
+    self.canonical_list() == other.canonical_list()
+  }
+
+  /// Returns the wildcards needed to turn the deck into `other`, owning the cards of
+  /// `owned` in addition to those of the deck, see `missing_cards`
+  pub fn upgrade_cost(&self, other: &Deck, owned: &Deck, options: &CraftOptions) -> Wildcards {
+    let mut owned = owned.clone();
+    for cc in &self.cards {
+      let owned_count = owned
+        .card_count_from_name(&cc.card.name)
+        .map_or(0, |o| o.count);
+      if owned_count < cc.count {
+        owned.insert_count(&cc.card, cc.count - owned_count);
+      }
+    }
+    let missing = other.missing_cards(&owned, options);
+    Wildcards {
+      common: missing.common_count(),
+      uncommon: missing.uncommon_count(),
+      rare: missing.rare_count(),
+      mythic: missing.mythic_count(),
+    }
+  }
+
+  /// Returns the Arena ids of the cards of the deck, for importing the deck into the Arena
+  /// client, or an error naming the cards that are not on Arena
+  pub fn to_arena_ids(&self) -> Result<ArenaDeck, DeckcodeError> {
+    let mut missing = Vec::new();
+    let mut arena_id = |card: &Card| {
+      let id = if card.arena_id != 0 {
+        Some(card.arena_id)
+      } else {
+        ALL_CARDS.arena_id_from_name(&card.name)
+      };
+      if id.is_none() {
+        missing.push(card.name.clone());
+      }
+      id.unwrap_or(0)
+    };
+    let arena_deck = ArenaDeck {
+      commanders: self.commanders.iter().map(&mut arena_id).collect(),
+      cards: self
+        .cards
+        .iter()
+        .map(|cc| (arena_id(&cc.card), cc.count))
+        .collect(),
+    };
+    if !missing.is_empty() {
+      return Err(DeckcodeError(format!(
+        "Cards not on Arena: {}",
+        missing.join(", ")
+      )));
+    }
+    Ok(arena_deck)
+  }
+
+  /// Returns the cards of the deck from sets that leave Standard at the next rotation,
+  /// see `SetCode::rotates_next`. Basic lands are reprinted in every set and never rotate
+  pub fn rotating_cards(&self) -> Vec<&DeckCard> {
+    self
+      .cards
+      .iter()
+      .filter(|cc| cc.card.kind != CardKind::BasicLand && cc.card.rotates_next())
+      .collect()
+  }
+
+  /// Returns true if none of the cards of the deck rotate at the next Standard rotation
+  pub fn survives_rotation(&self) -> bool {
+    self.rotating_cards().is_empty()
+  }
+
+  /// Returns a copy of the deck without its `rotating_cards`
+  pub fn post_rotation(&self) -> Deck {
+    let mut deck = self.clone();
+    for cc in self.rotating_cards() {
+      deck.remove_count(&cc.card, cc.count);
+    }
+    deck
+  }
+
   pub fn flatten(&self) -> Vec<&Card> {
     let mut result = Vec::with_capacity(self.card_count);
     for card_count in &self.cards {
@@ -196,52 +691,311 @@ impl Deck {
     self.len() == 0
   }
 
+  /// Returns the number of cards in the deck that satisfy the predicate
+  pub fn count<P>(&self, predicate: P) -> usize
+  where
+    P: Fn(&Card) -> bool,
+  {
+    self
+      .cards
+      .iter()
+      .filter(|cc| predicate(&cc.card))
+      .map(|cc| cc.count)
+      .sum()
+  }
+
+  /// Returns the exact probability to see at least `minimum` cards that satisfy
+  /// the predicate among the first `cards_seen` cards of the deck
+  pub fn p_at_least<P>(&self, predicate: P, minimum: usize, cards_seen: usize) -> f64
+  where
+    P: Fn(&Card) -> bool,
+  {
+    let cards_seen = std::cmp::min(cards_seen, self.len());
+    probability::hypergeometric_at_least(self.len(), self.count(predicate), cards_seen, minimum)
+  }
+
+  /// Returns the exact probability to see, for every `(predicate, minimum)` group, at least
+  /// `minimum` cards that satisfy the predicate among the first `cards_seen` cards of the deck.
+  /// Each card counts towards the first group whose predicate it satisfies
+  pub fn p_at_least_each(&self, groups: &[(CardPredicate, usize)], cards_seen: usize) -> f64 {
+    let mut group_sizes = vec![0; groups.len()];
+    for cc in &self.cards {
+      if let Some(i) = groups.iter().position(|(predicate, _)| predicate(&cc.card)) {
+        group_sizes[i] += cc.count;
+      }
+    }
+    let minimums: Vec<usize> = groups.iter().map(|(_, minimum)| *minimum).collect();
+    let cards_seen = std::cmp::min(cards_seen, self.len());
+    probability::multivariate_hypergeometric_at_least(
+      self.len(),
+      &group_sizes,
+      &minimums,
+      cards_seen,
+    )
+  }
+
+  /// Returns the library left once the `known` cards, e.g. the cards in hand, on the
+  /// battlefield and in the graveyard, are taken out of the deck, or an error if a known
+  /// card isn't in the deck
+  pub fn library_without(&self, known: &[&Card]) -> Result<Deck, String> {
+    let mut library = self.clone();
+    for card in known {
+      if library.cards.iter().all(|cc| cc.card.hash != card.hash) {
+        return Err(format!("{} is not in the library", card.name));
+      }
+      library.remove_count(card, 1);
+    }
+    Ok(library)
+  }
+
+  /// Returns the exact probability to draw at least `minimum` cards that satisfy the
+  /// predicate, the outs, in the next `draws` cards, after a scry or surveil of `scry`
+  /// cards that keeps the outs on top and puts every other card away. With a `scry` of 0,
+  /// this is `p_at_least` of `draws` cards
+  pub fn p_at_least_after_scry<P>(
+    &self,
+    predicate: P,
+    minimum: usize,
+    draws: usize,
+    scry: usize,
+  ) -> f64
+  where
+    P: Fn(&Card) -> bool,
+  {
+    let population = self.len();
+    let outs = self.count(predicate);
+    let scry = std::cmp::min(scry, population);
+    let draws = std::cmp::min(draws, population);
+    // Sum over the number of outs `j` seen by the scry. The first `min(j, draws)` draws are
+    // those outs, and the rest come from the cards under the scried ones
+    (0..=std::cmp::min(scry, outs))
+      .map(|j| {
+        let p_scry = probability::hypergeometric(population, outs, scry, j);
+        let drawn_outs = std::cmp::min(j, draws);
+        let rest = std::cmp::min(draws - drawn_outs, population - scry);
+        p_scry
+          * probability::hypergeometric_at_least(
+            population - scry,
+            outs - j,
+            rest,
+            minimum.saturating_sub(drawn_outs),
+          )
+      })
+      .sum()
+  }
+
+  /// Returns the exact probability to see at least `minimum` lands by `turn`, without a mulligan
+  pub fn p_lands_by_turn(&self, minimum: usize, turn: usize, play_order: PlayOrder) -> f64 {
+    let cards_seen = probability::cards_seen_by_turn(turn, play_order);
+    self.p_at_least(|c| c.is_land(), minimum, cards_seen)
+  }
+
+  /// Returns the exact probability to see at least `minimum` copies of `card` by `turn`,
+  /// without a mulligan
+  pub fn p_card_by_turn(
+    &self,
+    card: &Card,
+    minimum: usize,
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> f64 {
+    let cards_seen = probability::cards_seen_by_turn(turn, play_order);
+    self.p_at_least(|c| c.hash == card.hash, minimum, cards_seen)
+  }
+
+  /// Returns the exact probabilities to assemble the combo of `pieces`, one copy of each of
+  /// these distinct cards, with a 7 card opening hand and each following draw, without a
+  /// mulligan. Every card of the deck with `tutor_tag` finds any one missing piece, as soon as
+  /// it is seen. See `Simulation::p_combo_by_turn` for the same question with mulligans and
+  /// extra draws
+  pub fn combo_draws(&self, pieces: &[&Card], tutor_tag: &str) -> ComboDraws {
+    let copies = |card: &Card| {
+      self
+        .cards
+        .iter()
+        .find(|cc| cc.card.hash == card.hash)
+        .map_or(0, |cc| cc.count)
+    };
+    let tutor_count = self
+      .cards_with_tag(tutor_tag)
+      .into_iter()
+      .filter(|tutor| pieces.iter().all(|piece| piece.hash != tutor.hash))
+      .map(copies)
+      .sum();
+    // One group per piece, followed by the tutors
+    let mut group_sizes: Vec<usize> = pieces.iter().map(|piece| copies(piece)).collect();
+    group_sizes.push(tutor_count);
+    let assembled = |draws: &[usize]| {
+      let (pieces, tutors) = draws.split_at(draws.len() - 1);
+      pieces.iter().filter(|drawn| **drawn == 0).count() <= tutors[0]
+    };
+    let max_draws = self.len().saturating_sub(probability::OPENING_HAND_SIZE);
+    let p_assembled: Vec<f64> = (0..=max_draws)
+      .map(|draws| {
+        let cards_seen = std::cmp::min(probability::OPENING_HAND_SIZE + draws, self.len());
+        probability::multivariate_hypergeometric_where(
+          self.len(),
+          &group_sizes,
+          cards_seen,
+          assembled,
+        )
+      })
+      .collect();
+    // Every card is seen after the last draw, so the combo is either certain or impossible
+    let expected_draws = if p_assembled.last().is_some_and(|p| *p > 0.5) {
+      Some(p_assembled.iter().map(|p| 1.0 - p).sum())
+    } else {
+      None
+    };
+    ComboDraws {
+      p_assembled,
+      expected_draws,
+    }
+  }
+
+  /// Adds `tag` to `card`
+  pub fn tag(&mut self, tag: &str, card: &Card) {
+    let hashes = self.tags.entry(tag.to_string()).or_default();
+    if !hashes.contains(&card.hash) {
+      hashes.push(card.hash);
+    }
+  }
+
+  /// Returns the cards in the deck, including commanders, with `tag`
+  pub fn cards_with_tag(&self, tag: &str) -> Vec<&Card> {
+    let hashes = match self.tags.get(tag) {
+      Some(hashes) => hashes,
+      None => return Vec::new(),
+    };
+    self
+      .cards
+      .iter()
+      .map(|cc| &cc.card)
+      .chain(self.commanders.iter())
+      .filter(|c| hashes.contains(&c.hash))
+      .collect()
+  }
+
+  /// Returns the fraction of the nonland cards in the deck with `tag`, e.g. the removal
+  /// density, or 0 if the deck has no nonland cards
+  pub fn tag_density(&self, tag: &str) -> f64 {
+    let nonland_count = self.count(|card| !card.is_land());
+    if nonland_count == 0 {
+      return 0.0;
+    }
+    let tagged = self.cards_with_tag(tag);
+    let tagged_count = self.count(|card| !card.is_land() && tagged.contains(&card));
+    tagged_count as f64 / nonland_count as f64
+  }
+
   pub fn from_list(list: &str) -> Result<Self, DeckcodeError> {
+    Self::from_list_with_filter(list, CardFilter::Playable)
+  }
+
+  /// Returns the deck of `list`, resolving card names to the entries of the card data that
+  /// `filter` keeps. `Deck::from_list` only resolves playable cards
+  pub fn from_list_with_filter(list: &str, filter: CardFilter) -> Result<Self, DeckcodeError> {
+    Ok(Self::parse_list(list, filter, &mut Diagnostics::new())?)
+  }
+
+  /// Same as `from_list`, but a card name that doesn't resolve is returned as
+  /// `DeckListError::UnknownCard`, with the closest names for "did you mean" corrections
+  pub fn try_from_list(list: &str) -> Result<Self, DeckListError> {
+    Self::parse_list(list, CardFilter::Playable, &mut Diagnostics::new())
+  }
+
+  /// Same as `from_list`, but also returns the warnings about the lines of `list` that were
+  /// not taken as written: skipped lines, names that resolved to another card, and ignored
+  /// modifiers
+  pub fn from_list_with_diagnostics(list: &str) -> Result<(Self, Diagnostics), DeckcodeError> {
+    let mut diagnostics = Diagnostics::new();
+    let deck = Self::parse_list(list, CardFilter::Playable, &mut diagnostics)?;
+    Ok((deck, diagnostics))
+  }
+
+  fn parse_list(
+    list: &str,
+    filter: CardFilter,
+    diagnostics: &mut Diagnostics,
+  ) -> Result<Self, DeckListError> {
     lazy_static! {
         //https://regex101.com/r/OluNfe/3
         static ref ARENA_LINE_REGEX: Regex =
             Regex::new(r"^\s*(?P<amount>\d+)\s+(?P<name>[^\(#\n\r]+)(?:\s*\((?P<set>\w+)\)\s+(?P<setnum>\d+))?\s*#?(?:\s*[Xx]\s*=\s*(?P<X>\d+))?(?:\s*[Tt]\s*=\s*(?P<T>\d+))?(?:\s*[Mm]\s*=\s*(?P<M>[RGWUB\d{}]+))?")
                 .expect("Failed to compile ARENA_LINE_REGEX regex");
+        static ref TAGS_REGEX: Regex =
+            Regex::new(r"\[(?P<tags>[^\]]*)\]").expect("Failed to compile TAGS_REGEX regex");
     }
     let mut builder = DeckBuilder::new();
+    let mut tags: Vec<(String, Card)> = Vec::new();
     let mut looking_for_deck_line = false;
-    for line in list.trim().lines() {
+    let mut in_commander_section = false;
+    let mut commanders: Vec<Card> = Vec::new();
+    // The index of the line that ends the main deck, and the lines that follow it
+    let mut skipped: Option<(usize, &str)> = None;
+    // Leading blank lines are skipped, but still count toward the line numbers
+    let lines: Vec<&str> = list.lines().collect();
+    let first_line = lines
+      .iter()
+      .position(|line| !line.trim().is_empty())
+      .unwrap_or(lines.len());
+    for (index, line) in lines.iter().enumerate().skip(first_line) {
+      let line_number = index + 1;
       let trimmed = line.trim();
       let trimmed_lower = trimmed.to_lowercase();
       // Ignore reserved words
       if trimmed_lower == "deck" {
         looking_for_deck_line = false;
+        in_commander_section = false;
         continue;
       }
       if trimmed_lower == "commander" {
         looking_for_deck_line = true;
+        in_commander_section = true;
         continue;
       }
       if trimmed_lower == "companion" {
         looking_for_deck_line = true;
+        in_commander_section = false;
         continue;
       }
       if trimmed_lower == "sideboard" {
         // Assumes sideboard comes after deck
+        skipped = Some((index, "of the sideboard"));
         break;
       }
       if trimmed_lower == "maybeboard" {
         // Assumes maybeboard comes after deck
+        skipped = Some((index, "of the maybeboard"));
         break;
       }
       // Ignore line comments
       if trimmed.starts_with('#') {
         continue;
       }
-      if looking_for_deck_line {
+      // Of the sections before the deck, only the commander section has cards to parse
+      if looking_for_deck_line && (!in_commander_section || trimmed.is_empty()) {
         continue;
       }
       // An empty line divides the main board cards from the side board cards
       if trimmed.is_empty() {
+        skipped = Some((index, "after the empty line that ends the main deck"));
         break;
       }
+      // Strip the tags so they do not end up in the card name
+      let line_tags: Vec<String> = TAGS_REGEX
+        .captures_iter(trimmed)
+        .flat_map(|caps| {
+          caps["tags"]
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>()
+        })
+        .collect();
+      let untagged = TAGS_REGEX.replace_all(trimmed, "");
       let caps = ARENA_LINE_REGEX
-        .captures(trimmed)
+        .captures(&untagged)
         .ok_or_else(|| DeckcodeError(format!("Cannot regex capture deck list line: {}", line)))?;
       let amount = caps["amount"].parse::<usize>().or_else(|_| {
         Err(DeckcodeError(format!(
@@ -271,10 +1025,32 @@ impl Deck {
         .trim()
         .to_string();
       // Find the card from the name, and clone it so we can apply card modifiers
-      let mut card = ALL_CARDS
-        .card_from_name(&left_card_name)
-        .ok_or_else(|| DeckcodeError(format!("Cannot find card named \"{}\" in collection", name)))?
-        .clone();
+      let mut card = match ALL_CARDS.card_from_name_with_filter(&left_card_name, filter) {
+        Some(card) => card,
+        // Commander sections used to be skipped, so rather than failing the whole list,
+        // a misspelled commander falls back to the closest name
+        None if in_commander_section => ALL_CARDS
+          .suggest_names(&left_card_name, 1)
+          .first()
+          .and_then(|name| ALL_CARDS.card_from_name_with_filter(name, filter))
+          .ok_or_else(|| {
+            DeckListError::UnknownCard(UnknownCard::new(&left_card_name, &ALL_CARDS))
+          })?,
+        None => {
+          return Err(DeckListError::UnknownCard(UnknownCard::new(
+            &left_card_name,
+            &ALL_CARDS,
+          )))
+        }
+      }
+      .clone();
+      if card.name.to_lowercase() != left_card_name.to_lowercase() {
+        diagnostics.push(
+          DiagnosticKind::NameFallback,
+          Some(line_number),
+          format!("\"{}\" resolved to \"{}\"", left_card_name, card.name),
+        );
+      }
       // Handle the X = modifier
       if let Some(x_val) = caps.name("X") {
         // Only modify the colorless mana cost if the mana cost string contains an X value
@@ -293,6 +1069,15 @@ impl Deck {
             .for_each(|cost| cost.c = x_val);
           card.mana_cost_string = card.mana_cost_string.replace('X', &x_val.to_string());
           card.turn = card.mana_cost.cmc();
+        } else {
+          diagnostics.push(
+            DiagnosticKind::IgnoredModifier,
+            Some(line_number),
+            format!(
+              "Ignored X= for \"{}\", which has no X in its cost",
+              card.name
+            ),
+          );
         }
       }
       // Handle the M = modifier
@@ -300,10 +1085,13 @@ impl Deck {
         let mana_cost_str = m_val.as_str();
         let all_mana_costs = mana_costs_from_str(mana_cost_str);
         if all_mana_costs.is_empty() {
-          return Err(DeckcodeError(format!(
-            "Problematic mana cost ('M = ') specifed at line {}",
-            line
-          )));
+          return Err(
+            DeckcodeError(format!(
+              "Problematic mana cost ('M = ') specifed at line {}",
+              line
+            ))
+            .into(),
+          );
         }
         card.mana_cost = all_mana_costs[0];
         card.all_mana_costs = all_mana_costs;
@@ -322,9 +1110,146 @@ impl Deck {
         card.turn += turn_val;
       }
       card.set = set;
-      builder = builder.insert_count(card, amount);
+      for tag in line_tags {
+        tags.push((tag, card.clone()));
+      }
+      if in_commander_section {
+        commanders.extend(std::iter::repeat_n(card, amount));
+      } else {
+        builder = builder.insert_count(card, amount);
+      }
+    }
+    let mut deck = builder.build();
+    for (tag, card) in &tags {
+      deck.tag(tag, card);
+    }
+    if let Some((index, what)) = skipped {
+      let count = lines[index + 1..]
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !is_section_header(line))
+        .count();
+      if count > 0 {
+        diagnostics.push(
+          DiagnosticKind::SkippedLines,
+          Some(index + 1),
+          format!("Skipped {} line(s) {}", count, what),
+        );
+      }
+    }
+    if !commanders.is_empty() {
+      deck.commanders = commanders;
+      deck.format = GameFormat::Commander;
+    }
+    Ok(deck)
+  }
+
+  /// Returns true if the deck has at least one commander
+  pub fn is_commander(&self) -> bool {
+    !self.commanders.is_empty()
+  }
+
+  /// Returns a copy of the deck in `format`, e.g. to mark a deck list with a "Commander"
+  /// section, which parses as a Commander deck, as a Brawl deck
+  pub fn with_format(&self, format: GameFormat) -> Deck {
+    let mut deck = self.clone();
+    deck.format = format;
+    deck
+  }
+
+  /// Returns an error if the deck does not follow the deck construction rules of its format.
+  /// Only Commander and the Brawl formats are checked
+  pub fn validate(&self) -> Result<(), DeckcodeError> {
+    match self.format {
+      GameFormat::Commander => self.validate_commander(),
+      GameFormat::Brawl | GameFormat::StandardBrawl => self.validate_brawl(),
+      _ => Ok(()),
     }
-    Ok(builder.build())
+  }
+
+  /// Returns an error if the deck does not follow the Commander deck construction rules:
+  /// one commander (or two partners), exactly 100 cards including the commanders,
+  /// and no more than a single copy of any card other than basic lands.
+  /// NOTE: Card data does not carry legalities, so the ban list is not checked
+  pub fn validate_commander(&self) -> Result<(), DeckcodeError> {
+    self.validate_singleton(&GameFormat::Commander)?;
+    self.validate_color_identity()
+  }
+
+  /// Returns an error if the deck does not follow the Brawl deck construction rules of its
+  /// format, `GameFormat::Brawl` (Historic Brawl) unless the deck is `GameFormat::StandardBrawl`:
+  /// the Commander rules with 100 or 60 cards, and every card within the color identity
+  /// of the commanders. See `Deck::validate_color_identity`
+  pub fn validate_brawl(&self) -> Result<(), DeckcodeError> {
+    let format = if self.format == GameFormat::StandardBrawl {
+      GameFormat::StandardBrawl
+    } else {
+      GameFormat::Brawl
+    };
+    self.validate_singleton(&format)?;
+    self.validate_color_identity()
+  }
+
+  /// Returns the color bits, see `ManaCost::bits`, of the color identity of the commanders
+  pub fn commander_colors(&self) -> u8 {
+    self.commanders.iter().fold(0, |bits, commander| {
+      bits | (commander.mana_cost.bits & COLOR_BITS)
+    })
+  }
+
+  /// Returns an error if a card has a color outside the color identity of the commanders.
+  /// NOTE: Card data does not carry color identities, so the colors of a card are those of
+  /// its mana cost and mana symbols in rules text are not checked. Lands are exempt, since
+  /// the colors stored for a land are the mana it can produce (e.g. Command Tower produces
+  /// every color but has a colorless identity)
+  pub fn validate_color_identity(&self) -> Result<(), DeckcodeError> {
+    let identity = self.commander_colors();
+    for cc in &self.cards {
+      if cc.card.is_land() {
+        continue;
+      }
+      if cc.card.mana_cost.bits & COLOR_BITS & !identity != 0 {
+        return Err(DeckcodeError(format!(
+          "\"{}\" is outside the color identity of the commander",
+          cc.card.name
+        )));
+      }
+    }
+    Ok(())
+  }
+
+  /// Returns an error if the deck does not have one commander (or two partners), the deck
+  /// size of `format` including the commanders, and a single copy of every card other than
+  /// basic lands
+  fn validate_singleton(&self, format: &GameFormat) -> Result<(), DeckcodeError> {
+    let commander_count = self.commanders.len();
+    if commander_count == 0 || commander_count > 2 {
+      return Err(DeckcodeError(format!(
+        "Expected 1 or 2 commanders, found {}",
+        commander_count
+      )));
+    }
+    let deck_size = format.deck_size().unwrap_or(0);
+    let total = self.card_count + commander_count;
+    if total != deck_size {
+      return Err(DeckcodeError(format!(
+        "Expected {} cards including commanders, found {}",
+        deck_size, total
+      )));
+    }
+    for cc in &self.cards {
+      if cc.card.kind == CardKind::BasicLand {
+        continue;
+      }
+      let is_commander = self.commanders.iter().any(|c| c.name == cc.card.name);
+      if cc.count > 1 || is_commander {
+        return Err(DeckcodeError(format!(
+          "Expected a single copy of \"{}\"",
+          cc.card.name
+        )));
+      }
+    }
+    Ok(())
   }
 
   pub fn to_string(&self) -> String {
@@ -367,6 +1292,13 @@ impl Deref for Deck {
   }
 }
 
+/// Returns true if the trimmed deck list `line` starts a section, like "Sideboard"
+fn is_section_header(line: &str) -> bool {
+  ["deck", "commander", "companion", "sideboard", "maybeboard"]
+    .iter()
+    .any(|header| line.eq_ignore_ascii_case(header))
+}
+
 #[macro_export]
 macro_rules! decklist {
   ($list:expr) => {
@@ -376,6 +1308,101 @@ macro_rules! decklist {
 
 #[cfg(test)]
 mod tests {
+  use crate::deck::*;
+
+  #[test]
+  fn deck_diff_and_upgrade_cost() {
+    let current = decklist!(
+      "
+      20 Swamp
+      4 Thoughtseize
+      2 Opt
+      "
+    );
+    let latest = decklist!(
+      "
+      20 Swamp
+      2 Thoughtseize
+      4 Opt
+      4 Hero's Downfall
+      "
+    );
+    let diff = current.diff(&latest);
+    assert_eq!(diff.added.len(), 6);
+    assert_eq!(diff.added.card_count_from_name("Opt").unwrap().count, 2);
+    assert_eq!(diff.removed.len(), 2);
+    assert_eq!(
+      diff.changed,
+      vec![
+        CountChange {
+          name: "Opt".to_string(),
+          from: 2,
+          to: 4,
+        },
+        CountChange {
+          name: "Thoughtseize".to_string(),
+          from: 4,
+          to: 2,
+        },
+      ]
+    );
+    assert!(current.diff(&current).added.is_empty());
+    let total = |w: Wildcards| w.common + w.uncommon + w.rare + w.mythic;
+    let options = CraftOptions::default();
+    let cost = current.upgrade_cost(&latest, &Deck::new(), &options);
+    assert_eq!(total(cost), 6);
+    let cost = current.upgrade_cost(&latest, &decklist!("4 Hero's Downfall"), &options);
+    assert_eq!(total(cost), 2);
+  }
+
+  #[test]
+  fn unknown_card_suggestions() {
+    let unknown = match Deck::try_from_list("20 Swamp\n4 Thoughtsieze") {
+      Err(DeckListError::UnknownCard(unknown)) => unknown,
+      other => panic!("Expected an unknown card, got {:?}", other),
+    };
+    assert_eq!(unknown.name, "Thoughtsieze");
+    assert_eq!(unknown.suggestions[0], "Thoughtseize");
+    let error = Deck::from_list("20 Swamp\n4 Thoughtsieze").unwrap_err();
+    assert!(error.0.contains("did you mean \"Thoughtseize\""));
+    match Deck::try_from_list("4 No Card Has This Name") {
+      Err(DeckListError::UnknownCard(unknown)) => assert!(unknown.suggestions.is_empty()),
+      other => panic!("Expected an unknown card, got {:?}", other),
+    }
+    assert!(matches!(
+      Deck::try_from_list("Opt"),
+      Err(DeckListError::Deckcode(_))
+    ));
+  }
+
+  #[test]
+  fn deck_with_rebalanced_cards() {
+    let deck = decklist!(
+      "
+      20 Island
+      4 Alrund's Epiphany [draw]
+      4 Opt
+      "
+    );
+    let rebalanced = deck.with_card_version(CardVersion::Rebalanced);
+    assert_eq!(rebalanced.len(), 28);
+    assert!(rebalanced.card_from_name("Alrund's Epiphany").is_none());
+    assert_eq!(
+      rebalanced
+        .card_count_from_name("A-Alrund's Epiphany")
+        .unwrap()
+        .count,
+      4
+    );
+    assert_eq!(
+      rebalanced.cards_with_tag("draw")[0].name,
+      "A-Alrund's Epiphany"
+    );
+    assert!(rebalanced.card_from_name("Opt").is_some());
+    let original = rebalanced.with_card_version(CardVersion::Original);
+    assert_eq!(original.cards_with_tag("draw")[0].name, "Alrund's Epiphany");
+  }
+
   #[test]
   fn good_deckcode_0() {
     let code = "
@@ -890,6 +1917,199 @@ mod tests {
     ";
     let deck = decklist!(code);
     assert_eq!(deck.len(), 4);
+    assert_eq!(deck.commanders.len(), 1);
+    assert_eq!(deck.commanders[0].name, "Lurrus of the Dream-Den");
+    assert!(deck.is_commander());
+  }
+
+  #[test]
+  fn code_contains_partner_commanders() {
+    let code = "
+      Commander
+      1 Tymna the Weaver
+      1 Thrasios, Triton Hero
+
+      Deck
+      1 Island
+      1 Plains
+    ";
+    let deck = decklist!(code);
+    assert_eq!(deck.len(), 2);
+    assert_eq!(deck.commanders.len(), 2);
+  }
+
+  fn commander_deck(commander: &str) -> Deck {
+    let identity = card!(commander).mana_cost.bits & COLOR_BITS;
+    let mut cards: Vec<_> = crate::data::ALL_CARDS
+      .iter()
+      .filter(|c| !c.is_face && !c.is_land() && c.name != commander)
+      .filter(|c| c.mana_cost.bits & COLOR_BITS & !identity == 0)
+      .take(62)
+      .cloned()
+      .collect();
+    for _ in 0..37 {
+      cards.push(card!("Forest").clone());
+    }
+    let mut deck = Deck::from_cards(cards);
+    deck.commanders.push(card!(commander).clone());
+    deck
+  }
+
+  #[test]
+  fn validate_commander_0() {
+    let mut deck = commander_deck("Omnath, Locus of Mana");
+    assert_eq!(deck.len(), 99);
+    assert!(deck.validate_commander().is_ok());
+    // Swap a Forest for a blue card
+    deck
+      .cards
+      .iter_mut()
+      .find(|cc| cc.card.name == "Forest")
+      .unwrap()
+      .count -= 1;
+    deck.cards.push(DeckCard {
+      card: card!("Opt").clone(),
+      count: 1,
+    });
+    let error = deck.validate_commander().unwrap_err();
+    assert!(error.0.contains("\"Opt\" is outside the color identity"));
+  }
+
+  #[test]
+  fn validate_commander_command_tower() {
+    let mut deck = commander_deck("Omnath, Locus of Mana");
+    // Swap a Forest for a Command Tower, which taps for every color
+    deck
+      .cards
+      .iter_mut()
+      .find(|cc| cc.card.name == "Forest")
+      .unwrap()
+      .count -= 1;
+    deck.cards.push(DeckCard {
+      card: card!("Command Tower").clone(),
+      count: 1,
+    });
+    assert!(deck.validate_commander().is_ok());
+  }
+
+  #[test]
+  fn validate_commander_bad_size() {
+    let mut deck = commander_deck("Omnath, Locus of Mana");
+    deck.commanders.push(card!("Thrasios, Triton Hero").clone());
+    assert!(deck.validate_commander().is_err());
+    deck.commanders.clear();
+    assert!(deck.validate_commander().is_err());
+  }
+
+  #[test]
+  fn validate_commander_not_singleton() {
+    let mut deck = commander_deck("Omnath, Locus of Mana");
+    // Swap a Forest for a second copy of a nonland card
+    deck
+      .cards
+      .iter_mut()
+      .find(|cc| cc.card.name == "Forest")
+      .unwrap()
+      .count -= 1;
+    deck
+      .cards
+      .iter_mut()
+      .find(|cc| !cc.card.is_land())
+      .unwrap()
+      .count += 1;
+    assert!(deck.validate_commander().is_err());
+  }
+
+  #[test]
+  fn validate_brawl() {
+    let list = "
+      Commander
+      1 Omnath, Locus of Mana
+
+      Deck
+      1 Llanowar Elves
+      58 Forest
+    ";
+    let deck = decklist!(list).with_format(GameFormat::StandardBrawl);
+    assert!(deck.validate().is_ok());
+    assert_eq!(deck.commander_colors(), ManaCost::G_BITS);
+    // Historic Brawl decks have 100 cards
+    assert!(deck.with_format(GameFormat::Brawl).validate().is_err());
+    let off_color = decklist!(&list.replace("58 Forest", "57 Forest\n1 Opt"))
+      .with_format(GameFormat::StandardBrawl);
+    let error = off_color.validate().unwrap_err();
+    assert!(error.0.contains("\"Opt\" is outside the color identity"));
+    assert!(off_color.validate_commander().is_err());
+  }
+
+  #[test]
+  fn canonical_deck_identity() {
+    let deck = decklist!("4 Opt (XLN) 65\n20 Island\n2 Opt");
+    let reordered = decklist!("20 Island (M20) 265\n6 Opt");
+    assert_eq!(deck.canonical_list(), "20 Island\n6 Opt\n");
+    assert_eq!(deck.canonical_hash(), reordered.canonical_hash());
+    assert!(deck.same_cards(&reordered));
+    let other = decklist!("20 Island\n5 Opt");
+    assert_ne!(deck.canonical_hash(), other.canonical_hash());
+    assert!(!deck.same_cards(&other));
+    // FNV-1a of the empty list is the offset basis, whatever the build
+    assert_eq!(Deck::new().canonical_hash(), 0xcbf2_9ce4_8422_2325);
+    let commander = decklist!("Commander\n1 Omnath, Locus of Mana\n\nDeck\n59 Forest");
+    assert!(!commander.same_cards(&decklist!("59 Forest")));
+    let parsed = decklist!(&commander.canonical_list());
+    assert!(parsed.same_cards(&commander));
+  }
+
+  #[test]
+  fn deck_list_diagnostics() {
+    let list = "
+      20 Island
+      4 Opt #X=2
+      4 shock
+
+      Sideboard
+      2 Duress
+      # A comment
+      2 Negate
+    ";
+    let (deck, diagnostics) = Deck::from_list_with_diagnostics(list).unwrap();
+    assert_eq!(deck.len(), 28);
+    assert_eq!(diagnostics.len(), 2);
+    // The list starts with a line break, so "20 Island" is line 2
+    let ignored = diagnostics.of_kind(DiagnosticKind::IgnoredModifier);
+    assert_eq!(ignored[0].line, Some(3));
+    assert!(ignored[0].message.contains("\"Opt\""));
+    let skipped = diagnostics.of_kind(DiagnosticKind::SkippedLines);
+    assert_eq!(skipped[0].line, Some(5));
+    assert_eq!(
+      skipped[0].message,
+      "Skipped 2 line(s) after the empty line that ends the main deck"
+    );
+    let (_, diagnostics) = Deck::from_list_with_diagnostics("\n\n4 Opt #X=1").unwrap();
+    assert_eq!(diagnostics.diagnostics[0].line, Some(3));
+    let (_, diagnostics) = Deck::from_list_with_diagnostics("4 Opt\nSideboard\n2 Duress").unwrap();
+    assert_eq!(
+      diagnostics.diagnostics[0].message,
+      "Skipped 1 line(s) of the sideboard"
+    );
+    let (_, diagnostics) = Deck::from_list_with_diagnostics("20 Island\n4 Opt").unwrap();
+    assert!(diagnostics.is_empty());
+  }
+
+  #[test]
+  fn deck_to_arena_ids() {
+    let deck = decklist!("Commander\n1 Sigarda, Champion of Light\n\nDeck\n2 Opt");
+    let arena_deck = deck.to_arena_ids().unwrap();
+    assert_eq!(
+      arena_deck.commanders,
+      vec![card!("Sigarda, Champion of Light").arena_id]
+    );
+    assert_eq!(arena_deck.cards, vec![(card!("Opt").arena_id, 2)]);
+    // The printing of the card data is not on Arena
+    let error = decklist!("2 Opt\n1 Lovestruck Beast")
+      .to_arena_ids()
+      .unwrap_err();
+    assert_eq!(error.0, "Cards not on Arena: Lovestruck Beast");
   }
 
   #[test]
@@ -921,6 +2141,66 @@ mod tests {
     assert_eq!(deck.len(), 4);
   }
 
+  #[test]
+  fn missing_cards_with_owned_cap_and_extras() {
+    let deck = decklist!(
+      "
+      4 Opt
+      3 Teferi, Hero of Dominaria
+      20 Island
+      "
+    );
+    let owned = decklist!(
+      "
+      9 Opt
+      1 Teferi, Hero of Dominaria
+      "
+    );
+    let sideboard = decklist!("2 Opt");
+    let missing = deck.missing_cards(&owned, &CraftOptions::default());
+    assert_eq!(missing.len(), 2);
+    assert_eq!(missing.mythic_count(), 2);
+    assert!(missing.card_from_name("Island").is_none());
+    let options = CraftOptions {
+      owned_cap: Some(GameFormat::Standard.max_copies()),
+      extras: Some(sideboard),
+    };
+    let missing = deck.missing_cards(&owned, &options);
+    assert_eq!(missing.card_count_from_name("Opt").unwrap().count, 2);
+    assert_eq!(missing.common_count(), 2);
+    assert_eq!(missing.mythic_count(), 2);
+    let options = CraftOptions {
+      owned_cap: Some(4),
+      extras: None,
+    };
+    assert!(deck
+      .missing_cards(&owned, &options)
+      .card_from_name("Opt")
+      .is_none());
+  }
+
+  #[test]
+  fn deck_post_rotation() {
+    let deck = decklist!(
+      "
+      4 Nightpack Ambusher (M20) 185
+      4 Uro, Titan of Nature's Wrath (THB) 229
+      20 Forest
+      "
+    );
+    assert!(!deck.survives_rotation());
+    let rotating: Vec<&str> = deck
+      .rotating_cards()
+      .iter()
+      .map(|cc| cc.card.name.as_str())
+      .collect();
+    assert_eq!(rotating, vec!["Nightpack Ambusher"]);
+    let post_rotation = deck.post_rotation();
+    assert_eq!(post_rotation.len(), 24);
+    assert!(post_rotation.card_from_name("Nightpack Ambusher").is_none());
+    assert!(post_rotation.survives_rotation());
+  }
+
   #[test]
   fn code_contains_maybeboard() {
     let code = "
@@ -936,4 +2216,150 @@ mod tests {
     let deck = decklist!(code);
     assert_eq!(deck.len(), 4);
   }
+
+  #[test]
+  fn deck_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Deck>();
+    assert_send_sync::<DeckCard>();
+  }
+
+  #[test]
+  fn code_with_tags() {
+    let code = "
+      4 Shock (M19) 156 [removal, burn]
+      4 Lightning Strike [Removal]
+      2 Glorybringer [wincons]
+      4 Shivan Dragon []
+      20 Mountain
+      ";
+    let deck = Deck::from_list(code).unwrap();
+    assert_eq!(deck.card_count, 34);
+    let names = |tag: &str| -> Vec<String> {
+      let mut names: Vec<String> = deck
+        .cards_with_tag(tag)
+        .iter()
+        .map(|c| c.name.clone())
+        .collect();
+      names.sort();
+      names
+    };
+    assert_eq!(names("removal"), vec!["Shock"]);
+    assert_eq!(names("Removal"), vec!["Lightning Strike"]);
+    assert_eq!(names("burn"), vec!["Shock"]);
+    assert_eq!(names("wincons"), vec!["Glorybringer"]);
+    assert!(names("ramp").is_empty());
+    assert_eq!(deck.tags.len(), 4);
+  }
+
+  #[test]
+  fn curve_and_pip_counts() {
+    let deck = decklist!(
+      "
+      4 Ornithopter
+      4 Opt
+      4 Lightning Bolt
+      2 Counterspell
+      3 Vraska's Contempt
+      20 Island
+      "
+    );
+    assert_eq!(deck.curve(), vec![4, 8, 2, 0, 3]);
+    let pips = deck.pip_counts();
+    assert_eq!(pips.len(), 5);
+    assert_eq!(pips[0].total(), 0);
+    assert_eq!(
+      pips[1],
+      PipCount {
+        u: 4,
+        r: 4,
+        ..PipCount::default()
+      }
+    );
+    assert_eq!(pips[2].u, 4);
+    assert_eq!(pips[4].b, 6);
+    assert_eq!(pips.iter().map(|p| p.total()).sum::<usize>(), 18);
+  }
+
+  #[test]
+  fn insert_and_remove_count() {
+    let mut deck = decklist!(
+      "
+      4 Opt
+      20 Island
+      "
+    );
+    deck.insert_count(card!("Forest"), 2);
+    deck.insert_count(card!("Opt"), 1);
+    assert_eq!(deck.card_count, 27);
+    assert_eq!(deck.card_count_from_name("Forest").unwrap().count, 2);
+    assert_eq!(deck.card_count_from_name("Opt").unwrap().count, 5);
+    deck.remove_count(card!("Forest"), 3);
+    deck.remove_count(card!("Island"), 1);
+    assert_eq!(deck.card_count, 24);
+    assert!(deck.card_from_name("Forest").is_none());
+    assert_eq!(deck.card_count_from_name("Island").unwrap().count, 19);
+  }
+
+  #[test]
+  fn exact_probabilities() {
+    let deck = decklist!(
+      "
+      1 Teferi, Hero of Dominaria
+      24 Island
+      35 Opt
+      "
+    );
+    assert_eq!(deck.count(|c| c.is_land()), 24);
+    // Hypergeometric, 60, 24, 9, 3
+    let p = deck.p_lands_by_turn(3, 3, PlayOrder::First);
+    assert!(f64::abs(p - 0.7887) < 0.001, "{}", p);
+    // Hypergeometric, 60, 1, 11, 1
+    let teferi = card!("Teferi, Hero of Dominaria");
+    let p = deck.p_card_by_turn(teferi, 1, 5, PlayOrder::First);
+    assert!(f64::abs(p - 11.0 / 60.0) < 1e-9, "{}", p);
+    let is_land = |c: &Card| c.is_land();
+    let is_teferi = |c: &Card| c.hash == teferi.hash;
+    let p = deck.p_at_least_each(&[(&is_land, 0), (&is_teferi, 1)], 11);
+    assert!(f64::abs(p - 11.0 / 60.0) < 1e-9, "{}", p);
+    let p = deck.p_at_least(|c| c.is_land(), 0, 7);
+    assert!(f64::abs(p - 1.0) < 1e-9, "{}", p);
+  }
+
+  #[test]
+  fn outs_after_scry() {
+    let deck = decklist!(
+      "
+      20 Island
+      30 Opt
+      10 Shock
+      "
+    );
+    let opt = card!("Opt");
+    let known = vec![card!("Island"); 10]
+      .into_iter()
+      .chain(vec![opt; 10])
+      .collect::<Vec<_>>();
+    let library = deck.library_without(&known).unwrap();
+    assert_eq!(library.len(), 40);
+    assert!(deck.library_without(&[card!("Forest")]).is_err());
+    let is_shock = |c: &Card| c.name == "Shock";
+    let p = library.p_at_least_after_scry(is_shock, 1, 1, 0);
+    assert!(f64::abs(p - 0.25) < 1e-9, "{}", p);
+    // Scry 1 finds an out unless neither of the top 2 cards is one
+    let p = library.p_at_least_after_scry(is_shock, 1, 1, 1);
+    assert!(
+      f64::abs(p - (1.0 - 30.0 / 40.0 * 29.0 / 39.0)) < 1e-9,
+      "{}",
+      p
+    );
+    // Surveil 2 then draw 2, needing both outs, summed over the outs among the top 2 cards
+    let p = library.p_at_least_after_scry(is_shock, 2, 2, 2);
+    let expected = 45.0 / 780.0 + 300.0 / 780.0 * 9.0 / 38.0 + 435.0 / 780.0 * 45.0 / 703.0;
+    assert!(f64::abs(p - expected) < 1e-9, "{}", p);
+    assert!(
+      library.p_at_least_after_scry(|c| c.is_land(), 1, 3, 2)
+        > library.p_at_least(|c| c.is_land(), 1, 3)
+    );
+  }
 }
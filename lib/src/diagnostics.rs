@@ -0,0 +1,73 @@
+//! # Diagnostics
+//!
+//! Warnings about input that was accepted but not taken as written, e.g. deck list lines
+//! that were skipped or card names that resolved to another card. They are returned
+//! alongside results, so frontends can show them to users, rather than logged.
+
+/// DiagnosticKind is the cause of a `Diagnostic`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum DiagnosticKind {
+  /// Deck list lines that were not parsed, e.g. the sideboard
+  SkippedLines,
+  /// A card name that resolved to a card of another name, e.g. a localized name
+  NameFallback,
+  /// A deck list modifier that does not apply to the card, e.g. X= for a card without X
+  IgnoredModifier,
+  /// An Arena id without a card in the card data
+  UnknownArenaId,
+}
+
+/// Diagnostic is a single warning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Diagnostic {
+  pub kind: DiagnosticKind,
+  /// The one based line of the input the warning is about, if any
+  pub line: Option<usize>,
+  pub message: String,
+}
+
+/// Diagnostics collects the warnings of an operation
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Diagnostics {
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a warning of `kind` about the one based `line` of the input, if any
+  pub fn push(&mut self, kind: DiagnosticKind, line: Option<usize>, message: String) {
+    self.diagnostics.push(Diagnostic {
+      kind,
+      line,
+      message,
+    });
+  }
+
+  /// Adds the warnings of `other`
+  pub fn extend(&mut self, other: Diagnostics) {
+    self.diagnostics.extend(other.diagnostics);
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.diagnostics.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.diagnostics.len()
+  }
+
+  /// Returns the warnings of `kind`
+  pub fn of_kind(&self, kind: DiagnosticKind) -> Vec<&Diagnostic> {
+    self
+      .diagnostics
+      .iter()
+      .filter(|diagnostic| diagnostic.kind == kind)
+      .collect()
+  }
+}
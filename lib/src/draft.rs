@@ -0,0 +1,479 @@
+//! # Draft pick evaluation
+//!
+//! Scores the cards of a draft pack from a user supplied rating source, like a tier list,
+//! adjusted for how well each card fits the colors of the drafter's pool, and flags the
+//! picks that add to the playsets missing from the drafter's collection, and builds
+//! suggested 40 card decks from sealed or draft pools. For a finished build, simulations
+//! compare the candidates for its last slot, like a 17th land or a 23rd spell.
+use crate::card::{Card, CardKind, ManaColor};
+use crate::data::ALL_CARDS;
+use crate::deck::{Deck, DeckBuilder};
+use crate::manabase::manabase_score;
+use crate::mulligan::Mulligan;
+use crate::simulation::{simulate_many, SimulationConfig};
+use std::collections::HashMap;
+
+/// The number of copies of a card in a playset
+const PLAYSET_SIZE: usize = 4;
+
+/// The number of cards in a limited deck
+pub const LIMITED_DECK_SIZE: usize = 40;
+
+/// The number of lands in a limited deck
+pub const LIMITED_LAND_COUNT: usize = 17;
+
+/// The mana value from which a spell counts towards `MAX_EXPENSIVE_SPELLS`
+const EXPENSIVE_CMC: u8 = 5;
+
+/// The most spells with a mana value of at least `EXPENSIVE_CMC` in a limited deck,
+/// unless the pool is short on playable spells
+const MAX_EXPENSIVE_SPELLS: usize = 5;
+
+/// The basic lands of each color, in `COLORS` order
+const BASIC_LAND_NAMES: [&str; 5] = ["Plains", "Island", "Swamp", "Mountain", "Forest"];
+
+/// The colors a pool can be built around
+const COLORS: [ManaColor; 5] = [
+  ManaColor::White,
+  ManaColor::Blue,
+  ManaColor::Black,
+  ManaColor::Red,
+  ManaColor::Green,
+];
+
+/// Ratings are pick ratings keyed by card name, where higher is better
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Ratings {
+  /// Ratings keyed by lowercase card name
+  pub ratings: HashMap<String, f64>,
+}
+
+/// DraftConfig configures `evaluate_picks`
+#[derive(Debug, Clone)]
+pub struct DraftConfig<'a> {
+  pub ratings: &'a Ratings,
+  /// The rating of cards missing from `ratings`
+  pub unrated: f64,
+  /// The score added to a card whose colored mana symbols are all in the two main colors
+  /// of the pool, scaled down for cards that are only partly in those colors
+  pub color_weight: f64,
+  /// The drafter's collection, to flag picks that add to missing playsets
+  pub owned: Option<&'a Deck>,
+}
+
+/// PickScore is the evaluation of a single card of a pack, see `evaluate_picks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickScore {
+  pub name: String,
+  /// The rating of the card, or `DraftConfig::unrated`
+  pub rating: f64,
+  /// The fraction of the colored mana symbols of the card in the two main colors of the
+  /// pool, 1 for colorless cards and for pools without colored cards
+  pub color_fit: f64,
+  /// The rating plus the weighted color fit
+  pub score: f64,
+  /// The number of copies the collection misses for a playset, 0 without a collection
+  pub playset_missing: usize,
+  /// True if the pick completes the playset in the collection
+  pub completes_playset: bool,
+}
+
+/// LimitedDeck is a deck suggested by `build_limited_decks`
+#[derive(Debug, Clone)]
+pub struct LimitedDeck {
+  /// The two colors of the deck
+  pub colors: Vec<ManaColor>,
+  pub deck: Deck,
+  /// The sum of the ratings of the spells in the deck
+  pub score: f64,
+}
+
+/// SlotOption is the castability of a deck with a candidate card in its last slot,
+/// see `advise_last_slot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotOption {
+  pub name: String,
+  /// The name of the basic land the candidate replaces
+  pub replaces: String,
+  /// The castability of the spells of the submitted deck, see `manabase::manabase_score`
+  pub castability: f64,
+  /// The castability minus that of the submitted deck
+  pub delta: f64,
+}
+
+/// SlotAdvice compares the candidates for the last slot of a deck, see `advise_last_slot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotAdvice {
+  /// The castability of the submitted deck
+  pub castability: f64,
+  /// The candidates, best first
+  pub options: Vec<SlotOption>,
+  /// The name of the best candidate if it improves on the submitted deck, or None to keep
+  /// the deck as it is
+  pub recommendation: Option<String>,
+}
+
+impl Ratings {
+  /// Returns the ratings of `json`, an object mapping card names to ratings,
+  /// e.g. `{"Lightning Strike": 3.5, "Opt": 2.0}`
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    let ratings: HashMap<String, f64> = serde_json::from_str(json)?;
+    Ok(Self {
+      ratings: ratings
+        .into_iter()
+        .map(|(name, rating)| (name.to_lowercase(), rating))
+        .collect(),
+    })
+  }
+
+  /// Returns the rating of `card`, if any
+  pub fn rating(&self, card: &Card) -> Option<f64> {
+    self.ratings.get(&card.name.to_lowercase()).copied()
+  }
+}
+
+/// Returns the score of each card in `pack` for a drafter with the cards in `pool`,
+/// best pick first
+pub fn evaluate_picks(pack: &[&Card], pool: &[&Card], config: &DraftConfig) -> Vec<PickScore> {
+  let main_colors = main_colors(pool);
+  let mut picks: Vec<PickScore> = pack
+    .iter()
+    .map(|card| {
+      let rating = config.ratings.rating(card).unwrap_or(config.unrated);
+      let color_fit = color_fit(card, &main_colors);
+      let owned = config.owned.map_or(0, |owned| {
+        owned
+          .card_count_from_name(&card.name)
+          .map_or(0, |cc| cc.count)
+      });
+      let playset_missing = if config.owned.is_some() {
+        PLAYSET_SIZE.saturating_sub(owned)
+      } else {
+        0
+      };
+      PickScore {
+        name: card.name.clone(),
+        rating,
+        color_fit,
+        score: rating + config.color_weight * color_fit,
+        playset_missing,
+        completes_playset: playset_missing == 1,
+      }
+    })
+    .collect();
+  picks.sort_by(|a, b| b.score.total_cmp(&a.score));
+  picks
+}
+
+/// Returns a suggested 40 card deck with 17 lands for each pair of colors with playable
+/// spells in `pool`, the cards of a sealed or draft pool, best deck first. Cards without a
+/// rating in `ratings` are rated `unrated`
+///
+/// Each deck plays the best rated spells of `pool` that only need its two colors, with at
+/// most `MAX_EXPENSIVE_SPELLS` spells of mana value `EXPENSIVE_CMC` or more unless there are
+/// not enough other spells. The lands are the nonbasic lands of the pool that only tap for
+/// the colors of the deck, and basic lands split by the colored mana symbols of the spells.
+/// Decks with fewer than 23 playable spells make up the difference with basic lands.
+pub fn build_limited_decks(pool: &[&Card], ratings: &Ratings, unrated: f64) -> Vec<LimitedDeck> {
+  let rating = |card: &Card| ratings.rating(card).unwrap_or(unrated);
+  let basics: Vec<&Card> = BASIC_LAND_NAMES
+    .iter()
+    .map(|name| ALL_CARDS.card_from_name(name).expect("basic lands exist"))
+    .collect();
+  let mut spells: Vec<&Card> = pool.iter().copied().filter(|c| !c.is_land()).collect();
+  spells.sort_by(|a, b| rating(b).total_cmp(&rating(a)));
+  let mut decks = Vec::new();
+  for (first, first_color) in COLORS.iter().enumerate() {
+    for (second, second_color) in COLORS.iter().enumerate().skip(first + 1) {
+      let colors = [*first_color, *second_color];
+      let in_colors = |card: &Card| {
+        COLORS
+          .iter()
+          .all(|color| colors.contains(color) || card.mana_cost.count(*color) == 0)
+      };
+      let playable: Vec<&Card> = spells.iter().copied().filter(|c| in_colors(c)).collect();
+      if playable.is_empty() {
+        continue;
+      }
+      let spell_count = LIMITED_DECK_SIZE - LIMITED_LAND_COUNT;
+      let (cheap, expensive): (Vec<&Card>, Vec<&Card>) = playable
+        .iter()
+        .partition(|card| card.mana_cost.cmc() < EXPENSIVE_CMC);
+      let mut chosen: Vec<&Card> = cheap
+        .iter()
+        .chain(expensive.iter().take(MAX_EXPENSIVE_SPELLS))
+        .copied()
+        .collect();
+      chosen.sort_by(|a, b| rating(b).total_cmp(&rating(a)));
+      chosen.truncate(spell_count);
+      let extra = expensive.iter().skip(MAX_EXPENSIVE_SPELLS);
+      let missing = spell_count - chosen.len();
+      chosen.extend(extra.take(missing));
+      let lands: Vec<&Card> = pool
+        .iter()
+        .copied()
+        .filter(|card| card.is_land() && card.kind != CardKind::BasicLand && in_colors(card))
+        .filter(|card| colors.iter().any(|color| card.mana_cost.count(*color) > 0))
+        .take(LIMITED_LAND_COUNT)
+        .collect();
+      let basic_count = LIMITED_DECK_SIZE - chosen.len() - lands.len();
+      let pips: Vec<usize> = colors
+        .iter()
+        .map(|color| {
+          chosen
+            .iter()
+            .map(|card| card.mana_cost.count(*color) as usize)
+            .sum()
+        })
+        .collect();
+      let mut basic_counts = [0; 2];
+      for _ in 0..basic_count {
+        // Add the basic land of the color with the most mana symbols per basic land,
+        // comparing pips[i] / (basic_counts[i] + 1) without division
+        let i = if pips[0] * (basic_counts[1] + 1) >= pips[1] * (basic_counts[0] + 1) {
+          0
+        } else {
+          1
+        };
+        basic_counts[i] += 1;
+      }
+      let mut builder = DeckBuilder::new();
+      for card in chosen.iter().chain(lands.iter()) {
+        builder = builder.insert((*card).clone());
+      }
+      for (basic, count) in [first, second].iter().zip(basic_counts.iter()) {
+        if *count > 0 {
+          builder = builder.insert_count(basics[*basic].clone(), *count);
+        }
+      }
+      decks.push(LimitedDeck {
+        colors: colors.to_vec(),
+        deck: builder.build(),
+        score: chosen.iter().map(|card| rating(card)).sum(),
+      });
+    }
+  }
+  decks.sort_by(|a, b| b.score.total_cmp(&a.score));
+  decks
+}
+
+/// Returns the two colors with the most colored mana symbols in `pool`, or no colors
+/// if the pool has no colored cards
+fn main_colors(pool: &[&Card]) -> Vec<ManaColor> {
+  let mut counts: Vec<(ManaColor, usize)> = COLORS
+    .iter()
+    .map(|color| {
+      let count = pool
+        .iter()
+        .filter(|card| !card.is_land())
+        .map(|card| card.mana_cost.count(*color) as usize)
+        .sum();
+      (*color, count)
+    })
+    .filter(|(_, count)| *count > 0)
+    .collect();
+  counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+  counts.into_iter().take(2).map(|(color, _)| color).collect()
+}
+
+/// Returns the fraction of the colored mana symbols of `card` in `main_colors`
+fn color_fit(card: &Card, main_colors: &[ManaColor]) -> f64 {
+  let colored: usize = COLORS
+    .iter()
+    .map(|color| card.mana_cost.count(*color) as usize)
+    .sum();
+  if colored == 0 || main_colors.is_empty() {
+    return 1.0;
+  }
+  let in_colors: usize = main_colors
+    .iter()
+    .map(|color| card.mana_cost.count(*color) as usize)
+    .sum();
+  in_colors as f64 / colored as f64
+}
+
+/// Returns the comparison of `config.deck` with a copy of the deck for each of `candidates`,
+/// where the candidate replaces one copy of the basic land with the most copies, other than
+/// the candidate itself. A spell candidate answers "17th land or 23rd spell", and a land
+/// candidate, like a dual land or a tap land, which land to play. Every deck is simulated
+/// with the same seed, and scored by the castability of the spells of the submitted deck.
+/// Candidates are left out when the deck has no basic land to replace
+pub fn advise_last_slot<M: Mulligan + Sync>(
+  config: &SimulationConfig<M>,
+  candidates: &[&Card],
+) -> SlotAdvice {
+  let deck = config.deck;
+  let mut names = Vec::new();
+  let mut decks = vec![deck.clone()];
+  for candidate in candidates {
+    let basic = deck
+      .cards
+      .iter()
+      .filter(|cc| cc.card.kind == CardKind::BasicLand && cc.card.hash != candidate.hash)
+      .max_by_key(|cc| cc.count);
+    if let Some(basic) = basic {
+      let mut variant = deck.clone();
+      variant.remove_count(&basic.card, 1);
+      variant.insert_count(candidate, 1);
+      names.push((candidate.name.clone(), basic.card.name.clone()));
+      decks.push(variant);
+    }
+  }
+  let simulations = simulate_many(&decks, config);
+  let baseline = manabase_score(&simulations[0], deck);
+  let mut options: Vec<SlotOption> = names
+    .into_iter()
+    .zip(&simulations[1..])
+    .map(|((name, replaces), sim)| {
+      let castability = manabase_score(sim, deck);
+      SlotOption {
+        name,
+        replaces,
+        castability,
+        delta: castability - baseline,
+      }
+    })
+    .collect();
+  options.sort_by(|a, b| b.castability.total_cmp(&a.castability));
+  let recommendation = options
+    .first()
+    .filter(|option| option.delta > 0.0)
+    .map(|option| option.name.clone());
+  SlotAdvice {
+    castability: baseline,
+    options,
+    recommendation,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::draft::*;
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::Never;
+
+  #[test]
+  fn picks_follow_ratings_and_colors() {
+    let ratings =
+      Ratings::from_json(r#"{"Lightning Strike": 3.0, "Opt": 2.0, "Llanowar Elves": 3.5}"#)
+        .unwrap();
+    let pack = vec![
+      card!("Opt"),
+      card!("Lightning Strike"),
+      card!("Llanowar Elves"),
+      card!("Shock"),
+    ];
+    let pool = vec![card!("Shock"), card!("Opt"), card!("Lightning Strike")];
+    let owned = decklist!(
+      "
+      3 Lightning Strike
+      4 Opt
+      "
+    );
+    let config = DraftConfig {
+      ratings: &ratings,
+      unrated: 1.0,
+      color_weight: 2.0,
+      owned: Some(&owned),
+    };
+    let picks = evaluate_picks(&pack, &pool, &config);
+    let names: Vec<&str> = picks.iter().map(|pick| pick.name.as_str()).collect();
+    assert_eq!(
+      names,
+      vec!["Lightning Strike", "Opt", "Llanowar Elves", "Shock"]
+    );
+    let strike = &picks[0];
+    assert_eq!(strike.color_fit, 1.0);
+    assert_eq!(strike.playset_missing, 1);
+    assert!(strike.completes_playset);
+    let opt = &picks[1];
+    assert_eq!(opt.playset_missing, 0);
+    assert!(!opt.completes_playset);
+    let elves = &picks[2];
+    assert_eq!(elves.color_fit, 0.0);
+    assert_eq!(elves.playset_missing, 4);
+    // Without a pool, colors do not matter
+    let picks = evaluate_picks(&pack, &[], &config);
+    assert_eq!(picks[0].name, "Llanowar Elves");
+  }
+
+  #[test]
+  fn limited_decks_play_best_colors() {
+    let ratings = Ratings::from_json(
+      r#"{"Lightning Strike": 4.0, "Shock": 3.0, "Opt": 2.0, "Vraska's Contempt": 1.0}"#,
+    )
+    .unwrap();
+    let mut pool = Vec::new();
+    for (name, count) in &[
+      ("Lightning Strike", 8),
+      ("Shock", 8),
+      ("Opt", 8),
+      ("Llanowar Elves", 6),
+      ("Vraska's Contempt", 6),
+      ("Drowned Catacomb", 4),
+      ("Island", 5),
+    ] {
+      for _ in 0..*count {
+        pool.push(card!(name));
+      }
+    }
+    let decks = build_limited_decks(&pool, &ratings, 0.5);
+    let best = &decks[0];
+    assert_eq!(best.colors, vec![ManaColor::Blue, ManaColor::Red]);
+    assert_eq!(best.deck.len(), LIMITED_DECK_SIZE);
+    assert_eq!(best.deck.count(|card| card.is_land()), LIMITED_LAND_COUNT);
+    let count = |deck: &Deck, name: &str| deck.card_count_from_name(name).map_or(0, |cc| cc.count);
+    assert_eq!(count(&best.deck, "Lightning Strike"), 8);
+    assert_eq!(count(&best.deck, "Shock"), 8);
+    assert_eq!(count(&best.deck, "Opt"), 7);
+    assert!(count(&best.deck, "Mountain") > count(&best.deck, "Island"));
+    assert_eq!(count(&best.deck, "Drowned Catacomb"), 0);
+    // Alternatives come after the best deck, and use the pool's dual lands in their colors
+    let blue_black = decks
+      .iter()
+      .find(|deck| deck.colors == vec![ManaColor::Blue, ManaColor::Black])
+      .unwrap();
+    assert!(blue_black.score < best.score);
+    assert_eq!(blue_black.deck.len(), LIMITED_DECK_SIZE);
+    assert_eq!(count(&blue_black.deck, "Drowned Catacomb"), 4);
+  }
+
+  #[test]
+  fn last_slot_prefers_the_missing_color() {
+    let deck = decklist!(
+      "
+      14 Island
+      3 Swamp
+      11 Opt
+      12 Vraska's Contempt
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 2000,
+      draw_count: 8,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(3),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let advice = advise_last_slot(
+      &config,
+      &[card!("Swamp"), card!("Drowned Catacomb"), card!("Opt")],
+    );
+    assert_eq!(advice.options.len(), 3);
+    assert!(advice
+      .options
+      .iter()
+      .all(|option| option.replaces == "Island"));
+    // Both lands add a black source, and cutting a land for a spell only hurts
+    assert_eq!(advice.options[2].name, "Opt");
+    assert!(advice.options[2].delta < 0.0);
+    assert!(advice.options[0].delta > 0.0);
+    assert_eq!(
+      advice.recommendation.as_ref(),
+      Some(&advice.options[0].name)
+    );
+  }
+}
@@ -0,0 +1,132 @@
+//! # C bindings
+//!
+//! A stable C ABI, enabled with the `ffi` feature, for embedding landlord in programs
+//! written in other languages, like an Arena overlay written in C#. Inputs are nul
+//! terminated UTF-8 strings, and each function returns a nul terminated JSON string,
+//! either `{"ok": ...}` or `{"error": "..."}`, that must be released with
+//! `landlord_free_result`. See `include/landlord.h` for the C declarations.
+use crate::deck::Deck;
+use crate::run;
+use serde::Serialize;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// FfiResult is the JSON envelope of the results returned over the C ABI
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum FfiResult<T> {
+  Ok(T),
+  Error(String),
+}
+
+/// Parses the deck list `deck_str` and returns the serialized `Deck`
+///
+/// # Safety
+///
+/// `deck_str` must be null or a valid pointer to a nul terminated string
+#[no_mangle]
+pub unsafe extern "C" fn landlord_parse_deck(deck_str: *const c_char) -> *mut c_char {
+  let result = match str_from_ptr(deck_str) {
+    Ok(deck_str) => match Deck::from_list(deck_str) {
+      Ok(deck) => FfiResult::Ok(deck),
+      Err(e) => FfiResult::Error(format!("Error parsing deck: {}", e.0)),
+    },
+    Err(e) => FfiResult::Error(e),
+  };
+  into_json_ptr(&result)
+}
+
+/// Simulates the deck list `deck_str` with the JSON `config_json`, and returns a
+/// `SimulationResult`. The configuration is the same as the one of the `wasm`
+/// feature's `run_simulation`
+///
+/// # Safety
+///
+/// `deck_str` and `config_json` must be null or valid pointers to nul terminated strings
+#[no_mangle]
+pub unsafe extern "C" fn landlord_simulate(
+  deck_str: *const c_char,
+  config_json: *const c_char,
+) -> *mut c_char {
+  let result = match (str_from_ptr(deck_str), str_from_ptr(config_json)) {
+    (Ok(deck_str), Ok(config_json)) => match run::run_simulation(deck_str, config_json) {
+      Ok(output) => FfiResult::Ok(output),
+      Err(e) => FfiResult::Error(e),
+    },
+    (Err(e), _) | (_, Err(e)) => FfiResult::Error(e),
+  };
+  into_json_ptr(&result)
+}
+
+/// Releases a string returned by any other `landlord_` function
+///
+/// # Safety
+///
+/// `result` must be null or a pointer returned by a `landlord_` function that has
+/// not been released yet
+#[no_mangle]
+pub unsafe extern "C" fn landlord_free_result(result: *mut c_char) {
+  if !result.is_null() {
+    drop(CString::from_raw(result));
+  }
+}
+
+/// Returns the string at `ptr`, or an error message if it is null or not UTF-8
+unsafe fn str_from_ptr<'a>(ptr: *const c_char) -> Result<&'a str, String> {
+  if ptr.is_null() {
+    return Err("Unexpected null string".to_string());
+  }
+  CStr::from_ptr(ptr)
+    .to_str()
+    .map_err(|e| format!("Invalid UTF-8 string: {}", e))
+}
+
+/// Returns `result` as JSON in a string owned by the caller
+fn into_json_ptr<T: Serialize>(result: &FfiResult<T>) -> *mut c_char {
+  let json = serde_json::to_string(result)
+    .unwrap_or_else(|e| format!("{{\"error\": \"Error serializing result: {}\"}}", e));
+  // JSON escapes nul characters, so the string has no interior nul
+  CString::new(json)
+    .expect("JSON has no interior nul")
+    .into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::ffi::*;
+
+  fn call<F: FnOnce() -> *mut c_char>(f: F) -> serde_json::Value {
+    let ptr = f();
+    let json = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+    unsafe { landlord_free_result(ptr) };
+    serde_json::from_str(&json).unwrap()
+  }
+
+  #[test]
+  fn parse_deck() {
+    let deck_str = CString::new("4 Opt\n20 Island").unwrap();
+    let result = call(|| unsafe { landlord_parse_deck(deck_str.as_ptr()) });
+    assert_eq!(result["ok"]["card_count"], 24);
+    let deck_str = CString::new("4 Not A Card").unwrap();
+    let result = call(|| unsafe { landlord_parse_deck(deck_str.as_ptr()) });
+    assert!(result["error"].is_string());
+    let result = call(|| unsafe { landlord_parse_deck(std::ptr::null()) });
+    assert!(result["error"].is_string());
+  }
+
+  #[test]
+  fn simulate() {
+    let deck_str = CString::new("20 Island\n40 Opt").unwrap();
+    let config_json = CString::new(r#"{"run_count": 100, "draw_count": 2, "seed": 1}"#).unwrap();
+    let result = call(|| unsafe { landlord_simulate(deck_str.as_ptr(), config_json.as_ptr()) });
+    assert_eq!(result["ok"]["seed"], 1);
+    assert_eq!(result["ok"]["cards"][0]["name"], "Opt");
+    let result = call(|| unsafe { landlord_simulate(deck_str.as_ptr(), std::ptr::null()) });
+    assert!(result["error"].is_string());
+  }
+
+  #[test]
+  fn free_null_result() {
+    unsafe { landlord_free_result(std::ptr::null_mut()) };
+  }
+}
@@ -0,0 +1,225 @@
+//! # Card function classification
+//!
+//! Classifies nonland cards as removal, ramp, card draw or counterspells from patterns in
+//! their oracle text, and tags the cards of a deck with their functions, so that deck stats
+//! like the removal density work without tagging every card by hand. The embedded card data
+//! doesn't keep oracle text, so `CardFunctions` are built from Scryfall card data, e.g. by
+//! scryfall2landlord, and loaded from JSON.
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::scryfall::ScryfallCard;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// CardFunction is a role a card plays in a deck
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CardFunction {
+  Removal,
+  Ramp,
+  Draw,
+  Counterspell,
+}
+
+impl CardFunction {
+  /// Returns the deck tag of the function, see `Deck::tag`
+  pub fn tag(self) -> &'static str {
+    match self {
+      CardFunction::Removal => "removal",
+      CardFunction::Ramp => "ramp",
+      CardFunction::Draw => "draw",
+      CardFunction::Counterspell => "counterspell",
+    }
+  }
+}
+
+lazy_static! {
+  /// The oracle text patterns of each function, matched against lowercase oracle text
+  static ref PATTERNS: Vec<(CardFunction, Regex)> = [
+    (
+      CardFunction::Removal,
+      concat!(
+        r"(destroy|exile) (up to \w+ )?(another )?target (\w+ )*",
+        r"(creature|planeswalker|permanent|artifact|enchantment)",
+      ),
+    ),
+    (
+      CardFunction::Removal,
+      concat!(
+        r"deals (\d+|x) damage to ",
+        r"(any target|target (creature|planeswalker|player or planeswalker))",
+      ),
+    ),
+    (CardFunction::Removal, r"target creature gets -\d+/-\d+"),
+    (CardFunction::Removal, r"fights (target|another target|up to one target)"),
+    (CardFunction::Ramp, r"\badd (\{|one mana|two mana|three mana)"),
+    (
+      CardFunction::Ramp,
+      concat!(
+        r"search your library for (a|an|up to \w+) (basic )?",
+        r"(land|forest|island|mountain|plains|swamp)",
+      ),
+    ),
+    (
+      CardFunction::Ramp,
+      r"put (a|up to \w+) land cards? from your hand onto the battlefield",
+    ),
+    (CardFunction::Draw, r"\bdraws? (a|an additional|two|three|four|x) cards?"),
+    (CardFunction::Counterspell, r"counter target (\w+ )*spell"),
+  ]
+  .iter()
+  .map(|(function, pattern)| {
+    (
+      *function,
+      Regex::new(pattern).expect("Failed to compile card function regex"),
+    )
+  })
+  .collect();
+}
+
+/// Returns the functions of a card with `oracle_text` and `type_line`, in `CardFunction`
+/// order. Lands have no functions, so that lands that tap for mana aren't ramp
+pub fn functions_from_oracle_text(oracle_text: &str, type_line: &str) -> Vec<CardFunction> {
+  if type_line.contains("Land") {
+    return Vec::new();
+  }
+  let text = oracle_text.to_lowercase();
+  let mut functions: Vec<CardFunction> = PATTERNS
+    .iter()
+    .filter(|(_, pattern)| pattern.is_match(&text))
+    .map(|(function, _)| *function)
+    .collect();
+  functions.sort();
+  functions.dedup();
+  functions
+}
+
+/// CardFunctions are the functions of cards keyed by card name
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CardFunctions {
+  /// Functions keyed by lowercase card name
+  pub functions: BTreeMap<String, Vec<CardFunction>>,
+}
+
+impl CardFunctions {
+  /// Returns the functions of the cards in `cards`, including their faces, leaving out the
+  /// cards without any function
+  pub fn from_scryfall(cards: &[ScryfallCard]) -> Self {
+    let mut functions = BTreeMap::new();
+    for card in cards.iter().chain(cards.iter().flat_map(|c| &c.card_faces)) {
+      let card_functions = functions_from_oracle_text(&card.oracle_text, &card.type_line);
+      if !card_functions.is_empty() {
+        functions.insert(card.name.to_lowercase(), card_functions);
+      }
+    }
+    Self { functions }
+  }
+
+  /// Returns the card functions of the JSON serialization of `CardFunctions`
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    serde_json::from_str(json)
+  }
+
+  /// Returns the functions of `card`
+  pub fn functions(&self, card: &Card) -> &[CardFunction] {
+    self
+      .functions
+      .get(&card.name.to_lowercase())
+      .map_or(&[], Vec::as_slice)
+  }
+
+  /// Tags each card of `deck`, including commanders, with the tags of its functions, in
+  /// addition to any tags it already has
+  pub fn tag_deck(&self, deck: &mut Deck) {
+    let cards: Vec<Card> = deck
+      .cards
+      .iter()
+      .map(|cc| &cc.card)
+      .chain(deck.commanders.iter())
+      .cloned()
+      .collect();
+    for card in &cards {
+      for function in self.functions(card) {
+        deck.tag(function.tag(), card);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::functions::*;
+
+  #[test]
+  fn functions_from_oracle_text_patterns() {
+    let functions = |text: &str| functions_from_oracle_text(text, "Instant");
+    assert_eq!(
+      functions("Shock deals 2 damage to any target."),
+      vec![CardFunction::Removal]
+    );
+    assert_eq!(
+      functions("Destroy target creature or planeswalker. You gain 2 life."),
+      vec![CardFunction::Removal]
+    );
+    assert_eq!(functions("Scry 1.\nDraw a card."), vec![CardFunction::Draw]);
+    assert_eq!(
+      functions("Counter target noncreature spell."),
+      vec![CardFunction::Counterspell]
+    );
+    assert_eq!(
+      functions(
+        "Search your library for a basic land card, put that card onto the battlefield \
+         tapped, then shuffle."
+      ),
+      vec![CardFunction::Ramp]
+    );
+    assert_eq!(
+      functions_from_oracle_text("{T}: Add {G}.", "Creature — Elf Druid"),
+      vec![CardFunction::Ramp]
+    );
+    assert!(functions_from_oracle_text("{T}: Add {G}.", "Basic Land — Forest").is_empty());
+    assert!(functions("You gain 3 life.").is_empty());
+  }
+
+  #[test]
+  fn tag_deck_with_card_functions() {
+    let scryfall_cards: Vec<ScryfallCard> = serde_json::from_str(
+      r#"[
+        {
+          "name": "Shock",
+          "object": "card",
+          "oracle_text": "Shock deals 2 damage to any target.",
+          "type_line": "Instant"
+        },
+        {
+          "name": "Opt",
+          "object": "card",
+          "oracle_text": "Scry 1.\nDraw a card.",
+          "type_line": "Instant"
+        },
+        {
+          "name": "Island",
+          "object": "card",
+          "oracle_text": "({T}: Add {U}.)",
+          "type_line": "Basic Land — Island"
+        }
+      ]"#,
+    )
+    .unwrap();
+    let functions = CardFunctions::from_scryfall(&scryfall_cards);
+    assert_eq!(functions.functions.len(), 2);
+    let json = serde_json::to_string(&functions).unwrap();
+    let functions = CardFunctions::from_json(&json).unwrap();
+    let mut deck = decklist!(
+      "
+      20 Island
+      20 Shock
+      20 Opt
+      "
+    );
+    functions.tag_deck(&mut deck);
+    assert_eq!(deck.cards_with_tag("removal")[0].name, "Shock");
+    assert_eq!(deck.cards_with_tag("draw")[0].name, "Opt");
+    assert!(deck.cards_with_tag("ramp").is_empty());
+    assert_eq!(deck.tag_density("removal"), 0.5);
+  }
+}
@@ -1,17 +1,22 @@
 //! # Simulation hands and auto tap algorithm
 use crate::bipartite::maximum_bipartite_matching;
 use crate::card::{Card, CardKind, ManaCost};
-use crate::mulligan::Mulligan;
+use crate::mulligan::{Mulligan, MulliganCause};
 use rand::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 /// Hand represents the opening hand after the mulligan process, along with any cards drawn
 /// Note that the card draw is in order and represents the cards drawn during the draw step
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Hand {
   cards: Vec<SimCard>,
   pub starting_hand_size: usize,
   pub opening_hand_size: usize,
   pub mulligan_count: usize,
+  /// The cause of each hand mulliganed away, in order
+  pub mulligans: Vec<MulliganCause>,
 }
 
 /// SimCard is an internal compact card representation
@@ -23,12 +28,216 @@ pub struct SimCard {
   pub mana_cost: ManaCost,
 }
 
+/// ManaSource represents a nonland card that can tap for mana, like Llanowar Elves or Lotus Petal
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct ManaSource {
+  /// The hash of the card that produces mana
+  pub hash: u64,
+  /// The colors of mana the card can produce, represented in the same fashion as land cards
+  pub mana_cost: ManaCost,
+  /// The number of turns after the card is drawn before it can produce mana,
+  /// e.g. 1 for Llanowar Elves (summoning sickness) and 0 for Lotus Petal
+  pub delay: usize,
+}
+
+/// ConditionalMana represents a land that taps for more colors, or any mana at all, only
+/// when casting a creature spell, like Unclaimed Territory or Ancient Ziggurat
+struct ConditionalMana {
+  /// The mana the land can produce for a creature spell
+  creature: ManaCost,
+  /// The mana the land can produce for any other spell, if any
+  other: Option<ManaCost>,
+}
+
+// The card data represents these lands by the mana they produce for any spell, so the
+// solver looks up the mana they produce for creature spells by card hash
+lazy_static! {
+  static ref CONDITIONAL_LANDS: HashMap<u64, ConditionalMana> = {
+    let rainbow = ManaCost::from_rgbuwc(1, 1, 1, 1, 1, 0);
+    let colorless = ManaCost::from_rgbuwc(0, 0, 0, 0, 0, 1);
+    [
+      ("Unclaimed Territory", Some(colorless)),
+      ("Cavern of Souls", Some(colorless)),
+      ("Secluded Courtyard", Some(colorless)),
+      ("Ancient Ziggurat", None),
+    ]
+    .iter()
+    .map(|(name, other)| {
+      let mut s = DefaultHasher::new();
+      name.hash(&mut s);
+      let mana = ConditionalMana {
+        creature: rainbow,
+        other: *other,
+      };
+      (s.finish(), mana)
+    })
+    .collect()
+  };
+}
+
+/// Returns the mana `land` can produce to cast `goal`, or `None` if it can't pay for it.
+/// Only `CardKind::Creature` spells get the creature mana of conditional lands, cards of
+/// unknown kind are refused it
+#[inline]
+fn land_mana_for<'a>(land: &'a SimCard, goal: &SimCard) -> Option<&'a ManaCost> {
+  if land.kind != CardKind::OtherLand {
+    return Some(&land.mana_cost);
+  }
+  match CONDITIONAL_LANDS.get(&land.hash) {
+    Some(conditional) if goal.kind == CardKind::Creature => Some(&conditional.creature),
+    Some(conditional) => conditional.other.as_ref(),
+    None => Some(&land.mana_cost),
+  }
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub enum PlayOrder {
   First,
   Second,
 }
 
+/// ExtraDraw represents `count` additional cards drawn on `turn`,
+/// e.g. approximating a turn 2 Expressive Iteration as 1 extra card on turn 2
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExtraDraw {
+  pub turn: usize,
+  pub count: usize,
+  /// True if the `count` additional cards are drawn on `turn` and every turn after it,
+  /// e.g. an emblem or a permanent like Howling Mine
+  #[serde(default)]
+  pub every_turn: bool,
+}
+
+/// DrawEngine represents a nonland card that draws `count` additional cards every turn
+/// once it is cast, like Howling Mine or Phyrexian Arena
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct DrawEngine {
+  /// The hash of the card that draws
+  pub hash: u64,
+  /// The earliest turn on which the card is cast
+  pub turn: usize,
+  pub count: usize,
+  /// The number of turns after the card is cast before the first additional draw,
+  /// e.g. 1 for Phyrexian Arena, which draws on the next upkeep
+  pub delay: usize,
+}
+
+/// Cantrip represents a nonland card that looks at the top `look` cards of the library,
+/// keeping the cards wanted by the `CantripPolicy` on top and putting the others on the
+/// bottom, and draws `draws` cards, like Opt or Serum Visions
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Cantrip {
+  /// The hash of the card
+  pub hash: u64,
+  /// The earliest turn on which the card is cast
+  pub turn: usize,
+  pub look: usize,
+  pub draws: usize,
+  /// True if the card looks at the library before drawing, like Opt, and false if it
+  /// draws first, like Serum Visions
+  pub look_first: bool,
+}
+
+/// CantripPolicy selects the cards that cantrips keep on top of the library
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CantripPolicy {
+  /// Cantrips are not cast
+  #[default]
+  Off,
+  /// Keep lands and bottom spells
+  Lands,
+  /// Keep spells and bottom lands
+  Spells,
+  /// Keep lands until there are enough for the turn after the cantrip is cast, then
+  /// keep spells
+  Curve,
+}
+
+lazy_static! {
+  /// Well known cantrips, as (look, draws, look_first), keyed by card hash.
+  /// Effects that look at more cards than they keep, like Ponder, are approximated as scry
+  static ref KNOWN_CANTRIPS: HashMap<u64, (usize, usize, bool)> = [
+    ("Opt", (1, 1, true)),
+    ("Consider", (1, 1, true)),
+    ("Preordain", (2, 1, true)),
+    ("Ponder", (3, 1, true)),
+    ("Sleight of Hand", (2, 1, true)),
+    ("Serum Visions", (2, 1, false)),
+  ]
+  .iter()
+  .map(|(name, cantrip)| {
+    let mut s = DefaultHasher::new();
+    name.hash(&mut s);
+    (s.finish(), *cantrip)
+  })
+  .collect();
+}
+
+impl ExtraDraw {
+  /// Returns the number of additional cards drawn on or before `turn`
+  pub fn count_by_turn(self, turn: usize) -> usize {
+    if turn < self.turn {
+      0
+    } else if self.every_turn {
+      self.count * (turn - self.turn + 1)
+    } else {
+      self.count
+    }
+  }
+}
+
+impl DrawEngine {
+  /// Returns a new draw engine for `card` that draws `count` additional cards every turn,
+  /// starting `delay` turns after it is cast
+  pub fn new(card: &Card, count: usize, delay: usize) -> Self {
+    Self {
+      hash: card.hash,
+      turn: card.turn as usize,
+      count,
+      delay,
+    }
+  }
+}
+
+impl Cantrip {
+  /// Returns a new cantrip for `card` that looks at `look` cards and draws `draws` cards
+  pub fn new(card: &Card, look: usize, draws: usize, look_first: bool) -> Self {
+    Self {
+      hash: card.hash,
+      turn: std::cmp::max(1, card.turn as usize),
+      look,
+      draws,
+      look_first,
+    }
+  }
+
+  /// Returns the cantrip of `card` if it is a well known cantrip, like Opt or Preordain
+  pub fn known(card: &Card) -> Option<Self> {
+    KNOWN_CANTRIPS
+      .get(&card.hash)
+      .map(|(look, draws, look_first)| Self::new(card, *look, *draws, *look_first))
+  }
+}
+
+impl PlayOrder {
+  /// Returns the number of cards drawn after the opening hand by `turn`,
+  /// including the `extra_draws` made on or before `turn`
+  pub fn draw_count(self, turn: usize, extra_draws: &[ExtraDraw]) -> usize {
+    let draw_step_count = match self {
+      PlayOrder::First => turn - 1,
+      PlayOrder::Second => turn,
+    };
+    draw_step_count
+      + extra_draws
+        .iter()
+        .map(|extra| extra.count_by_turn(turn))
+        .sum::<usize>()
+  }
+}
+
 /// AutoTapResult represents the result of the process that attempts
 /// to tap land cards for mana in order to pay some mana cost
 #[derive(Debug, Copy, Clone, Default)]
@@ -49,6 +258,17 @@ impl AutoTapResult {
   }
 }
 
+impl ManaSource {
+  /// Returns a new mana source for `card` that produces `mana_cost` `delay` turns after being drawn
+  pub fn new(card: &Card, mana_cost: ManaCost, delay: usize) -> Self {
+    Self {
+      hash: card.hash,
+      mana_cost,
+      delay,
+    }
+  }
+}
+
 impl SimCard {
   pub fn new() -> Self {
     Self {
@@ -62,7 +282,7 @@ impl SimCard {
 // Scratch space for the bipartite matching algorithm
 // Used to reduce allocations at runtime
 pub struct Scratch<'a> {
-  lands: Vec<&'a SimCard>,
+  lands: Vec<&'a ManaCost>,
   edges: Vec<u8>,
   seen: Vec<bool>,
   matches: Vec<i32>,
@@ -110,6 +330,7 @@ impl Hand {
       starting_hand_size,
       opening_hand_size,
       mulligan_count: starting_hand_size - opening_hand_size,
+      mulligans: Vec::new(),
     }
   }
   /// Returns a new random hand from `deck` using a mulligan strategy
@@ -202,6 +423,183 @@ impl Hand {
       .fold(0, |count, card| if p(card) { count + 1 } else { count })
   }
 
+  /// Returns `extra_draws` along with the additional draws of the draw `engines` found in
+  /// this hand by `max_turn`. Each engine is cast on the first turn, on or after its
+  /// `DrawEngine::turn`, by which it has been drawn and the lands in hand can pay for it
+  pub fn extra_draws_with_engines(
+    &self,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+    engines: &[DrawEngine],
+    max_turn: usize,
+  ) -> Vec<ExtraDraw> {
+    let mut all_extra_draws = extra_draws.to_vec();
+    let mut cast = vec![false; engines.len()];
+    for turn in 1..=max_turn {
+      let draw_count = play_order.draw_count(turn, &all_extra_draws);
+      for (engine, cast) in engines.iter().zip(cast.iter_mut()) {
+        if *cast || turn < engine.turn {
+          continue;
+        }
+        if self
+          .opening_with_draws(draw_count)
+          .iter()
+          .any(|card| card.hash == engine.hash)
+          && self.can_pay(engine.hash, turn, play_order, &all_extra_draws)
+        {
+          *cast = true;
+          all_extra_draws.push(ExtraDraw {
+            turn: turn + engine.delay,
+            count: engine.count,
+            every_turn: true,
+          });
+        }
+      }
+    }
+    all_extra_draws
+  }
+
+  /// Returns `extra_draws` along with the draws of the `cantrips` cast in this hand by
+  /// `max_turn`. Every copy of a cantrip is cast on the first turn, on or after its
+  /// `Cantrip::turn`, by which it has been drawn and the lands in hand can pay for it,
+  /// see `play_cantrips`
+  pub fn extra_draws_with_cantrips(
+    &self,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+    cantrips: &[Cantrip],
+    max_turn: usize,
+  ) -> Vec<ExtraDraw> {
+    let mut all_extra_draws = extra_draws.to_vec();
+    let mut cast = vec![0; cantrips.len()];
+    for turn in 1..=max_turn {
+      loop {
+        let seen = self.opening_hand_size + play_order.draw_count(turn, &all_extra_draws);
+        match self.next_cantrip(cantrips, &cast, turn, seen, play_order, &all_extra_draws) {
+          Some(i) => {
+            cast[i] += 1;
+            all_extra_draws.push(ExtraDraw {
+              turn,
+              count: cantrips[i].draws,
+              every_turn: false,
+            });
+          }
+          None => break,
+        }
+      }
+    }
+    all_extra_draws
+  }
+
+  /// Casts the `cantrips` of this hand by `max_turn`, like `extra_draws_with_cantrips`,
+  /// and reorders the draws under `policy`: the cards a cantrip looks at that the policy
+  /// wants stay on top and the others are removed, as the simulation never draws the
+  /// bottom of the library
+  pub fn play_cantrips(
+    &mut self,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+    cantrips: &[Cantrip],
+    policy: CantripPolicy,
+    max_turn: usize,
+  ) {
+    if policy == CantripPolicy::Off {
+      return;
+    }
+    let mut all_extra_draws = extra_draws.to_vec();
+    let mut cast = vec![0; cantrips.len()];
+    for turn in 1..=max_turn {
+      loop {
+        let seen = self.opening_hand_size + play_order.draw_count(turn, &all_extra_draws);
+        let cantrip =
+          match self.next_cantrip(cantrips, &cast, turn, seen, play_order, &all_extra_draws) {
+            Some(i) => {
+              cast[i] += 1;
+              cantrips[i]
+            }
+            None => break,
+          };
+        if cantrip.look_first {
+          self.keep_on_top(seen, cantrip.look, policy, turn);
+        } else {
+          self.keep_on_top(seen + cantrip.draws, cantrip.look, policy, turn);
+        }
+        all_extra_draws.push(ExtraDraw {
+          turn,
+          count: cantrip.draws,
+          every_turn: false,
+        });
+      }
+    }
+  }
+
+  /// Returns the index of the first of `cantrips` castable on `turn` with more copies among
+  /// the first `seen` cards than already `cast`
+  fn next_cantrip(
+    &self,
+    cantrips: &[Cantrip],
+    cast: &[usize],
+    turn: usize,
+    seen: usize,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+  ) -> Option<usize> {
+    let cards = self.slice(0, seen);
+    cantrips.iter().zip(cast).position(|(cantrip, cast)| {
+      turn >= cantrip.turn
+        && cards.iter().filter(|c| c.hash == cantrip.hash).count() > *cast
+        && self.can_pay(cantrip.hash, turn, play_order, extra_draws)
+    })
+  }
+
+  /// Returns true if the lands in hand by `turn` can pay for the card with `hash` on their
+  /// own. Each spell is checked on its own, the mana spent on other spells that turn is not
+  /// accounted for
+  fn can_pay(
+    &self,
+    hash: u64,
+    turn: usize,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+  ) -> bool {
+    let goal = match self.cards.iter().find(|card| card.hash == hash) {
+      Some(card) => card.clone(),
+      None => return false,
+    };
+    let mut scratch = Scratch::new(30, 8);
+    let result =
+      self.auto_tap_with_sources(&goal, turn, play_order, extra_draws, &[], &mut scratch);
+    result.cmc && result.paid
+  }
+
+  /// Keeps the cards wanted by `policy` among the `look` cards from index `top` on top of
+  /// the library, in order, and removes the others
+  fn keep_on_top(&mut self, top: usize, look: usize, policy: CantripPolicy, turn: usize) {
+    let top = std::cmp::min(top, self.cards.len());
+    let end = std::cmp::min(top + look, self.cards.len());
+    let mut land_count = self
+      .slice(0, top)
+      .iter()
+      .filter(|c| c.kind.is_land())
+      .count();
+    let looked: Vec<SimCard> = self.cards.drain(top..end).collect();
+    let mut kept = Vec::with_capacity(looked.len());
+    for card in looked {
+      let is_land = card.kind.is_land();
+      let keep = match policy {
+        CantripPolicy::Off => true,
+        CantripPolicy::Lands => is_land,
+        CantripPolicy::Spells => !is_land,
+        CantripPolicy::Curve => is_land == (land_count <= turn),
+      };
+      if keep {
+        land_count += is_land as usize;
+        kept.push(card);
+      }
+    }
+    self.cards.splice(top..top, kept);
+  }
+
   #[inline]
   fn slice(&self, from: usize, to: usize) -> &[SimCard] {
     let to = std::cmp::min(to, self.cards.len());
@@ -226,10 +624,22 @@ impl Hand {
     play_order: PlayOrder,
     scratch: &mut Scratch<'a>,
   ) -> AutoTapResult {
-    let draw_count = match play_order {
-      PlayOrder::First => turland_count - 1,
-      PlayOrder::Second => turland_count,
-    };
+    self.auto_tap_with_sources(goal, turland_count, play_order, &[], &[], scratch)
+  }
+
+  /// Same as `auto_tap_with_scratch`, but nonland `sources` found in hand
+  /// can also tap for the goal once their delay has passed, and `extra_draws`
+  /// add to the cards drawn by each turn
+  pub fn auto_tap_with_sources<'a>(
+    &'a self,
+    goal: &SimCard,
+    turland_count: usize,
+    play_order: PlayOrder,
+    extra_draws: &[ExtraDraw],
+    sources: &'a [ManaSource],
+    scratch: &mut Scratch<'a>,
+  ) -> AutoTapResult {
+    let draw_count = play_order.draw_count(turland_count, extra_draws);
     let opening_hand = self.opening();
     let draws = self.draws(draw_count);
 
@@ -242,7 +652,9 @@ impl Hand {
       let mut found = false;
       for card in opening_hand {
         if card.kind.is_land() {
-          scratch.lands.push(card);
+          if let Some(mana_cost) = land_mana_for(card, goal) {
+            scratch.lands.push(mana_cost);
+          }
         }
         if card.hash == goal.hash {
           found = true;
@@ -257,7 +669,9 @@ impl Hand {
       let mut found = false;
       for card in draws {
         if card.kind.is_land() {
-          scratch.lands.push(card);
+          if let Some(mana_cost) = land_mana_for(card, goal) {
+            scratch.lands.push(mana_cost);
+          }
         }
         if card.hash == goal.hash {
           found = true;
@@ -266,6 +680,21 @@ impl Hand {
       found
     };
 
+    // Add the nonland mana sources that were drawn early enough to produce mana this turn.
+    // The goal card is never considered a source for itself
+    for source in sources {
+      if source.hash == goal.hash || source.delay >= turland_count {
+        continue;
+      }
+      let source_turn = turland_count - source.delay;
+      let source_draw_count = play_order.draw_count(source_turn, extra_draws);
+      for card in self.opening_with_draws(source_draw_count) {
+        if card.hash == source.hash {
+          scratch.lands.push(&source.mana_cost);
+        }
+      }
+    }
+
     let pip_count = goal.mana_cost.cmc() as usize; // rows (height)
     let land_count = scratch.lands.len(); // columns (width)
 
@@ -300,27 +729,27 @@ impl Hand {
     let c_range = w_range.end..(w_range.end + c_pips);
     for m in r_range {
       for (n, land) in scratch.lands.iter().enumerate() {
-        scratch.edges[land_count * m + n] = land.mana_cost.r;
+        scratch.edges[land_count * m + n] = land.r;
       }
     }
     for m in g_range {
       for (n, land) in scratch.lands.iter().enumerate() {
-        scratch.edges[land_count * m + n] = land.mana_cost.g;
+        scratch.edges[land_count * m + n] = land.g;
       }
     }
     for m in b_range {
       for (n, land) in scratch.lands.iter().enumerate() {
-        scratch.edges[land_count * m + n] = land.mana_cost.b;
+        scratch.edges[land_count * m + n] = land.b;
       }
     }
     for m in u_range {
       for (n, land) in scratch.lands.iter().enumerate() {
-        scratch.edges[land_count * m + n] = land.mana_cost.u;
+        scratch.edges[land_count * m + n] = land.u;
       }
     }
     for m in w_range {
       for (n, land) in scratch.lands.iter().enumerate() {
-        scratch.edges[land_count * m + n] = land.mana_cost.w;
+        scratch.edges[land_count * m + n] = land.w;
       }
     }
     for m in c_range {
@@ -1142,4 +1571,153 @@ mod tests {
     assert_eq!(result.paid, true);
     assert_eq!(result.cmc, true);
   }
+
+  #[test]
+  fn draw_count_with_extra_draws() {
+    let extra_draws = [ExtraDraw {
+      turn: 2,
+      count: 1,
+      every_turn: false,
+    }];
+    assert_eq!(PlayOrder::First.draw_count(1, &extra_draws), 0);
+    assert_eq!(PlayOrder::First.draw_count(2, &extra_draws), 2);
+    assert_eq!(PlayOrder::Second.draw_count(1, &extra_draws), 1);
+    assert_eq!(PlayOrder::Second.draw_count(3, &extra_draws), 4);
+    assert_eq!(PlayOrder::Second.draw_count(3, &[]), 3);
+  }
+
+  #[test]
+  fn draw_count_with_every_turn_extra_draws() {
+    let extra_draws = [ExtraDraw {
+      turn: 2,
+      count: 1,
+      every_turn: true,
+    }];
+    assert_eq!(PlayOrder::First.draw_count(1, &extra_draws), 0);
+    assert_eq!(PlayOrder::First.draw_count(2, &extra_draws), 2);
+    assert_eq!(PlayOrder::First.draw_count(4, &extra_draws), 6);
+  }
+
+  #[test]
+  fn draw_engine_extra_draws() {
+    let arena = card!("Phyrexian Arena");
+    let engine = DrawEngine::new(arena, 1, 1);
+    assert_eq!(engine.turn, 3);
+    let swamp = card!("Swamp");
+    let h = vec![swamp, swamp, swamp, arena];
+    let hand = Hand::from_opening_and_draws(&h, &[card!("Opt"); 10]);
+    let extra_draws = hand.extra_draws_with_engines(PlayOrder::First, &[], &[engine], 6);
+    assert_eq!(
+      extra_draws,
+      vec![ExtraDraw {
+        turn: 4,
+        count: 1,
+        every_turn: true,
+      }]
+    );
+    assert_eq!(PlayOrder::First.draw_count(6, &extra_draws), 8);
+    // Drawn on turn 4 on the play, so cast on turn 4 and drawing from turn 5
+    let mut draws = vec![card!("Opt"); 10];
+    draws[2] = arena;
+    let hand = Hand::from_opening_and_draws(&[swamp, swamp, swamp], &draws);
+    let extra_draws = hand.extra_draws_with_engines(PlayOrder::First, &[], &[engine], 6);
+    assert_eq!(extra_draws[0].turn, 5);
+    let hand = Hand::from_opening_and_draws(&[swamp], &[card!("Opt"); 10]);
+    assert!(hand
+      .extra_draws_with_engines(PlayOrder::First, &[], &[engine], 6)
+      .is_empty());
+    // One land can't pay for Phyrexian Arena
+    let hand = Hand::from_opening_and_draws(&[swamp, arena], &[card!("Opt"); 10]);
+    assert!(hand
+      .extra_draws_with_engines(PlayOrder::First, &[], &[engine], 6)
+      .is_empty());
+  }
+
+  #[test]
+  fn cantrips_dig_under_policy() {
+    let opt = Cantrip::known(card!("Opt")).unwrap();
+    assert_eq!((opt.turn, opt.look, opt.draws), (1, 1, 1));
+    let visions = Cantrip::known(card!("Serum Visions")).unwrap();
+    assert!(!visions.look_first);
+    assert!(Cantrip::known(card!("Island")).is_none());
+    let island = card!("Island");
+    let spell = card!("Sinister Sabotage");
+    let opening = [card!("Opt"), island, island, spell, spell, spell, spell];
+    let draws = [spell, island, spell, spell, island];
+    let mut hand = Hand::from_opening_and_draws(&opening, &draws);
+    let extra_draws = hand.extra_draws_with_cantrips(PlayOrder::First, &[], &[opt], 2);
+    assert_eq!(
+      extra_draws,
+      vec![ExtraDraw {
+        turn: 1,
+        count: 1,
+        every_turn: false,
+      }]
+    );
+    // Opt bottoms the spell on top and draws the island under it on turn 1
+    hand.play_cantrips(PlayOrder::First, &[], &[opt], CantripPolicy::Lands, 2);
+    assert_eq!(hand.draws(1)[0].hash, island.hash);
+    assert_eq!(hand.len(), 11);
+    let mut hand = Hand::from_opening_and_draws(&opening, &draws);
+    hand.play_cantrips(PlayOrder::First, &[], &[opt], CantripPolicy::Spells, 2);
+    assert_eq!(hand.draws(1)[0].hash, spell.hash);
+    assert_eq!(hand.len(), 12);
+    // Two lands are enough for turn 1, so the curve policy keeps the spell
+    let mut hand = Hand::from_opening_and_draws(&opening, &draws);
+    hand.play_cantrips(PlayOrder::First, &[], &[opt], CantripPolicy::Curve, 2);
+    assert_eq!(hand.draws(1)[0].hash, spell.hash);
+    let mut hand = Hand::from_opening_and_draws(&opening, &draws);
+    hand.play_cantrips(PlayOrder::First, &[], &[opt], CantripPolicy::Off, 2);
+    assert_eq!(hand.len(), 12);
+    // Without a land to pay for it, Opt is not cast
+    let opening = [card!("Opt"), spell, spell, spell, spell, spell, spell];
+    let hand = Hand::from_opening_and_draws(&opening, &[spell, spell]);
+    assert!(hand
+      .extra_draws_with_cantrips(PlayOrder::First, &[], &[opt], 2)
+      .is_empty());
+  }
+
+  #[test]
+  fn extra_draw_finds_land() {
+    let card = card!("Opt");
+    let h = vec![card!("Opt")];
+    let draws = vec![card!("Opt"), card!("Island")];
+    let hand = Hand::from_opening_and_draws(&h, &draws);
+    let goal = SimCard {
+      hash: card.hash,
+      kind: card.kind,
+      mana_cost: card.mana_cost,
+    };
+    let mut scratch = Scratch::new(30, 8);
+    let result = hand.auto_tap_with_sources(&goal, 1, PlayOrder::Second, &[], &[], &mut scratch);
+    assert!(!result.paid);
+    let extra_draws = [ExtraDraw {
+      turn: 1,
+      count: 1,
+      every_turn: false,
+    }];
+    let result =
+      hand.auto_tap_with_sources(&goal, 1, PlayOrder::Second, &extra_draws, &[], &mut scratch);
+    assert!(result.paid);
+  }
+
+  #[test]
+  fn conditional_lands_only_pay_colors_for_creatures() {
+    let mut elves = card!("Llanowar Elves").clone();
+    elves.kind = CardKind::Creature;
+    let shock = card!("Shock");
+    let territory = Hand::from_opening_and_draws(&[card!("Unclaimed Territory")], &[]);
+    assert!(territory.play_cmc_auto_tap(&elves).paid);
+    assert!(!territory.play_cmc_auto_tap(shock).paid);
+    // Cards of unknown kind are not known to be creatures
+    assert_eq!(card!("Lightning Bolt").kind, CardKind::Unknown);
+    assert!(!territory.play_cmc_auto_tap(card!("Lightning Bolt")).paid);
+    // Ancient Ziggurat can't pay for noncreature spells at all, even generic mana
+    let ziggurat =
+      Hand::from_opening_and_draws(&[card!("Ancient Ziggurat"), card!("Mountain")], &[]);
+    let mut bear = card!("Grizzly Bears").clone();
+    bear.kind = CardKind::Creature;
+    assert!(ziggurat.play_cmc_auto_tap(&bear).paid);
+    assert!(!ziggurat.play_cmc_auto_tap(card!("Lightning Strike")).paid);
+  }
 }
@@ -0,0 +1,170 @@
+//! # Standalone HTML reports
+//!
+//! Renders a deck analysis into a single self-contained HTML file, with the charts
+//! drawn as inline SVG and no external stylesheets or scripts, so it can be archived
+//! or shared without running the web app.
+use crate::castability::CardCastability;
+use crate::deck::Deck;
+use std::fmt::Write;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #ccc;padding:0.3em 0.6em;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+.bar{fill:#4a7ebb}.label{font-size:12px;text-anchor:middle}";
+
+const BAR_WIDTH: usize = 40;
+const CHART_HEIGHT: usize = 120;
+
+/// Returns a standalone HTML report of `deck`, with its mana curve, colored mana
+/// symbols by turn and the `castability` of each card, see `castability_report`
+pub fn html_report(deck: &Deck, castability: &[CardCastability]) -> String {
+  let title = deck.title.as_deref().unwrap_or("Deck analysis");
+  let mut html = String::new();
+  let _ = write!(
+    html,
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+    escape(title),
+    STYLE,
+    escape(title)
+  );
+  let _ = writeln!(
+    html,
+    "<p>{} cards, {} lands</p>",
+    deck.card_count,
+    deck
+      .cards
+      .iter()
+      .filter(|cc| cc.card.is_land())
+      .map(|cc| cc.count)
+      .sum::<usize>()
+  );
+  html.push_str("<h2>Mana curve</h2>\n");
+  html.push_str(&curve_chart(&deck.curve()));
+  html.push_str("<h2>Colored mana symbols by turn</h2>\n");
+  html.push_str(
+    "<table>\n<tr><th>Turn</th><th>W</th><th>U</th><th>B</th><th>R</th><th>G</th></tr>\n",
+  );
+  for (turn, pips) in deck.pip_counts().iter().enumerate() {
+    if pips.total() == 0 {
+      continue;
+    }
+    let _ = writeln!(
+      html,
+      "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+      turn, pips.w, pips.u, pips.b, pips.r, pips.g
+    );
+  }
+  html.push_str("</table>\n");
+  if !castability.is_empty() {
+    html.push_str("<h2>Castability</h2>\n");
+    html.push_str("<table>\n<tr><th>Card</th><th>Turn</th><th>P(mana)</th><th>P(play)</th><th>P(mana) next turn</th><th>Blocking color</th></tr>\n");
+    for card in castability {
+      let blocking_color = card
+        .blocking_color
+        .map_or_else(String::new, |color| format!("{:?}", color));
+      let _ = writeln!(
+        html,
+        "<tr><td>{}</td><td>{}</td><td>{:.1}%</td><td>{:.1}%</td><td>{:.1}%</td><td>{}</td></tr>",
+        escape(&card.name),
+        card.turn,
+        100.0 * card.on_curve.p_mana(),
+        100.0 * card.on_curve.p_play(),
+        100.0 * card.on_curve_plus_one.p_mana(),
+        blocking_color
+      );
+    }
+    html.push_str("</table>\n");
+  }
+  html.push_str("</body>\n</html>\n");
+  html
+}
+
+/// Returns an inline SVG bar chart of the mana value histogram `curve`
+fn curve_chart(curve: &[usize]) -> String {
+  let max = curve.iter().copied().max().unwrap_or(0).max(1);
+  let width = BAR_WIDTH * curve.len().max(1);
+  let mut svg = String::new();
+  let _ = writeln!(
+    svg,
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+    width,
+    CHART_HEIGHT + 40
+  );
+  for (cmc, count) in curve.iter().enumerate() {
+    let height = CHART_HEIGHT * count / max;
+    let x = cmc * BAR_WIDTH;
+    let _ = writeln!(
+      svg,
+      "<rect class=\"bar\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+      x + 4,
+      20 + CHART_HEIGHT - height,
+      BAR_WIDTH - 8,
+      height
+    );
+    let _ = writeln!(
+      svg,
+      "<text class=\"label\" x=\"{}\" y=\"{}\">{}</text>",
+      x + BAR_WIDTH / 2,
+      15 + CHART_HEIGHT - height,
+      count
+    );
+    let _ = writeln!(
+      svg,
+      "<text class=\"label\" x=\"{}\" y=\"{}\">{}</text>",
+      x + BAR_WIDTH / 2,
+      CHART_HEIGHT + 35,
+      cmc
+    );
+  }
+  svg.push_str("</svg>\n");
+  svg
+}
+
+/// Returns `text` with the HTML special characters escaped
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::castability::castability_report;
+  use crate::hand::CantripPolicy;
+  use crate::html::*;
+  use crate::mulligan::Never;
+  use crate::simulation::*;
+
+  #[test]
+  fn html_report_is_standalone() {
+    let mut deck = decklist!(
+      "
+      4 Opt
+      3 Vraska's Contempt
+      17 Island
+      "
+    );
+    deck.title = Some("Dimir <Test>".to_string());
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(1),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let html = html_report(&deck, &castability_report(&sim, &deck));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Dimir &lt;Test&gt;</title>"));
+    assert!(html.contains("<svg"));
+    assert!(html.contains("<td>Vraska&#39;s Contempt</td>"));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("<link"));
+  }
+}
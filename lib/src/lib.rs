@@ -14,22 +14,56 @@ extern crate bincode;
 extern crate flate2;
 extern crate log;
 extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 extern crate regex;
+#[cfg(feature = "schemars")]
+#[macro_use]
+extern crate schemars;
 extern crate wasm_bindgen;
 
 #[macro_use]
 pub mod card;
 #[macro_use]
 pub mod deck;
+pub mod advisor;
+pub mod archetype;
 mod bipartite;
+pub mod castability;
 pub mod collection;
 pub mod data;
+pub mod diagnostics;
+pub mod draft;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod functions;
 pub mod hand;
+pub mod html;
+#[cfg(feature = "localization")]
+pub mod localization;
+pub mod manabase;
+pub mod manifest;
 pub mod mulligan;
 pub mod prelude;
+#[cfg(feature = "prices")]
+pub mod prices;
+pub mod probability;
+pub mod result;
+mod run;
+#[cfg(feature = "schemars")]
+pub mod schema;
 pub mod scryfall;
+pub mod session;
+pub mod sideboard;
 pub mod simulation;
+pub mod store;
+pub mod turn_one;
+pub mod verification;
+mod version;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // mtgoncurve.com
 mod mtgoncurve;
-pub use crate::mtgoncurve::mtgoncurve_run;
+pub use crate::mtgoncurve::{mtgoncurve_run, mtgoncurve_version_info};
+pub use crate::version::{version_info, VersionInfo};
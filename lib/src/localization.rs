@@ -0,0 +1,146 @@
+//! # Localized card names
+//!
+//! Resolves the printed names of non-English cards, e.g. "Blitzschlag", to their English
+//! names, so that deck lists exported from an Arena client in another language parse.
+//! The names come from the Scryfall "All Cards" bulk data file, the only one with every
+//! language, which is too large to embed, so they are loaded at runtime and installed for
+//! `Collection::card_from_name`. Requires the `localization` feature.
+use crate::card::Card;
+use crate::collection::Collection;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+lazy_static! {
+  /// The localized names used by `Collection::card_from_name`, see `LocalizedNames::install`
+  static ref INSTALLED: RwLock<Option<LocalizedNames>> = RwLock::new(None);
+}
+
+/// LocalizedNames maps the printed names of non-English cards to their English names
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LocalizedNames {
+  /// English names keyed by lowercase printed name
+  pub names: HashMap<String, String>,
+}
+
+/// The subset of a Scryfall card object with its printed name
+#[derive(Debug, Deserialize)]
+struct ScryfallPrintedName {
+  name: String,
+  #[serde(default)]
+  lang: String,
+  #[serde(default)]
+  printed_name: Option<String>,
+  #[serde(default)]
+  card_faces: Vec<ScryfallPrintedName>,
+}
+
+impl LocalizedNames {
+  /// Returns the localized names of the Scryfall bulk data file contents `json`, an array
+  /// of card objects. English cards and cards without a printed name are skipped, and the
+  /// faces of multiface cards map to the English name of each face
+  pub fn from_scryfall_json(json: &str) -> Result<Self, serde_json::Error> {
+    let cards: Vec<ScryfallPrintedName> = serde_json::from_str(json)?;
+    let mut names = HashMap::new();
+    for card in cards.iter().filter(|card| card.lang != "en") {
+      for named in std::iter::once(card).chain(card.card_faces.iter()) {
+        if let Some(printed_name) = &named.printed_name {
+          names.insert(printed_name.to_lowercase(), named.name.clone());
+        }
+      }
+    }
+    Ok(Self { names })
+  }
+
+  /// Returns the localized names of `json`, an object mapping printed names to English
+  /// names, e.g. `{"Blitzschlag": "Lightning Bolt"}`
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    let names: HashMap<String, String> = serde_json::from_str(json)?;
+    Ok(Self {
+      names: names
+        .into_iter()
+        .map(|(printed_name, name)| (printed_name.to_lowercase(), name))
+        .collect(),
+    })
+  }
+
+  /// Returns the English name of the card printed as `name`, ignoring case
+  pub fn english_name(&self, name: &str) -> Option<&str> {
+    self.names.get(&name.to_lowercase()).map(String::as_str)
+  }
+
+  /// Returns the card of `collection` printed as `name`
+  pub fn card_from_name<'a>(&self, collection: &'a Collection, name: &str) -> Option<&'a Card> {
+    collection.card_from_name(self.english_name(name)?)
+  }
+
+  /// Installs the names for `Collection::card_from_name`, and so for deck list parsing,
+  /// replacing any names installed before
+  pub fn install(self) {
+    *INSTALLED.write().expect("Localized names lock poisoned") = Some(self);
+  }
+}
+
+/// Returns the English name of the card printed as `name` in the installed localized names
+pub(crate) fn installed_english_name(name: &str) -> Option<String> {
+  INSTALLED
+    .read()
+    .expect("Localized names lock poisoned")
+    .as_ref()?
+    .english_name(name)
+    .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::data::ALL_CARDS;
+  use crate::deck::Deck;
+  use crate::localization::*;
+
+  #[test]
+  fn localized_names_from_scryfall() {
+    let names = LocalizedNames::from_scryfall_json(
+      r#"[
+        {"name": "Lightning Bolt", "lang": "de", "printed_name": "Blitzschlag"},
+        {"name": "Lightning Bolt", "lang": "en"},
+        {
+          "name": "Fable of the Mirror-Breaker // Reflection of Kiki-Jiki",
+          "lang": "de",
+          "card_faces": [
+            {"name": "Fable of the Mirror-Breaker", "printed_name": "Vorderseite"},
+            {"name": "Reflection of Kiki-Jiki", "printed_name": "Rückseite"}
+          ]
+        }
+      ]"#,
+    )
+    .unwrap();
+    assert_eq!(names.english_name("BLITZSCHLAG"), Some("Lightning Bolt"));
+    assert_eq!(
+      names.english_name("Vorderseite"),
+      Some("Fable of the Mirror-Breaker")
+    );
+    assert_eq!(names.english_name("Lightning Bolt"), None);
+    assert_eq!(
+      names
+        .card_from_name(&ALL_CARDS, "blitzschlag")
+        .unwrap()
+        .name,
+      "Lightning Bolt"
+    );
+  }
+
+  #[test]
+  fn deck_list_with_installed_localized_names() {
+    LocalizedNames::from_json(r#"{"Blitzschlag": "Lightning Bolt"}"#)
+      .unwrap()
+      .install();
+    let deck = Deck::from_list("20 Mountain\n4 Blitzschlag").unwrap();
+    assert_eq!(
+      deck.card_count_from_name("Lightning Bolt").unwrap().count,
+      4
+    );
+    assert_eq!(
+      ALL_CARDS.card_from_name("blitzschlag").unwrap().name,
+      "Lightning Bolt"
+    );
+  }
+}
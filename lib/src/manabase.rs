@@ -0,0 +1,654 @@
+//! # Manabase optimizer
+//!
+//! Searches for the land configuration that maximizes the on curve castability of the
+//! spells in a deck, by hill climbing over single land swaps from a pool of candidate lands,
+//! recommends land counts for Commander decks given their ramp, and checks whether a deck
+//! supports a splash.
+use crate::card::{Card, ManaColor};
+use crate::data::ALL_CARDS;
+use crate::deck::{Deck, PipCount};
+use crate::mulligan::Mulligan;
+use crate::simulation::{Simulation, SimulationConfig};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use std::ops::RangeInclusive;
+
+/// The basic lands of each color, in `PipCount` order
+const BASIC_LAND_NAMES: [&str; 5] = ["Plains", "Island", "Swamp", "Mountain", "Forest"];
+
+/// The colors of `BASIC_LAND_NAMES`
+const BASIC_LAND_COLORS: [ManaColor; 5] = [
+  ManaColor::White,
+  ManaColor::Blue,
+  ManaColor::Black,
+  ManaColor::Red,
+  ManaColor::Green,
+];
+
+/// ManabaseConfig describes the lands the optimizer may choose from
+pub struct ManabaseConfig<'a> {
+  /// The candidate lands, e.g. basics, duals and utility lands
+  pub land_pool: &'a [&'a Card],
+  /// The total number of lands in the deck
+  pub land_count: usize,
+  /// Stop after this many swaps, even if a swap would still improve the score
+  pub max_swaps: usize,
+}
+
+/// ManabaseReport is the result of `optimize_manabase`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManabaseReport {
+  /// The deck with the optimized lands
+  pub deck: Deck,
+  /// The score of the lands the optimizer started from, see `manabase_score`
+  pub initial_score: f64,
+  /// The score of the optimized lands
+  pub score: f64,
+  /// The number of land swaps made
+  pub swaps: usize,
+}
+
+/// CommanderLandOption is the simulated result of a single land count,
+/// see `recommend_commander_lands`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommanderLandOption {
+  pub land_count: usize,
+  /// The number of nonland mana sources, see `Deck::add_mana_source`
+  pub ramp_count: usize,
+  /// The probability to have the mana for every commander on its turn
+  pub p_commander_on_curve: f64,
+  /// See `manabase_score`
+  pub score: f64,
+}
+
+/// CommanderLandReport is the result of `recommend_commander_lands`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommanderLandReport {
+  pub options: Vec<CommanderLandOption>,
+  /// The fewest lands that cast the commanders on curve with the target probability,
+  /// or the land count with the highest probability if none does
+  pub recommended_land_count: usize,
+}
+
+/// SplashSourceOption is the simulated castability of a splashed card with a number of
+/// sources of the splashed color, see `analyze_splash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplashSourceOption {
+  pub sources: usize,
+  /// The probability to have the mana for the card on curve
+  pub p_mana: f64,
+}
+
+/// SplashReport is the result of `analyze_splash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplashReport {
+  pub card: String,
+  /// The color of the card with the fewest sources in the deck
+  pub color: ManaColor,
+  /// The number of lands of the deck that produce `color`
+  pub sources: usize,
+  /// The probability to have the mana for the card on curve with the current lands
+  pub p_mana: f64,
+  /// The castability with each number of sources, fewest sources first
+  pub options: Vec<SplashSourceOption>,
+  /// The fewest sources that cast the card on curve with the target probability, or None
+  /// if no number of sources does
+  pub required_sources: Option<usize>,
+  /// True if the deck has at least `required_sources` sources
+  pub supported: bool,
+}
+
+/// Returns the average probability to have the mana for each nonland card in `deck`
+/// on its turn, weighted by the number of copies of each card
+pub fn manabase_score(sim: &Simulation, deck: &Deck) -> f64 {
+  let (total, count) =
+    deck
+      .cards
+      .iter()
+      .filter(|cc| !cc.card.is_land())
+      .fold((0.0, 0), |(total, count), cc| {
+        let p_mana = sim.observations_for_card(&cc.card).p_mana();
+        (total + p_mana * cc.count as f64, count + cc.count)
+      });
+  if count == 0 {
+    return 0.0;
+  }
+  total / count as f64
+}
+
+/// Returns the lands for the spells in `config.deck` with the highest `manabase_score`
+/// that the optimizer finds
+///
+/// The optimizer starts from the lands in the deck if there are `manabase.land_count` of
+/// them, and from an even split of the land pool otherwise. It then repeatedly makes the
+/// single swap, of one land in the deck for one land in the pool, that raises the score the
+/// most, until no swap improves the score. Every candidate is simulated with the same seed,
+/// so that differences in score come from the lands rather than from the shuffles.
+pub fn optimize_manabase<M: Mulligan + Sync>(
+  config: &SimulationConfig<M>,
+  manabase: &ManabaseConfig,
+) -> ManabaseReport {
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let score = |deck: &Deck| -> f64 {
+    let sim = Simulation::from_config(&SimulationConfig {
+      deck,
+      seed: Some(seed),
+      ..*config
+    });
+    manabase_score(&sim, deck)
+  };
+
+  let mut deck = initial_lands(config.deck, manabase);
+  let initial_score = score(&deck);
+  let mut best_score = initial_score;
+  let mut swaps = 0;
+  while swaps < manabase.max_swaps {
+    let lands: Vec<Card> = deck
+      .cards
+      .iter()
+      .filter(|cc| cc.card.is_land())
+      .map(|cc| cc.card.clone())
+      .collect();
+    let mut best: Option<(Deck, f64)> = None;
+    for remove in &lands {
+      for add in manabase.land_pool {
+        if remove.hash == add.hash {
+          continue;
+        }
+        let mut candidate = deck.clone();
+        candidate.remove_count(remove, 1);
+        candidate.insert_count(add, 1);
+        let candidate_score = score(&candidate);
+        let best_so_far = best.as_ref().map_or(best_score, |(_, s)| *s);
+        if candidate_score > best_so_far {
+          best = Some((candidate, candidate_score));
+        }
+      }
+    }
+    match best {
+      Some((candidate, candidate_score)) => {
+        deck = candidate;
+        best_score = candidate_score;
+        swaps += 1;
+      }
+      None => break,
+    }
+  }
+
+  ManabaseReport {
+    deck,
+    initial_score,
+    score: best_score,
+    swaps,
+  }
+}
+
+/// Returns the land counts in `land_counts` for the singleton deck in `config`, e.g. a
+/// Commander or Brawl deck, along with the fewest lands that cast the commanders on
+/// curve with at least probability `target`
+///
+/// The ramp in the deck, registered with `Deck::add_mana_source`, counts towards
+/// casting the commanders, so decks with more ramp get by with fewer lands. Each
+/// land count is simulated by adding basic lands in place of the most expensive spells,
+/// or by replacing basic lands with copies of the most expensive spell, which stand in
+/// for the spells that would take those slots. Basic lands are added to, or removed
+/// from, the colors with the most, or fewest, mana symbols per basic land.
+pub fn recommend_commander_lands<M: Mulligan + Sync>(
+  config: &SimulationConfig<M>,
+  land_counts: RangeInclusive<usize>,
+  target: f64,
+) -> CommanderLandReport {
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let ramp_count: usize = config
+    .deck
+    .cards
+    .iter()
+    .filter(|cc| is_ramp(config.deck, &cc.card))
+    .map(|cc| cc.count)
+    .sum();
+  let options: Vec<CommanderLandOption> = land_counts
+    .map(|land_count| {
+      let deck = with_land_count(config.deck, land_count);
+      let sim = Simulation::from_config(&SimulationConfig {
+        deck: &deck,
+        seed: Some(seed),
+        ..*config
+      });
+      let p_commander_on_curve = deck
+        .commanders
+        .iter()
+        .map(|commander| sim.observations_for_card(commander).p_mana())
+        .fold(1.0, f64::min);
+      CommanderLandOption {
+        land_count: count_lands(&deck),
+        ramp_count,
+        p_commander_on_curve,
+        score: manabase_score(&sim, &deck),
+      }
+    })
+    .collect();
+  let recommended_land_count = options
+    .iter()
+    .find(|option| option.p_commander_on_curve >= target)
+    .or_else(|| {
+      options.iter().max_by(|a, b| {
+        a.p_commander_on_curve
+          .partial_cmp(&b.p_commander_on_curve)
+          .unwrap()
+      })
+    })
+    .map_or(0, |option| option.land_count);
+  CommanderLandReport {
+    options,
+    recommended_land_count,
+  }
+}
+
+/// Returns whether the lands of `config.deck` support a splash of `card`, i.e. cast it on
+/// curve with probability `target`, in the spirit of Frank Karsten's source tables but
+/// derived from this simulation. The splashed color is the color of the card with the
+/// fewest sources. Each number of sources up to `max_sources` is simulated by swapping
+/// basic lands between the splashed color and the most numerous other basic land, with
+/// the same seed, so the land count stays the same
+pub fn analyze_splash<M: Mulligan + Sync>(
+  config: &SimulationConfig<M>,
+  card: &Card,
+  target: f64,
+  max_sources: usize,
+) -> SplashReport {
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let color = BASIC_LAND_COLORS
+    .iter()
+    .copied()
+    .filter(|color| card.mana_cost.count(*color) > 0)
+    .min_by_key(|color| count_sources(config.deck, *color))
+    .unwrap_or(ManaColor::Colorless);
+  let sources = count_sources(config.deck, color);
+  let p_mana = |deck: &Deck| {
+    let sim = Simulation::from_config(&SimulationConfig {
+      deck,
+      seed: Some(seed),
+      ..*config
+    });
+    sim.observations_for_card(card).p_mana()
+  };
+  let options: Vec<SplashSourceOption> = (0..=max_sources)
+    .filter_map(|n| with_sources(config.deck, color, n))
+    .map(|deck| SplashSourceOption {
+      sources: count_sources(&deck, color),
+      p_mana: p_mana(&deck),
+    })
+    .collect();
+  let required_sources = options
+    .iter()
+    .find(|option| option.p_mana >= target)
+    .map(|option| option.sources);
+  SplashReport {
+    card: card.name.clone(),
+    color,
+    sources,
+    p_mana: p_mana(config.deck),
+    options,
+    required_sources,
+    supported: required_sources.is_some_and(|required| sources >= required),
+  }
+}
+
+/// Returns the number of lands of `deck` that produce `color`
+fn count_sources(deck: &Deck, color: ManaColor) -> usize {
+  deck
+    .cards
+    .iter()
+    .filter(|cc| cc.card.is_land() && cc.card.mana_cost.count(color) > 0)
+    .map(|cc| cc.count)
+    .sum()
+}
+
+/// Returns a copy of `deck` with `sources` lands that produce `color`, by swapping basic
+/// lands of `color` with the most numerous other basic land, or None if the deck doesn't
+/// have enough basic lands to swap, see `analyze_splash`
+fn with_sources(deck: &Deck, color: ManaColor, sources: usize) -> Option<Deck> {
+  let i = BASIC_LAND_COLORS.iter().position(|c| *c == color)?;
+  let splash_basic = ALL_CARDS.card_from_name(BASIC_LAND_NAMES[i])?;
+  let basic_count = |deck: &Deck, card: &Card| {
+    deck
+      .card_count_from_name(&card.name)
+      .map_or(0, |cc| cc.count)
+  };
+  let other_basic = BASIC_LAND_NAMES
+    .iter()
+    .filter(|name| **name != BASIC_LAND_NAMES[i])
+    .filter_map(|name| ALL_CARDS.card_from_name(name))
+    .max_by_key(|basic| basic_count(deck, basic))?;
+  let current = count_sources(deck, color);
+  let mut deck = deck.clone();
+  if sources > current {
+    let swaps = sources - current;
+    if basic_count(&deck, other_basic) < swaps {
+      return None;
+    }
+    deck.remove_count(other_basic, swaps);
+    deck.insert_count(splash_basic, swaps);
+  } else if sources < current {
+    let swaps = current - sources;
+    if basic_count(&deck, splash_basic) < swaps {
+      return None;
+    }
+    deck.remove_count(splash_basic, swaps);
+    deck.insert_count(other_basic, swaps);
+  }
+  Some(deck)
+}
+
+/// Returns a copy of `deck` with `land_count` lands, see `recommend_commander_lands`
+fn with_land_count(deck: &Deck, land_count: usize) -> Deck {
+  let mut deck = deck.clone();
+  let mut pips = PipCount::default();
+  for cc in deck.cards.iter().filter(|cc| !cc.card.is_land()) {
+    pips.add(&cc.card.mana_cost, cc.count);
+  }
+  for commander in &deck.commanders {
+    pips.add(&commander.mana_cost, 1);
+  }
+  let pips = [pips.w, pips.u, pips.b, pips.r, pips.g];
+  let basics: Vec<&Card> = BASIC_LAND_NAMES
+    .iter()
+    .map(|name| ALL_CARDS.card_from_name(name).expect("basic lands exist"))
+    .collect();
+  let basic_counts = |deck: &Deck| -> Vec<usize> {
+    basics
+      .iter()
+      .map(|basic| {
+        deck
+          .cards
+          .iter()
+          .find(|cc| cc.card.hash == basic.hash)
+          .map_or(0, |cc| cc.count)
+      })
+      .collect()
+  };
+  let mut current = count_lands(&deck);
+  while current != land_count {
+    let spell = match most_expensive_spell(&deck) {
+      Some(spell) => spell,
+      None => break,
+    };
+    let counts = basic_counts(&deck);
+    // Compare pips[i] / counts[i] between colors without division
+    let per_basic = |i: usize, extra: usize| (pips[i], counts[i] + extra);
+    if current < land_count {
+      let color = (0..basics.len())
+        .max_by(|a, b| {
+          let (pa, ca) = per_basic(*a, 1);
+          let (pb, cb) = per_basic(*b, 1);
+          (pa * cb).cmp(&(pb * ca))
+        })
+        .expect("there are basic lands");
+      deck.remove_count(&spell, 1);
+      deck.insert_count(basics[color], 1);
+      current += 1;
+    } else {
+      let color = match (0..basics.len()).filter(|i| counts[*i] > 0).min_by(|a, b| {
+        let (pa, ca) = per_basic(*a, 0);
+        let (pb, cb) = per_basic(*b, 0);
+        (pa * cb).cmp(&(pb * ca))
+      }) {
+        Some(color) => color,
+        None => break,
+      };
+      deck.remove_count(basics[color], 1);
+      deck.insert_count(&spell, 1);
+      current -= 1;
+    }
+  }
+  deck
+}
+
+/// Returns the nonland card in `deck` with the highest mana value that is not ramp
+fn most_expensive_spell(deck: &Deck) -> Option<Card> {
+  deck
+    .cards
+    .iter()
+    .filter(|cc| !cc.card.is_land() && !is_ramp(deck, &cc.card))
+    .max_by_key(|cc| cc.card.mana_cost.cmc())
+    .map(|cc| cc.card.clone())
+}
+
+/// Returns true if `card` is a nonland mana source of `deck`
+fn is_ramp(deck: &Deck, card: &Card) -> bool {
+  deck
+    .mana_sources
+    .iter()
+    .any(|source| source.hash == card.hash)
+}
+
+/// Returns the number of lands in `deck`
+fn count_lands(deck: &Deck) -> usize {
+  deck
+    .cards
+    .iter()
+    .filter(|cc| cc.card.is_land())
+    .map(|cc| cc.count)
+    .sum()
+}
+
+/// Returns `deck` with the lands the optimizer starts from
+fn initial_lands(deck: &Deck, manabase: &ManabaseConfig) -> Deck {
+  if count_lands(deck) == manabase.land_count || manabase.land_pool.is_empty() {
+    return deck.clone();
+  }
+  let mut deck = deck.clone();
+  let lands: Vec<Card> = deck
+    .cards
+    .iter()
+    .filter(|cc| cc.card.is_land())
+    .map(|cc| cc.card.clone())
+    .collect();
+  for land in &lands {
+    deck.remove_count(land, usize::MAX);
+  }
+  for i in 0..manabase.land_count {
+    deck.insert_count(manabase.land_pool[i % manabase.land_pool.len()], 1);
+  }
+  deck
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::card::ManaCost;
+  use crate::hand::CantripPolicy;
+  use crate::manabase::*;
+  use crate::mulligan::{London, Never};
+
+  #[test]
+  fn splash_needs_enough_sources() {
+    let deck = decklist!(
+      "
+      23 Swamp
+      1 Mountain
+      32 Thoughtseize
+      4 Lightning Strike
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 3000,
+      draw_count: 3,
+      deck: &deck,
+      mulligan: &Never::never(),
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(3),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let strike = card!("Lightning Strike");
+    let report = analyze_splash(&config, strike, 0.6, 12);
+    assert_eq!(report.color, ManaColor::Red);
+    assert_eq!(report.sources, 1);
+    assert_eq!(report.options.len(), 13);
+    assert!(report.options[12].p_mana > report.options[1].p_mana);
+    assert!((report.options[1].p_mana - report.p_mana).abs() < 1e-9);
+    let required = report.required_sources.unwrap();
+    assert!((5..=9).contains(&required), "{}", required);
+    assert!(!report.supported);
+    let mut splash = deck.clone();
+    splash.remove_count(card!("Swamp"), 9);
+    splash.insert_count(card!("Mountain"), 9);
+    let report = analyze_splash(
+      &SimulationConfig {
+        deck: &splash,
+        ..config
+      },
+      strike,
+      0.6,
+      12,
+    );
+    assert_eq!(report.sources, 10);
+    assert!(report.supported);
+  }
+
+  #[test]
+  fn optimize_mono_blue() {
+    let deck = decklist!(
+      "
+      36 Opt
+      "
+    );
+    let pool = [card!("Island"), card!("Mountain")];
+    let report = optimize_manabase(
+      &SimulationConfig {
+        run_count: 500,
+        draw_count: 2,
+        deck: &deck,
+        mulligan: &Never::never(),
+        on_the_play: true,
+        extra_draws: &[],
+        seed: Some(17),
+        cantrip_policy: CantripPolicy::Off,
+      },
+      &ManabaseConfig {
+        land_pool: &pool,
+        land_count: 24,
+        max_swaps: 8,
+      },
+    );
+    assert_eq!(report.deck.card_count, 60);
+    assert_eq!(report.swaps, 8);
+    assert!(report.score > report.initial_score);
+    // Starts from 12 of each, and every swap trades a Mountain for an Island
+    assert_eq!(
+      report.deck.card_count_from_name("Island").unwrap().count,
+      20
+    );
+    assert_eq!(
+      report.deck.card_count_from_name("Mountain").unwrap().count,
+      4
+    );
+  }
+
+  fn commander_deck() -> Deck {
+    let mut cards: Vec<Card> = crate::data::ALL_CARDS
+      .iter()
+      .filter(|c| !c.is_face && !c.is_land() && c.mana_cost.bits == ManaCost::G_BITS && c.turn >= 2)
+      .take(56)
+      .cloned()
+      .collect();
+    let elves = card!("Llanowar Elves");
+    cards.push(elves.clone());
+    for _ in 0..36 {
+      cards.push(card!("Forest").clone());
+    }
+    let mut deck = Deck::from_cards(cards);
+    deck.commanders.push(card!("Omnath, Locus of Mana").clone());
+    deck.add_mana_source(elves, elves.mana_cost, 1);
+    deck
+  }
+
+  #[test]
+  fn recommend_commander_land_count() {
+    let deck = commander_deck();
+    let config = SimulationConfig {
+      run_count: 1000,
+      draw_count: 4,
+      deck: &deck,
+      mulligan: &London::commander(),
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(23),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let report = recommend_commander_lands(&config, 30..=40, 0.0);
+    assert_eq!(report.options.len(), 11);
+    assert_eq!(report.recommended_land_count, 30);
+    for (option, land_count) in report.options.iter().zip(30..=40) {
+      assert_eq!(option.land_count, land_count);
+      assert_eq!(option.ramp_count, 1);
+    }
+    let fewest = &report.options[0];
+    let most = &report.options[10];
+    assert!(most.p_commander_on_curve > fewest.p_commander_on_curve);
+    // An unreachable target recommends the most likely land count
+    let report = recommend_commander_lands(&config, 30..=40, 1.1);
+    let best = report
+      .options
+      .iter()
+      .map(|option| option.p_commander_on_curve)
+      .fold(0.0, f64::max);
+    let recommended = report
+      .options
+      .iter()
+      .find(|option| option.land_count == report.recommended_land_count)
+      .unwrap();
+    assert_eq!(recommended.p_commander_on_curve, best);
+  }
+
+  #[test]
+  fn with_land_count_keeps_deck_size() {
+    let deck = commander_deck();
+    for land_count in &[30, 36, 42] {
+      let resized = with_land_count(&deck, *land_count);
+      assert_eq!(resized.card_count, deck.card_count);
+      assert_eq!(count_lands(&resized), *land_count);
+      // Ramp is never cut
+      assert!(resized.card_from_name("Llanowar Elves").is_some());
+    }
+  }
+
+  #[test]
+  fn optimize_keeps_best_lands() {
+    let deck = decklist!(
+      "
+      36 Opt
+      24 Island
+      "
+    );
+    let pool = [card!("Island"), card!("Mountain")];
+    let report = optimize_manabase(
+      &SimulationConfig {
+        run_count: 500,
+        draw_count: 2,
+        deck: &deck,
+        mulligan: &Never::never(),
+        on_the_play: true,
+        extra_draws: &[],
+        seed: Some(17),
+        cantrip_policy: CantripPolicy::Off,
+      },
+      &ManabaseConfig {
+        land_pool: &pool,
+        land_count: 24,
+        max_swaps: 8,
+      },
+    );
+    assert_eq!(report.swaps, 0);
+    assert_eq!(
+      report.deck.card_count_from_name("Island").unwrap().count,
+      24
+    );
+  }
+}
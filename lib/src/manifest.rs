@@ -0,0 +1,138 @@
+//! # Batch analysis manifests
+//!
+//! Runs the simulations described by a JSON manifest file end to end, so a batch of deck
+//! lists and configurations can be analyzed, and reproduced, with a single command.
+//! # Example
+//!
+//! ```json
+//! {
+//!   "runs": [
+//!     {
+//!       "name": "Mono red, 22 lands",
+//!       "deck": "decks/mono-red-22.txt",
+//!       "config": { "run_count": 10000, "draw_count": 10, "on_the_play": true, "seed": 1 },
+//!       "output": "results/mono-red-22.json"
+//!     }
+//!   ]
+//! }
+//! ```
+use crate::result::SimulationResult;
+use crate::run::run_simulation;
+use std::fs;
+use std::path::Path;
+
+/// Manifest describes a batch of simulations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+  pub runs: Vec<ManifestRun>,
+}
+
+/// ManifestRun describes a single simulation of a `Manifest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestRun {
+  /// The name of the run in the results
+  pub name: String,
+  /// The path of the deck list file, relative to the manifest
+  pub deck: String,
+  /// The simulation configuration, in the JSON format of the `wasm` and `ffi` bindings
+  pub config: serde_json::Value,
+  /// The path to write the `SimulationResult` JSON to, relative to the manifest, if any
+  #[serde(default)]
+  pub output: Option<String>,
+}
+
+/// ManifestResult is the result of a single `ManifestRun`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestResult {
+  pub name: String,
+  pub result: SimulationResult,
+}
+
+/// Runs each simulation of the manifest file at `path` in order, writes the results of
+/// the runs with an `output`, and returns all of the results, or the first error
+pub fn run_manifest_file(path: &Path) -> Result<Vec<ManifestResult>, String> {
+  let json = fs::read_to_string(path)
+    .map_err(|e| format!("Error reading manifest {}: {}", path.display(), e))?;
+  let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+  run_manifest(&json, base_dir)
+}
+
+/// Same as `run_manifest_file`, but for the manifest contents `json`, with paths relative
+/// to `base_dir`
+pub fn run_manifest(json: &str, base_dir: &Path) -> Result<Vec<ManifestResult>, String> {
+  let manifest: Manifest =
+    serde_json::from_str(json).map_err(|e| format!("Error parsing manifest: {}", e))?;
+  let mut results = Vec::with_capacity(manifest.runs.len());
+  for run in &manifest.runs {
+    let deck_path = base_dir.join(&run.deck);
+    let deck_str = fs::read_to_string(&deck_path)
+      .map_err(|e| format!("{}: error reading {}: {}", run.name, deck_path.display(), e))?;
+    let result = run_simulation(&deck_str, &run.config.to_string())
+      .map_err(|e| format!("{}: {}", run.name, e))?;
+    if let Some(output) = &run.output {
+      let output_path = base_dir.join(output);
+      let json = serde_json::to_string_pretty(&result).expect("this can't fail");
+      fs::write(&output_path, json).map_err(|e| {
+        format!(
+          "{}: error writing {}: {}",
+          run.name,
+          output_path.display(),
+          e
+        )
+      })?;
+    }
+    results.push(ManifestResult {
+      name: run.name.clone(),
+      result,
+    });
+  }
+  Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::manifest::*;
+  use std::env;
+
+  #[test]
+  fn run_manifest_writes_outputs() {
+    let dir = env::temp_dir().join(format!("landlord-manifest-{}", std::process::id()));
+    fs::create_dir_all(dir.join("decks")).unwrap();
+    fs::write(dir.join("decks/blue.txt"), "20 Island\n40 Opt").unwrap();
+    fs::write(
+      dir.join("manifest.json"),
+      r#"{
+        "runs": [
+          {
+            "name": "blue",
+            "deck": "decks/blue.txt",
+            "config": {"run_count": 100, "draw_count": 2, "seed": 5},
+            "output": "blue.json"
+          },
+          {
+            "name": "blue on the play",
+            "deck": "decks/blue.txt",
+            "config": {"run_count": 50, "draw_count": 2, "on_the_play": true}
+          }
+        ]
+      }"#,
+    )
+    .unwrap();
+    let results = run_manifest_file(&dir.join("manifest.json")).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].name, "blue");
+    assert_eq!(results[0].result.seed, 5);
+    assert_eq!(results[1].result.run_count, 50);
+    let written: SimulationResult =
+      serde_json::from_str(&fs::read_to_string(dir.join("blue.json")).unwrap()).unwrap();
+    assert_eq!(written.seed, 5);
+    assert_eq!(written.cards[0].name, "Opt");
+    let error = run_manifest(
+      r#"{"runs": [{"name": "missing", "deck": "nope.txt", "config": {}}]}"#,
+      &dir,
+    )
+    .unwrap_err();
+    assert!(error.starts_with("missing: error reading"), "{}", error);
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
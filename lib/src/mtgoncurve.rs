@@ -4,8 +4,9 @@
 use crate::card::{Card, CardKind, ManaColorCount, ManaCost};
 use crate::data::ALL_CARDS;
 use crate::deck::Deck;
-use crate::mulligan::London;
-use crate::simulation::{Observations, Simulation, SimulationConfig};
+use crate::hand::{CantripPolicy, ExtraDraw, PlayOrder};
+use crate::mulligan::{KeepCriteria, London};
+use crate::simulation::{MulliganStats, Observations, Simulation, SimulationConfig};
 
 use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
@@ -33,6 +34,18 @@ struct Input {
     pub mulligan_on_lands: HashSet<usize>,
     #[doc(hidden)]
     pub acceptable_hand_list: Vec<Vec<String>>,
+    /// True to draw hands using the Arena best-of-one hand smoothing algorithm
+    #[serde(default)]
+    pub hand_smoothing: bool,
+    /// Extra cards drawn on specific turns, e.g. from card draw spells
+    #[serde(default)]
+    pub extra_draws: Vec<ExtraDraw>,
+    /// The simulation seed, or None for a random seed
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The criteria a hand with an acceptable land count must also meet to be kept
+    #[serde(default)]
+    pub keep_criteria: KeepCriteria,
 }
 
 /// Output format expected by https://mtgoncurve.com
@@ -43,6 +56,7 @@ struct Output {
     pub deck_size: usize,
     pub accumulated_opening_hand_size: usize,
     pub accumulated_opening_hand_land_count: usize,
+    pub mulligan_stats: MulliganStats,
     pub deck_average_cmc: f64,
 
     pub total_land_counts: ManaColorCount,
@@ -59,7 +73,10 @@ struct CardObservation {
     card: MtgOnCurveCard,
     cmc: u8,
     card_count: usize,
+    /// Observations for the play order given by `Input::on_the_play`
     observations: Observations,
+    observations_on_the_play: Observations,
+    observations_on_the_draw: Observations,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -120,6 +137,12 @@ pub fn mtgoncurve_run(input: &JsValue) -> JsValue {
     JsValue::from_serde(&result).expect("this can't fail")
 }
 
+/// Returns the serialized `VersionInfo` of this build, see `landlord::version_info`
+#[wasm_bindgen]
+pub fn mtgoncurve_version_info() -> JsValue {
+    JsValue::from_serde(&crate::version_info()).expect("this can't fail")
+}
+
 fn run_impl(input: &Input) -> Result<Output, Error> {
     let deck = match Deck::from_list(&input.code) {
         Err(e) => return Err(Error::BadDeckcode(e.0)),
@@ -134,6 +157,8 @@ fn run_impl(input: &Input) -> Result<Output, Error> {
     let mut mulligan = London::never();
     mulligan.mulligan_down_to = input.mulligan_down_to;
     mulligan.mulligan_on_lands = input.mulligan_on_lands.clone();
+    mulligan.hand_smoothing = input.hand_smoothing;
+    mulligan.keep_criteria = input.keep_criteria;
     for (i, acceptable_hand) in input.acceptable_hand_list.iter().enumerate() {
         let mut keep_cards = HashSet::new();
         for card_name in acceptable_hand {
@@ -153,10 +178,14 @@ fn run_impl(input: &Input) -> Result<Output, Error> {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: input.on_the_play,
+        extra_draws: &input.extra_draws,
+        seed: input.seed,
+        cantrip_policy: CantripPolicy::Off,
     });
     let mut outputs = Output::new();
     outputs.accumulated_opening_hand_size = sim.accumulated_opening_hand_size;
     outputs.accumulated_opening_hand_land_count = sim.accumulated_opening_hand_land_count;
+    outputs.mulligan_stats = sim.mulligan_stats();
 
     outputs.card_observations = deck
         .iter()
@@ -164,13 +193,24 @@ fn run_impl(input: &Input) -> Result<Output, Error> {
         .map(|c| {
             let card = &c.card;
             let count = c.count;
-            let o = sim.observations_for_card_by_turn(&card, card.turn as usize);
+            let turn = card.turn as usize;
+            let o = sim.observations_for_card_by_turn(&card, turn);
             let cmc = card.mana_cost.cmc();
             CardObservation {
                 card: card.into(),
                 cmc,
                 card_count: count,
                 observations: o,
+                observations_on_the_play: sim.observations_for_card_by_turn_and_play_order(
+                    card,
+                    turn,
+                    PlayOrder::First,
+                ),
+                observations_on_the_draw: sim.observations_for_card_by_turn_and_play_order(
+                    card,
+                    turn,
+                    PlayOrder::Second,
+                ),
             }
         })
         .collect();
@@ -192,6 +232,8 @@ fn run_impl(input: &Input) -> Result<Output, Error> {
                 cmc,
                 card_count: c.count,
                 observations: Observations::new(),
+                observations_on_the_play: Observations::new(),
+                observations_on_the_draw: Observations::new(),
             }
         })
         .collect();
@@ -252,6 +294,7 @@ impl Output {
             land_counts: Vec::new(),
             accumulated_opening_hand_size: 0,
             accumulated_opening_hand_land_count: 0,
+            mulligan_stats: MulliganStats::default(),
             deck_average_cmc: 0.0,
             deck_size: 0,
 
@@ -335,6 +378,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands,
             acceptable_hand_list: Vec::new(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         run_impl(&input).expect("simulation ok");
     }
@@ -387,6 +434,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands,
             acceptable_hand_list: Vec::new(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         run_impl(&input).expect("simulation ok");
     }
@@ -438,6 +489,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands,
             acceptable_hand_list,
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         run_impl(&input).expect("simulation ok");
     }
@@ -458,6 +513,10 @@ mod tests {
             mulligan_down_to: 7,
             mulligan_on_lands: Default::default(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations[0];
@@ -500,6 +559,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -559,6 +622,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -618,6 +685,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -677,6 +748,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -745,6 +820,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -793,6 +872,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -839,6 +922,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -878,6 +965,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -919,6 +1010,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -964,6 +1059,10 @@ mod tests {
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -1004,6 +1103,10 @@ Deck
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         let results = run_impl(&input).expect("simulation ok");
         let obs = &results.card_observations;
@@ -1028,6 +1131,10 @@ Deck
             mulligan_down_to: 5,
             mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
             acceptable_hand_list: Default::default(),
+            hand_smoothing: false,
+            extra_draws: Vec::new(),
+            seed: None,
+            keep_criteria: KeepCriteria::Lands,
         };
         run_impl(&input).expect("simulation ok");
     }
@@ -0,0 +1,104 @@
+use crate::card::{Card, ManaCost};
+use crate::hand::{Hand, PlayOrder};
+
+/// The turn by which `KeepCriteria::LandsAndEarlyPlay` requires a castable spell
+const EARLY_PLAY_TURN: u8 = 2;
+
+/// KeepCriteria selects which hands are keepable once their land count passes the
+/// mulligan strategy. Every criterion requires an acceptable land count, and the others
+/// are independent: `LandsAndEarlyPlay` does not require colors for the other spells
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KeepCriteria {
+  /// Keep any hand with an acceptable land count
+  #[default]
+  Lands,
+  /// Keep hands whose lands can produce every color found in the mana costs
+  /// of the spells in hand. Generic and colorless costs are ignored
+  LandsAndColor,
+  /// Keep hands with a spell that the lands in hand can cast by turn 2,
+  /// without relying on any draws, even if they can't cast the other spells
+  LandsAndEarlyPlay,
+}
+
+impl KeepCriteria {
+  /// Returns true if `hand` meets the criteria. Does not check the land count,
+  /// which is up to the mulligan strategy
+  pub fn is_keepable(self, hand: &[&Card]) -> bool {
+    match self {
+      KeepCriteria::Lands => true,
+      KeepCriteria::LandsAndColor => {
+        let colors = ManaCost::R_BITS
+          | ManaCost::G_BITS
+          | ManaCost::B_BITS
+          | ManaCost::U_BITS
+          | ManaCost::W_BITS;
+        let (produced, required) = hand.iter().fold((0, 0), |(produced, required), card| {
+          if card.is_land() {
+            (produced | card.mana_cost.bits, required)
+          } else {
+            (produced, required | (card.mana_cost.bits & colors))
+          }
+        });
+        required & !produced == 0
+      }
+      KeepCriteria::LandsAndEarlyPlay => {
+        let opening = Hand::from_opening_and_draws(hand, &[]);
+        hand.iter().any(|card| {
+          !card.is_land()
+            && card.turn <= EARLY_PLAY_TURN
+            && opening
+              .auto_tap_by_turn(card, std::cmp::max(1, card.turn) as usize, PlayOrder::First)
+              .paid
+        })
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::mulligan::keep::*;
+
+  #[test]
+  fn keep_criteria_lands() {
+    let hand = vec![card!("Island"), card!("Lightning Bolt")];
+    assert!(KeepCriteria::Lands.is_keepable(&hand));
+  }
+
+  #[test]
+  fn keep_criteria_lands_and_color() {
+    let hand = vec![card!("Island"), card!("Lightning Bolt"), card!("Opt")];
+    assert!(!KeepCriteria::LandsAndColor.is_keepable(&hand));
+    let hand = vec![
+      card!("Island"),
+      card!("Steam Vents"),
+      card!("Lightning Bolt"),
+      card!("Opt"),
+    ];
+    assert!(KeepCriteria::LandsAndColor.is_keepable(&hand));
+    let hand = vec![card!("Island"), card!("Ornithopter")];
+    assert!(KeepCriteria::LandsAndColor.is_keepable(&hand));
+  }
+
+  #[test]
+  fn keep_criteria_lands_and_early_play() {
+    let hand = vec![card!("Island"), card!("Lightning Bolt"), card!("Opt")];
+    assert!(KeepCriteria::LandsAndEarlyPlay.is_keepable(&hand));
+    let hand = vec![card!("Island"), card!("Lightning Bolt")];
+    assert!(!KeepCriteria::LandsAndEarlyPlay.is_keepable(&hand));
+    let hand = vec![
+      card!("Island"),
+      card!("Forest"),
+      card!("Craterhoof Behemoth"),
+    ];
+    assert!(!KeepCriteria::LandsAndEarlyPlay.is_keepable(&hand));
+  }
+
+  #[test]
+  fn keep_criteria_early_play_ignores_other_colors() {
+    // Opt is castable on turn 1, and the Lightning Bolt is off color
+    let hand = vec![card!("Island"), card!("Lightning Bolt"), card!("Opt")];
+    assert!(!KeepCriteria::LandsAndColor.is_keepable(&hand));
+    assert!(KeepCriteria::LandsAndEarlyPlay.is_keepable(&hand));
+  }
+}
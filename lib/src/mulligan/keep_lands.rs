@@ -0,0 +1,129 @@
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::mulligan::{simulate_strategy, Mulligan, MulliganCause, MulliganStrategy};
+use rand::prelude::*;
+use std::collections::HashSet;
+
+const STARTING_HAND_SIZE: usize = 7;
+
+/// KeepLands represents a mulligan strategy that only keeps hands with one of
+/// the given land counts, following the
+/// [London mulligan rule](https://mtg.gamepedia.com/Mulligan#London_mulligan)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeepLands {
+  pub starting_hand_size: usize,
+  pub mulligan_down_to: usize,
+  /// The set of land counts on which to keep a hand
+  pub keep_on_lands: HashSet<usize>,
+}
+
+impl KeepLands {
+  /// Returns a strategy that keeps hands with any of the `lands` land counts,
+  /// mulliganing down to `down_to` card count
+  pub fn new(lands: &[usize], down_to: usize) -> Self {
+    Self {
+      starting_hand_size: STARTING_HAND_SIZE,
+      mulligan_down_to: down_to,
+      keep_on_lands: lands.iter().copied().collect(),
+    }
+  }
+}
+
+impl MulliganStrategy for KeepLands {
+  fn keep(&self, hand: &[&Card], mulligan_count: usize) -> bool {
+    let opening_hand_size = hand.len().saturating_sub(mulligan_count);
+    if opening_hand_size <= self.mulligan_down_to {
+      return true;
+    }
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    self.keep_on_lands.contains(&land_count)
+  }
+
+  fn mulligan_cause(&self, hand: &[&Card], _mulligan_count: usize) -> MulliganCause {
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    MulliganCause::of_land_count(land_count, hand.len(), |lands| {
+      self.keep_on_lands.contains(&lands)
+    })
+  }
+
+  /// Bottoms cards so the kept hand has the largest acceptable land count it can reach,
+  /// otherwise as many lands as possible, putting the highest turn spells on the bottom first
+  fn bottom(&self, hand: &[&Card], count: usize) -> Vec<usize> {
+    let (lands, spells): (Vec<usize>, Vec<usize>) =
+      (0..hand.len()).partition(|i| hand[*i].is_land());
+    let kept_size = hand.len() - count;
+    let most_lands = std::cmp::min(lands.len(), kept_size);
+    let fewest_lands = kept_size.saturating_sub(spells.len());
+    let keep_lands = (fewest_lands..=most_lands)
+      .rev()
+      .find(|n| self.keep_on_lands.contains(n))
+      .unwrap_or(most_lands);
+    let mut spells = spells;
+    spells.sort_by_key(|i| std::cmp::Reverse(hand[*i].turn));
+    let mut bottom: Vec<usize> = lands[keep_lands..].to_vec();
+    bottom.extend(spells.into_iter().take(count - bottom.len()));
+    bottom
+  }
+
+  fn starting_hand_size(&self) -> usize {
+    self.starting_hand_size
+  }
+}
+
+impl Mulligan for KeepLands {
+  fn simulate_hand(&self, rng: &mut impl Rng, deck: &[&Card], draws: usize) -> Hand {
+    simulate_strategy(self, rng, deck, draws)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::keep_lands::*;
+  use crate::simulation::*;
+
+  #[test]
+  fn keep_lands_only_keeps_land_counts() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let mulligan = KeepLands::new(&[2, 3, 4], 0);
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 500,
+      draw_count: 0,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      let land_count = hand.count_in_opening_with_draws(0, |c| c.kind.is_land());
+      assert!(
+        mulligan.keep_on_lands.contains(&land_count) || hand.opening_hand_size < 2,
+        "kept {} lands in a hand of {}",
+        land_count,
+        hand.opening_hand_size
+      );
+    }
+  }
+
+  #[test]
+  fn keep_lands_bottom() {
+    let forest = card!("Forest");
+    let elves = card!("Llanowar Elves");
+    let hand = vec![forest, forest, forest, forest, elves, elves, elves];
+    let mulligan = KeepLands::new(&[2], 5);
+    let mut bottom = mulligan.bottom(&hand, 2);
+    bottom.sort();
+    assert_eq!(bottom, vec![2, 3]);
+    let mulligan = KeepLands::new(&[5], 5);
+    let mut bottom = mulligan.bottom(&hand, 2);
+    bottom.sort();
+    assert_eq!(bottom, vec![4, 5]);
+  }
+}
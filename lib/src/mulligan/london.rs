@@ -1,6 +1,10 @@
 use crate::card::Card;
+use crate::deck::Deck;
 use crate::hand::Hand;
-use crate::mulligan::Mulligan;
+use crate::mulligan::mulligan::draw_cards;
+use crate::mulligan::strategy::bottom_by_turn;
+use crate::mulligan::{KeepCriteria, Mulligan, MulliganCause, MulliganStrategy};
+use crate::simulation::CardGoal;
 use rand::prelude::*;
 use std::collections::HashSet;
 
@@ -17,6 +21,16 @@ pub struct London {
   /// A list of card sets that represent keepable hands
   /// The card is represented by it's `u64` hash value
   pub acceptable_hand_list: Vec<HashSet<u64>>,
+  /// The number of mulligans that do not put a card on the bottom,
+  /// e.g. the free first mulligan in Commander
+  #[serde(default)]
+  pub free_mulligans: usize,
+  /// True to draw each hand using the Arena best-of-one hand smoothing algorithm
+  #[serde(default)]
+  pub hand_smoothing: bool,
+  /// The criteria a hand with an acceptable land count must also meet to be kept
+  #[serde(default)]
+  pub keep_criteria: KeepCriteria,
 }
 
 impl London {
@@ -27,6 +41,9 @@ impl London {
       mulligan_down_to: STARTING_HAND_SIZE,
       mulligan_on_lands: HashSet::new(),
       acceptable_hand_list: Default::default(),
+      free_mulligans: 0,
+      hand_smoothing: false,
+      keep_criteria: KeepCriteria::Lands,
     }
   }
 
@@ -42,6 +59,42 @@ impl London {
       mulligan_down_to: down_to,
       mulligan_on_lands,
       acceptable_hand_list: Default::default(),
+      free_mulligans: 0,
+      hand_smoothing: false,
+      keep_criteria: KeepCriteria::Lands,
+    }
+  }
+
+  /// Returns a London mulligan strategy for Commander and Brawl, with a free first mulligan,
+  /// that mulligans hands with 0, 1, 6, or 7 lands down to 5 cards
+  pub fn commander() -> Self {
+    Self {
+      starting_hand_size: STARTING_HAND_SIZE,
+      mulligan_down_to: 5,
+      mulligan_on_lands: vec![0, 1, 6, 7].into_iter().collect(),
+      acceptable_hand_list: Default::default(),
+      free_mulligans: 1,
+      hand_smoothing: false,
+      keep_criteria: KeepCriteria::Lands,
+    }
+  }
+
+  /// Returns a London mulligan strategy that digs for any of the cards of `deck` that meet
+  /// `goal`, e.g. a combo piece, mulliganing hands without one of them or with any of the
+  /// `lands` land counts down to `down_to` card count. The kept card is never bottomed
+  pub fn for_goal(deck: &Deck, goal: &CardGoal, lands: &[usize], down_to: usize) -> Self {
+    Self {
+      starting_hand_size: STARTING_HAND_SIZE,
+      mulligan_down_to: down_to,
+      mulligan_on_lands: lands.iter().copied().collect(),
+      acceptable_hand_list: goal
+        .cards(deck)
+        .iter()
+        .map(|card| std::iter::once(card.hash).collect())
+        .collect(),
+      free_mulligans: 0,
+      hand_smoothing: false,
+      keep_criteria: KeepCriteria::Lands,
     }
   }
 }
@@ -55,8 +108,8 @@ impl Mulligan for London {
     // The number of cards to mulligan down to, capped by starting_hand_size
     let mulligan_down_to = std::cmp::min(self.mulligan_down_to, starting_hand_size);
     assert!(starting_hand_size >= mulligan_down_to);
-    // The maximum number of mulligan rounds to consider
-    let max_mulligan_rounds = starting_hand_size - mulligan_down_to + 1;
+    // The maximum number of mulligan rounds to consider, including any free mulligans
+    let max_mulligan_rounds = starting_hand_size - mulligan_down_to + 1 + self.free_mulligans;
     assert!(max_mulligan_rounds > 0);
 
     // Need to draw starting_hand_size cards plus the number of draws specified, capped by deck_size
@@ -69,16 +122,19 @@ impl Mulligan for London {
     // Data structures used across multiple mulligan rounds to reduce the number of allocations
     let mut must_keep_card_indices = Vec::with_capacity(starting_hand_size);
     let mut seen_card_hashes = HashSet::with_capacity(starting_hand_size);
+    let mut mulligans = Vec::new();
 
     // Iterate through the mulligan rounds. Note that round == 0 is considered the first starting hand draw
     for round in 0..max_mulligan_rounds {
       // Rather than shuffle the entire deck, only consider cards_to_draw
-      let mut shuffled_deck: Vec<_> = index_range
-        .partial_shuffle(&mut rng, cards_to_draw)
-        .0
-        .iter()
-        .map(|i| deck[*i])
-        .collect();
+      let mut shuffled_deck = draw_cards(
+        &mut rng,
+        deck,
+        &mut index_range,
+        cards_to_draw,
+        starting_hand_size,
+        self.hand_smoothing,
+      );
       // Starting hand consists of the first starting_hand_size cards
       let starting_hand = &mut shuffled_deck[..starting_hand_size];
 
@@ -90,9 +146,11 @@ impl Mulligan for London {
       let land_count = starting_hand
         .iter()
         .fold(0, |accum, c| if c.is_land() { accum + 1 } else { accum });
-      let sufficient_land_count = !self.mulligan_on_lands.contains(&land_count);
-      // Is this not the last round? Not enough lands? Great -- onto the next round
+      let sufficient_land_count = !self.mulligan_on_lands.contains(&land_count)
+        && self.keep_criteria.is_keepable(starting_hand);
+      // Is this not the last round? Not enough lands, or missing the keep criteria? Great -- onto the next round
       if !is_last_round && !sufficient_land_count {
+        mulligans.push(self.land_cause(land_count, starting_hand_size));
         continue;
       }
 
@@ -125,7 +183,8 @@ impl Mulligan for London {
       let keep = is_last_round
         || (sufficient_land_count && (disregard_found_acceptable_hand || found_acceptable_hand));
       if keep {
-        let opening_hand_size = starting_hand_size - round;
+        // Free mulligans do not reduce the opening hand size
+        let opening_hand_size = starting_hand_size - round.saturating_sub(self.free_mulligans);
         // We can keep the hand! Let's update the must_keep_card_indices list
         // with some land cards to keep as well. Try to keep enough lands to
         // satisfy the mulligan strategy
@@ -169,16 +228,112 @@ impl Mulligan for London {
         for (discard_count, i) in (opening_hand_size..starting_hand_size).enumerate() {
           shuffled_deck.swap(i, cards_to_draw - 1 - discard_count);
         }
-        return Hand::from_opening_and_draws(
+        let mut hand = Hand::from_opening_and_draws(
           &shuffled_deck[..opening_hand_size],
           &shuffled_deck[opening_hand_size..],
         );
+        // Free mulligans count as mulligans, even though the hand size is unchanged
+        hand.mulligan_count += std::cmp::min(round, self.free_mulligans);
+        hand.mulligans = mulligans;
+        return hand;
       }
+      // The land count and keep criteria passed, so the hand misses the acceptable cards
+      mulligans.push(MulliganCause::MissingCards);
     }
     unreachable!();
   }
 }
 
+impl London {
+  /// Returns the cause of mulliganing a hand of `hand_size` cards with `land_count` lands
+  /// that has a land count to mulligan or misses the keep criteria
+  fn land_cause(&self, land_count: usize, hand_size: usize) -> MulliganCause {
+    if self.mulligan_on_lands.contains(&land_count) {
+      MulliganCause::of_land_count(land_count, hand_size, |lands| {
+        !self.mulligan_on_lands.contains(&lands)
+      })
+    } else {
+      MulliganCause::KeepCriteria
+    }
+  }
+}
+
+impl MulliganStrategy for London {
+  fn mulligan_cause(&self, hand: &[&Card], _mulligan_count: usize) -> MulliganCause {
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    if self.mulligan_on_lands.contains(&land_count) || !self.keep_criteria.is_keepable(hand) {
+      self.land_cause(land_count, hand.len())
+    } else {
+      MulliganCause::MissingCards
+    }
+  }
+
+  fn keep(&self, hand: &[&Card], mulligan_count: usize) -> bool {
+    let opening_hand_size = hand
+      .len()
+      .saturating_sub(mulligan_count.saturating_sub(self.free_mulligans));
+    if opening_hand_size <= self.mulligan_down_to {
+      return true;
+    }
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    if self.mulligan_on_lands.contains(&land_count) || !self.keep_criteria.is_keepable(hand) {
+      return false;
+    }
+    self.acceptable_hand_list.is_empty()
+      || self.acceptable_hand_list.iter().any(|acceptable_hand| {
+        acceptable_hand
+          .iter()
+          .all(|hash| hand.iter().any(|c| c.hash == *hash))
+      })
+  }
+
+  /// Keeps the first acceptable hand found and enough lands to satisfy the
+  /// mulligan strategy, bottoming the highest turn spells first
+  fn bottom(&self, hand: &[&Card], count: usize) -> Vec<usize> {
+    let opening_hand_size = hand.len() - count;
+    let mut must_keep_card_indices = Vec::with_capacity(hand.len());
+    for acceptable_hand in &self.acceptable_hand_list {
+      let mut seen_card_hashes = HashSet::with_capacity(hand.len());
+      let indices: Vec<usize> = (0..hand.len())
+        .filter(|i| {
+          seen_card_hashes.insert(hand[*i].hash) && acceptable_hand.contains(&hand[*i].hash)
+        })
+        .collect();
+      if indices.len() == acceptable_hand.len() {
+        must_keep_card_indices = indices;
+        break;
+      }
+    }
+    let mut lands_saved = 0;
+    for (i, card) in hand.iter().enumerate() {
+      if !card.is_land() {
+        continue;
+      }
+      if self.mulligan_on_lands.contains(&lands_saved) && lands_saved < opening_hand_size {
+        must_keep_card_indices.push(i);
+        lands_saved += 1;
+      } else {
+        break;
+      }
+    }
+    must_keep_card_indices.sort();
+    must_keep_card_indices.dedup();
+    bottom_by_turn(hand, count, &must_keep_card_indices)
+  }
+
+  fn starting_hand_size(&self) -> usize {
+    self.starting_hand_size
+  }
+
+  fn free_mulligans(&self) -> usize {
+    self.free_mulligans
+  }
+
+  fn hand_smoothing(&self) -> bool {
+    self.hand_smoothing
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use crate::deck::*;
@@ -210,6 +365,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       for hand in sim.hands {
         assert_eq!(hand.opening_hand_size, 7);
@@ -253,6 +411,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       for hand in sim.hands {
         let hand_contains_card = hand
@@ -301,6 +462,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       for hand in sim.hands {
         let hand_contains_cards = hand
@@ -349,6 +513,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       for hand in sim.hands {
         let hand_contains_cards = hand
@@ -397,6 +564,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       for hand in sim.hands {
         let hand_contains_cards = hand
@@ -432,6 +602,9 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     for hand in sim.hands {
       assert_eq!(hand.opening_hand_size, 0);
@@ -439,6 +612,106 @@ mod tests {
     }
   }
 
+  #[test]
+  fn mulligan_free_mulligan_test_0() {
+    let code = "
+        1 Cleansing Nova (M19) 9
+        1 Vraska, Relic Seeker (XLN) 232
+        1 Sinister Sabotage (GRN) 54
+        1 Opt (XLN) 65
+        1 Vraska's Contempt (XLN) 129
+        1 Thought Erasure
+        1 Cry of the Carnarium (RNA) 70
+        1 Island
+        ";
+    let deck = decklist!(code);
+    let mut mulligan = London::always(7);
+    mulligan.free_mulligans = 1;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 0,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 7);
+      assert_eq!(hand.mulligan_count, 1);
+    }
+  }
+
+  #[test]
+  fn mulligan_free_mulligan_test_1() {
+    let code = "
+        1 Cleansing Nova (M19) 9
+        1 Vraska, Relic Seeker (XLN) 232
+        1 Sinister Sabotage (GRN) 54
+        1 Opt (XLN) 65
+        1 Vraska's Contempt (XLN) 129
+        1 Thought Erasure
+        1 Cry of the Carnarium (RNA) 70
+        1 Island
+        ";
+    let deck = decklist!(code);
+    let mut mulligan = London::always(5);
+    mulligan.free_mulligans = 1;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 0,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 5);
+      assert_eq!(hand.mulligan_count, 3);
+    }
+  }
+
+  #[test]
+  fn hand_smoothing_test_0() {
+    let code = "
+        30 Island
+        30 Opt
+        ";
+    let deck = decklist!(code);
+    let runs = 20000;
+    let mut mulligan = London::never();
+    let p_three_or_four_lands = |mulligan: &London| {
+      let sim = Simulation::from_config(&SimulationConfig {
+        run_count: runs,
+        draw_count: 0,
+        mulligan,
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      });
+      let count = sim
+        .hands
+        .iter()
+        .filter(|hand| {
+          let lands = hand.count_in_opening_with_draws(0, |c| c.kind.is_land());
+          lands == 3 || lands == 4
+        })
+        .count();
+      count as f64 / runs as f64
+    };
+    // Hypergeometric, 60, 30, 7, 3 or 4
+    let unsmoothed = p_three_or_four_lands(&mulligan);
+    assert!(f64::abs(unsmoothed - 0.567) < 0.02);
+    mulligan.hand_smoothing = true;
+    let smoothed = p_three_or_four_lands(&mulligan);
+    assert!(smoothed > unsmoothed + 0.1);
+  }
+
   // Verify that our simulation can return results matched by various articles found by Frank
   // Karsten
 
@@ -475,6 +748,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -489,6 +765,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -503,6 +782,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -517,6 +799,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -531,6 +816,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -545,6 +833,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -559,6 +850,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let obs = sim.observations_for_card(card);
       let p = obs.in_opening_hand as f64 / runs as f64;
@@ -624,6 +918,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -638,6 +935,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -652,6 +952,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -666,6 +969,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -734,6 +1040,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands);
       let p = good_hands as f64 / runs as f64;
@@ -748,6 +1057,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands);
       let p = good_hands as f64 / runs as f64;
@@ -763,6 +1075,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands);
       let p = good_hands as f64 / runs as f64;
@@ -777,6 +1092,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands);
       let p = good_hands as f64 / runs as f64;
@@ -831,6 +1149,9 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let good_hands = good_hand_count(&sim.hands, 0);
     let p = good_hands as f64 / runs as f64;
@@ -843,6 +1164,9 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let good_hands = good_hand_count(&sim.hands, 1);
     let p = good_hands as f64 / runs as f64;
@@ -898,6 +1222,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -912,6 +1239,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 1);
       let p = good_hands as f64 / runs as f64;
@@ -967,6 +1297,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 0);
       let p = good_hands as f64 / runs as f64;
@@ -981,6 +1314,9 @@ mod tests {
         mulligan: &mulligan,
         deck: &deck,
         on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
       });
       let good_hands = good_hand_count(&sim.hands, 1);
       let p = good_hands as f64 / runs as f64;
@@ -1035,6 +1371,9 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let good_hands = good_hand_count(&sim.hands, 0);
     let p = good_hands as f64 / runs as f64;
@@ -1060,6 +1399,9 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     for hand in sim.hands {
       assert_eq!(hand.opening_hand_size, 2);
@@ -1084,9 +1426,61 @@ mod tests {
       mulligan: &mulligan,
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     for hand in sim.hands {
       assert_eq!(hand.opening_hand_size, 0);
     }
   }
+
+  #[test]
+  fn for_goal_digs_for_the_goal() {
+    let deck = decklist!(
+      "
+      24 Forest
+      32 Llanowar Elves
+      4 Craterhoof Behemoth [kill]
+      "
+    );
+    let goal = CardGoal::Tag("kill".to_string());
+    let kill = goal.cards(&deck);
+    assert_eq!(kill.len(), 1);
+    let simulate = |mulligan: &London, run_count: usize, draw_count: usize| {
+      Simulation::from_config(&SimulationConfig {
+        run_count,
+        draw_count,
+        mulligan,
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: Some(5),
+        cantrip_policy: CantripPolicy::Off,
+      })
+    };
+    let keep_all = simulate(&London::never(), 5000, 3).p_combo_by_turn(&kill, &[], 3);
+    let dig =
+      simulate(&London::for_goal(&deck, &goal, &[], 4), 5000, 3).p_combo_by_turn(&kill, &[], 3);
+    // Hypergeometric odds of 1+ of 4 copies in the 7 card opening hand of 60: ~0.40
+    assert!(f64::abs(keep_all[0] - 0.40) < 0.03);
+    assert!(dig[0] > 0.75);
+    for turn in 1..3 {
+      assert!(dig[turn] >= dig[turn - 1]);
+      assert!(dig[turn] > keep_all[turn]);
+    }
+    // The kept hands never bottom the card dug for
+    let sim = simulate(&London::for_goal(&deck, &goal, &[], 4), 500, 0);
+    let found = sim.p_combo_by_turn(&kill, &[], 1)[0];
+    let mulliganed_to_four = sim
+      .hands
+      .iter()
+      .filter(|hand| hand.opening_hand_size == 4)
+      .count() as f64
+      / 500.0;
+    assert!(found + mulliganed_to_four >= 1.0 - f64::EPSILON);
+    // A goal missing from the deck keeps every hand
+    let missing = London::for_goal(&deck, &CardGoal::Card("Opt".to_string()), &[], 4);
+    assert!(missing.acceptable_hand_list.is_empty());
+  }
 }
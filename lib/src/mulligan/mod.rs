@@ -2,13 +2,22 @@
 //!
 //! The `mulligan` module defines a `Mulligan` trait and
 //! several implementations of different mulligan strategies.
+//!
+//! The `MulliganStrategy` trait separates the keep and bottom decisions from the
+//! mulligan rule itself, and can be boxed to choose a strategy at runtime.
 
+mod keep;
+mod keep_lands;
 mod london;
 mod mulligan;
 mod never;
+mod strategy;
 mod vancouver;
 
+pub use keep::KeepCriteria;
+pub use keep_lands::KeepLands;
 pub use london::London;
 pub use mulligan::Mulligan;
 pub use never::Never;
+pub use strategy::{simulate_strategy, MulliganCause, MulliganRule, MulliganStrategy};
 pub use vancouver::Vancouver;
@@ -13,3 +13,41 @@ pub trait Mulligan {
   /// * `draws` - The number of cards to draw after the mulligan process
   fn simulate_hand(&self, rng: &mut impl Rng, deck: &[&Card], draws: usize) -> Hand;
 }
+
+/// Returns the first `count` cards of a randomly shuffled `deck`, using `index_range` as scratch space.
+///
+/// When `smoothing` is true, this replicates the Arena best-of-one hand smoothing algorithm:
+/// two candidates are shuffled and we keep the one whose first `hand_size` cards contain a
+/// land count closest to the expected land count of the deck
+pub(crate) fn draw_cards<'a>(
+  rng: &mut impl Rng,
+  deck: &[&'a Card],
+  index_range: &mut [usize],
+  count: usize,
+  hand_size: usize,
+  smoothing: bool,
+) -> Vec<&'a Card> {
+  let mut shuffle = |rng: &mut _| -> Vec<&'a Card> {
+    index_range
+      .partial_shuffle(rng, count)
+      .0
+      .iter()
+      .map(|i| deck[*i])
+      .collect()
+  };
+  let candidate = shuffle(rng);
+  if !smoothing || deck.is_empty() {
+    return candidate;
+  }
+  let land_count = |cards: &[&Card]| cards.iter().take(hand_size).filter(|c| c.is_land()).count();
+  let deck_land_count = deck.iter().filter(|c| c.is_land()).count();
+  let expected = (hand_size * deck_land_count) as f64 / deck.len() as f64;
+  let other = shuffle(rng);
+  let candidate_distance = f64::abs(land_count(&candidate) as f64 - expected);
+  let other_distance = f64::abs(land_count(&other) as f64 - expected);
+  if other_distance < candidate_distance {
+    other
+  } else {
+    candidate
+  }
+}
@@ -1,6 +1,7 @@
 use crate::card::Card;
 use crate::hand::Hand;
-use crate::mulligan::Mulligan;
+use crate::mulligan::mulligan::draw_cards;
+use crate::mulligan::{Mulligan, MulliganStrategy};
 use rand::prelude::*;
 
 // Hardcoded starting handsize, consider allowing users to specify
@@ -11,12 +12,16 @@ const STARTING_HAND_SIZE: usize = 7;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Never {
   pub starting_hand_size: usize,
+  /// True to draw the hand using the Arena best-of-one hand smoothing algorithm
+  #[serde(default)]
+  pub hand_smoothing: bool,
 }
 
 impl Never {
   pub fn new() -> Self {
     Self {
       starting_hand_size: STARTING_HAND_SIZE,
+      hand_smoothing: false,
     }
   }
 
@@ -38,15 +43,31 @@ impl Mulligan for Never {
     let cards_to_draw = std::cmp::min(deck_len, self.starting_hand_size + draws);
     let starting_hand_size = std::cmp::min(deck_len, self.starting_hand_size);
     let mut index_range: Vec<_> = (0..deck_len).collect();
-    let shuffled_deck: Vec<_> = index_range
-      .partial_shuffle(&mut rng, cards_to_draw)
-      .0
-      .iter()
-      .map(|i| deck[*i])
-      .collect();
+    let shuffled_deck = draw_cards(
+      &mut rng,
+      deck,
+      &mut index_range,
+      cards_to_draw,
+      starting_hand_size,
+      self.hand_smoothing,
+    );
     return Hand::from_opening_and_draws(
       &shuffled_deck[..starting_hand_size],
       &shuffled_deck[starting_hand_size..],
     );
   }
 }
+
+impl MulliganStrategy for Never {
+  fn keep(&self, _hand: &[&Card], _mulligan_count: usize) -> bool {
+    true
+  }
+
+  fn starting_hand_size(&self) -> usize {
+    self.starting_hand_size
+  }
+
+  fn hand_smoothing(&self) -> bool {
+    self.hand_smoothing
+  }
+}
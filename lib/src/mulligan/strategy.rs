@@ -0,0 +1,314 @@
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::mulligan::mulligan::draw_cards;
+use crate::mulligan::Mulligan;
+use rand::prelude::*;
+
+const STARTING_HAND_SIZE: usize = 7;
+
+/// The rule that determines how the hand size shrinks with each mulligan
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MulliganRule {
+  /// Draw a full starting hand every round, then put one card per mulligan on the bottom
+  /// of the library. See [London mulligan](https://mtg.gamepedia.com/Mulligan#London_mulligan)
+  London,
+  /// Draw one fewer card every round.
+  /// See [Vancouver mulligan](https://mtg.gamepedia.com/Mulligan#Vancouver_mulligan)
+  Vancouver,
+}
+
+/// MulliganCause is the reason a mulligan strategy didn't keep a hand,
+/// see `Simulation::mulligan_stats`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MulliganCause {
+  /// The strategy mulligans the land count of the hand, and keeps a larger one
+  TooFewLands,
+  /// The strategy mulligans the land count of the hand, and only keeps smaller ones
+  TooManyLands,
+  /// The land count is kept, but the hand misses the keep criteria, see `KeepCriteria`
+  KeepCriteria,
+  /// The land count is kept, but the hand lacks the cards the strategy looks for
+  MissingCards,
+  /// A cause the strategy doesn't report, or a strategy that keeps no land count
+  Other,
+}
+
+impl MulliganCause {
+  /// Returns the cause of mulliganing a hand of `hand_size` cards for its `land_count`,
+  /// where `keeps` returns true for the land counts the strategy keeps
+  pub fn of_land_count<F: Fn(usize) -> bool>(
+    land_count: usize,
+    hand_size: usize,
+    keeps: F,
+  ) -> Self {
+    if (land_count + 1..=hand_size).any(&keeps) {
+      MulliganCause::TooFewLands
+    } else if (0..land_count).any(&keeps) {
+      MulliganCause::TooManyLands
+    } else {
+      MulliganCause::Other
+    }
+  }
+}
+
+/// A mulligan strategy decides which hands to keep and, under the London rule,
+/// which cards to put on the bottom of the library
+///
+/// Unlike `Mulligan`, this trait is object safe, so a strategy can be chosen at runtime
+/// and handed to `SimulationConfig` as a `Box<dyn MulliganStrategy + Send + Sync>`
+pub trait MulliganStrategy {
+  /// Returns true if the drawn `hand` should be kept after `mulligan_count` mulligans
+  fn keep(&self, hand: &[&Card], mulligan_count: usize) -> bool;
+
+  /// Returns why the drawn `hand` isn't kept after `mulligan_count` mulligans, see `keep`
+  fn mulligan_cause(&self, _hand: &[&Card], _mulligan_count: usize) -> MulliganCause {
+    MulliganCause::Other
+  }
+
+  /// Returns the indices of the `count` cards in the kept `hand` to put on the bottom of the library
+  ///
+  /// The default implementation bottoms the nonland cards with the highest turn first, then lands
+  fn bottom(&self, hand: &[&Card], count: usize) -> Vec<usize> {
+    bottom_by_turn(hand, count, &[])
+  }
+
+  /// Returns the rule used to shrink the hand with each mulligan
+  fn rule(&self) -> MulliganRule {
+    MulliganRule::London
+  }
+
+  /// Returns the number of cards drawn for the first hand
+  fn starting_hand_size(&self) -> usize {
+    STARTING_HAND_SIZE
+  }
+
+  /// Returns the number of mulligans that do not shrink the hand,
+  /// e.g. the free first mulligan in Commander
+  fn free_mulligans(&self) -> usize {
+    0
+  }
+
+  /// Returns true to draw each hand using the Arena best-of-one hand smoothing algorithm
+  fn hand_smoothing(&self) -> bool {
+    false
+  }
+}
+
+impl Mulligan for Box<dyn MulliganStrategy + Send + Sync> {
+  fn simulate_hand(&self, rng: &mut impl Rng, deck: &[&Card], draws: usize) -> Hand {
+    simulate_strategy(self.as_ref(), rng, deck, draws)
+  }
+}
+
+/// Returns a randomly shuffled `Hand`, asking `strategy` whether to keep each drawn hand
+/// and which cards to bottom once it keeps
+///
+/// # Arguments
+///
+/// * `strategy` - The mulligan strategy that makes the keep and bottom decisions
+/// * `rng` - A random number generator used to shuffle the deck
+/// * `deck` - A collection of cards that a player starts a game with. See [Deck](https://mtg.gamepedia.com/Deck)
+/// * `draws` - The number of cards to draw after the mulligan process
+pub fn simulate_strategy<S: MulliganStrategy + ?Sized>(
+  strategy: &S,
+  mut rng: &mut impl Rng,
+  deck: &[&Card],
+  draws: usize,
+) -> Hand {
+  let deck_size = deck.len();
+  let starting_hand_size = std::cmp::min(strategy.starting_hand_size(), deck_size);
+  let mut index_range: Vec<_> = (0..deck_size).collect();
+  let mut mulligan_count: usize = 0;
+  let mut mulligans = Vec::new();
+  loop {
+    let opening_hand_size =
+      starting_hand_size.saturating_sub(mulligan_count.saturating_sub(strategy.free_mulligans()));
+    let drawn_hand_size = match strategy.rule() {
+      MulliganRule::London => starting_hand_size,
+      MulliganRule::Vancouver => opening_hand_size,
+    };
+    let cards_to_draw = std::cmp::min(drawn_hand_size + draws, deck_size);
+    let shuffled_deck = draw_cards(
+      &mut rng,
+      deck,
+      &mut index_range,
+      cards_to_draw,
+      drawn_hand_size,
+      strategy.hand_smoothing(),
+    );
+    let drawn_hand = &shuffled_deck[..drawn_hand_size];
+
+    // Have to keep the hand once there is nothing left to mulligan away
+    if opening_hand_size > 0 && !strategy.keep(drawn_hand, mulligan_count) {
+      mulligans.push(strategy.mulligan_cause(drawn_hand, mulligan_count));
+      mulligan_count += 1;
+      continue;
+    }
+
+    let bottom_count = drawn_hand_size - opening_hand_size;
+    let mut bottom = if bottom_count > 0 {
+      strategy.bottom(drawn_hand, bottom_count)
+    } else {
+      Vec::new()
+    };
+    bottom.sort();
+    bottom.dedup();
+    assert_eq!(
+      bottom.len(),
+      bottom_count,
+      "strategy bottomed the wrong number of cards"
+    );
+
+    // Cards put on the bottom of the library are drawn after every other card
+    let (bottomed, opening): (Vec<_>, Vec<_>) = drawn_hand
+      .iter()
+      .enumerate()
+      .partition(|(i, _)| bottom.contains(i));
+    let opening: Vec<&Card> = opening.into_iter().map(|(_, c)| *c).collect();
+    let draws: Vec<&Card> = shuffled_deck[drawn_hand_size..]
+      .iter()
+      .chain(bottomed.into_iter().map(|(_, c)| c))
+      .copied()
+      .collect();
+    let mut hand = Hand::from_opening_and_draws(&opening, &draws);
+    hand.mulligan_count = mulligan_count;
+    hand.mulligans = mulligans;
+    return hand;
+  }
+}
+
+/// Returns the indices of `count` cards in `hand` to bottom, preferring nonland cards
+/// with the highest turn, then lands. Indices in `keep` are only bottomed as a last resort
+pub(crate) fn bottom_by_turn(hand: &[&Card], count: usize, keep: &[usize]) -> Vec<usize> {
+  let mut candidates: Vec<usize> = (0..hand.len()).filter(|i| !keep.contains(i)).collect();
+  candidates.sort_by_key(|i| (hand[*i].is_land(), std::cmp::Reverse(hand[*i].turn)));
+  candidates.extend(keep.iter().rev().filter(|i| **i < hand.len()));
+  candidates.dedup();
+  candidates.truncate(count);
+  candidates
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::strategy::*;
+  use crate::mulligan::*;
+  use crate::simulation::*;
+
+  #[test]
+  fn boxed_strategy_never() {
+    let deck = decklist!(
+      "
+      30 Forest
+      30 Llanowar Elves
+      "
+    );
+    let mulligan: Box<dyn MulliganStrategy + Send + Sync> = Box::new(Never::new());
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 3,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 7);
+      assert_eq!(hand.mulligan_count, 0);
+      assert_eq!(hand.len(), 10);
+    }
+  }
+
+  #[test]
+  fn boxed_strategy_london_bottoms_to_down_to() {
+    let deck = decklist!(
+      "
+      30 Forest
+      30 Llanowar Elves
+      "
+    );
+    let mulligan: Box<dyn MulliganStrategy + Send + Sync> = Box::new(London::always(5));
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 2,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 5);
+      assert_eq!(hand.mulligan_count, 2);
+      // Bottomed cards are still drawn eventually, the hand keeps every drawn card
+      assert_eq!(hand.len(), 9);
+    }
+  }
+
+  #[test]
+  fn boxed_strategy_vancouver() {
+    let deck = decklist!(
+      "
+      30 Forest
+      30 Llanowar Elves
+      "
+    );
+    let mulligan: Box<dyn MulliganStrategy + Send + Sync> = Box::new(Vancouver::always(5));
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 2,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 5);
+      assert_eq!(hand.mulligan_count, 2);
+      assert_eq!(hand.len(), 7);
+    }
+  }
+
+  #[test]
+  fn boxed_strategy_keep_lands() {
+    let deck = decklist!(
+      "
+      30 Forest
+      30 Llanowar Elves
+      "
+    );
+    let mulligan: Box<dyn MulliganStrategy + Send + Sync> = Box::new(KeepLands::new(&[3], 4));
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 200,
+      draw_count: 0,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      let land_count = hand.count_in_opening_with_draws(0, |c| c.kind.is_land());
+      assert!(hand.opening_hand_size >= 4);
+      assert!(land_count == 3 || hand.opening_hand_size == 4);
+    }
+  }
+
+  #[test]
+  fn bottom_by_turn_prefers_expensive_spells() {
+    let forest = card!("Forest");
+    let elves = card!("Llanowar Elves");
+    let wurm = card!("Craterhoof Behemoth");
+    let hand = vec![forest, elves, wurm, forest];
+    assert_eq!(bottom_by_turn(&hand, 1, &[]), vec![2]);
+    assert_eq!(bottom_by_turn(&hand, 2, &[]), vec![2, 1]);
+    assert_eq!(bottom_by_turn(&hand, 1, &[2]), vec![1]);
+    assert_eq!(bottom_by_turn(&hand, 4, &[2]).len(), 4);
+  }
+}
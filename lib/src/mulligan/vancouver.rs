@@ -1,30 +1,125 @@
 use crate::card::Card;
 use crate::hand::Hand;
-use crate::mulligan::Mulligan;
+use crate::mulligan::{simulate_strategy, Mulligan, MulliganCause, MulliganRule, MulliganStrategy};
 use rand::prelude::*;
+use std::collections::HashSet;
 
-const _STARTING_HAND_SIZE: usize = 7;
+const STARTING_HAND_SIZE: usize = 7;
 
 /// Vancouver represents a mulligan strategy that adheres to the
 /// [Vancouver mulligan rule](https://mtg.gamepedia.com/Mulligan#Vancouver_mulligan)
-///
-/// TODO This strategy is currently unimplemented
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Vancouver {
   pub starting_hand_size: usize,
+  pub mulligan_down_to: usize,
+  /// A set of land counts on which to always mulligan
+  pub mulligan_on_lands: HashSet<usize>,
 }
 
 impl Vancouver {
+  /// Returns a Vancouver mulligan strategy that never mulligans
   pub fn never() -> Self {
-    unimplemented!()
+    Self {
+      starting_hand_size: STARTING_HAND_SIZE,
+      mulligan_down_to: STARTING_HAND_SIZE,
+      mulligan_on_lands: HashSet::new(),
+    }
   }
-  pub fn always(_down_to: usize) -> Self {
-    unimplemented!()
+
+  /// Returns a Vancouver mulligan strategy that always mulligans
+  /// down to `down_to` card count
+  pub fn always(down_to: usize) -> Self {
+    Self {
+      starting_hand_size: STARTING_HAND_SIZE,
+      mulligan_down_to: down_to,
+      mulligan_on_lands: (0..=STARTING_HAND_SIZE).collect(),
+    }
+  }
+}
+
+impl MulliganStrategy for Vancouver {
+  fn keep(&self, hand: &[&Card], mulligan_count: usize) -> bool {
+    if hand.len() <= self.mulligan_down_to || mulligan_count >= self.starting_hand_size {
+      return true;
+    }
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    !self.mulligan_on_lands.contains(&land_count)
+  }
+
+  fn mulligan_cause(&self, hand: &[&Card], _mulligan_count: usize) -> MulliganCause {
+    let land_count = hand.iter().filter(|c| c.is_land()).count();
+    MulliganCause::of_land_count(land_count, hand.len(), |lands| {
+      !self.mulligan_on_lands.contains(&lands)
+    })
+  }
+
+  fn rule(&self) -> MulliganRule {
+    MulliganRule::Vancouver
+  }
+
+  fn starting_hand_size(&self) -> usize {
+    self.starting_hand_size
   }
 }
 
 impl Mulligan for Vancouver {
-  fn simulate_hand(&self, mut _rng: &mut impl Rng, _deck: &[&Card], _draws: usize) -> Hand {
-    unimplemented!();
+  fn simulate_hand(&self, rng: &mut impl Rng, deck: &[&Card], draws: usize) -> Hand {
+    simulate_strategy(self, rng, deck, draws)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::vancouver::*;
+  use crate::simulation::*;
+
+  #[test]
+  fn vancouver_never() {
+    let deck = decklist!("60 Forest");
+    let mulligan = Vancouver::never();
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 1,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      assert_eq!(hand.opening_hand_size, 7);
+      assert_eq!(hand.mulligan_count, 0);
+      assert_eq!(hand.len(), 8);
+    }
+  }
+
+  #[test]
+  fn vancouver_mulligans_on_lands() {
+    let deck = decklist!(
+      "
+      20 Forest
+      40 Llanowar Elves
+      "
+    );
+    let mut mulligan = Vancouver::never();
+    mulligan.mulligan_down_to = 4;
+    mulligan.mulligan_on_lands = vec![0, 1].into_iter().collect();
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 500,
+      draw_count: 0,
+      mulligan: &mulligan,
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    for hand in sim.hands {
+      let land_count = hand.count_in_opening_with_draws(0, |c| c.kind.is_land());
+      assert_eq!(hand.opening_hand_size + hand.mulligan_count, 7);
+      assert!(land_count >= 2 || hand.opening_hand_size == 4);
+    }
   }
 }
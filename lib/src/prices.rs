@@ -0,0 +1,282 @@
+//! # Card prices
+//!
+//! Values decks and collections from Scryfall price data, loaded from the Scryfall bulk
+//! data file or from a user supplied JSON file, so it works offline.
+//! Requires the `prices` feature.
+use crate::collection::Collection;
+use crate::deck::Deck;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+/// CardPrices are the prices of a single card, in the currencies of Scryfall price data.
+/// Missing prices are `None`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardPrices {
+  /// The paper price in US dollars
+  #[serde(default, deserialize_with = "price")]
+  pub usd: Option<f64>,
+  /// The paper price in euros
+  #[serde(default, deserialize_with = "price")]
+  pub eur: Option<f64>,
+  /// The MTGO price in event tickets
+  #[serde(default, deserialize_with = "price")]
+  pub tix: Option<f64>,
+}
+
+/// CardValue is the value of the copies of a card, see `PriceList::deck_value`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardValue {
+  pub name: String,
+  pub count: usize,
+  /// The prices of a single copy
+  pub prices: CardPrices,
+  /// The prices of all `count` copies
+  pub total: CardPrices,
+}
+
+/// Value is the value of a deck or a collection, with a breakdown by card
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Value {
+  /// The cards, most expensive in US dollars first
+  pub cards: Vec<CardValue>,
+  /// The sum of the card totals, leaving out missing prices
+  pub total: CardPrices,
+  /// The names of the cards without any price
+  pub missing: Vec<String>,
+}
+
+/// PriceList maps card names to their prices
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PriceList {
+  /// Prices keyed by lowercase card name
+  pub prices: HashMap<String, CardPrices>,
+}
+
+/// A card object of the Scryfall bulk data file, with only the fields needed for prices
+#[derive(Deserialize)]
+struct ScryfallPrices {
+  name: String,
+  #[serde(default)]
+  prices: CardPrices,
+}
+
+impl CardPrices {
+  /// Returns these prices multiplied by `count`
+  pub fn times(self, count: usize) -> Self {
+    let times = |price: Option<f64>| price.map(|price| price * count as f64);
+    Self {
+      usd: times(self.usd),
+      eur: times(self.eur),
+      tix: times(self.tix),
+    }
+  }
+
+  /// Returns the sum of these prices and `other`, where a missing price counts as zero
+  /// unless both are missing
+  pub fn plus(self, other: Self) -> Self {
+    let plus = |a: Option<f64>, b: Option<f64>| match (a, b) {
+      (None, None) => None,
+      (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+    };
+    Self {
+      usd: plus(self.usd, other.usd),
+      eur: plus(self.eur, other.eur),
+      tix: plus(self.tix, other.tix),
+    }
+  }
+
+  /// Returns the lowest of these prices and `other` in each currency
+  fn min(self, other: Self) -> Self {
+    let min = |a: Option<f64>, b: Option<f64>| match (a, b) {
+      (Some(a), Some(b)) => Some(f64::min(a, b)),
+      (a, b) => a.or(b),
+    };
+    Self {
+      usd: min(self.usd, other.usd),
+      eur: min(self.eur, other.eur),
+      tix: min(self.tix, other.tix),
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.usd.is_none() && self.eur.is_none() && self.tix.is_none()
+  }
+}
+
+impl PriceList {
+  /// Returns the prices of the Scryfall bulk data file contents `json`, an array of card
+  /// objects. Cards with several printings get the lowest price of each currency
+  pub fn from_scryfall_json(json: &str) -> Result<Self, serde_json::Error> {
+    let cards: Vec<ScryfallPrices> = serde_json::from_str(json)?;
+    let mut prices: HashMap<String, CardPrices> = HashMap::new();
+    for card in cards {
+      let entry = prices.entry(card.name.to_lowercase()).or_default();
+      *entry = if entry.is_empty() {
+        card.prices
+      } else {
+        entry.min(card.prices)
+      };
+    }
+    Ok(Self { prices })
+  }
+
+  /// Returns the prices of `json`, an object mapping card names to prices, e.g.
+  /// `{"Opt": {"usd": 0.1, "tix": 0.01}}`. Prices can be numbers or strings
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    let prices: HashMap<String, CardPrices> = serde_json::from_str(json)?;
+    Ok(Self {
+      prices: prices
+        .into_iter()
+        .map(|(name, prices)| (name.to_lowercase(), prices))
+        .collect(),
+    })
+  }
+
+  /// Returns the prices of the card named `name`, ignoring case
+  pub fn card_prices(&self, name: &str) -> Option<&CardPrices> {
+    self.prices.get(&name.to_lowercase())
+  }
+
+  /// Returns the value of `deck`, including its commanders
+  pub fn deck_value(&self, deck: &Deck) -> Value {
+    let counts = deck
+      .cards
+      .iter()
+      .map(|cc| (cc.card.name.as_str(), cc.count))
+      .chain(deck.commanders.iter().map(|card| (card.name.as_str(), 1)));
+    self.value(counts)
+  }
+
+  /// Returns the value of `collection`, where each copy of a card counts
+  pub fn collection_value(&self, collection: &Collection) -> Value {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for card in collection.iter().filter(|card| !card.is_face) {
+      *counts.entry(&card.name).or_insert(0) += 1;
+    }
+    self.value(counts.into_iter())
+  }
+
+  fn value<'a>(&self, counts: impl Iterator<Item = (&'a str, usize)>) -> Value {
+    let mut cards = Vec::new();
+    let mut missing = Vec::new();
+    let mut total = CardPrices::default();
+    for (name, count) in counts {
+      match self.card_prices(name).filter(|prices| !prices.is_empty()) {
+        Some(prices) => {
+          let card_total = prices.times(count);
+          total = total.plus(card_total);
+          cards.push(CardValue {
+            name: name.to_string(),
+            count,
+            prices: *prices,
+            total: card_total,
+          });
+        }
+        None => missing.push(name.to_string()),
+      }
+    }
+    cards.sort_by(|a, b| {
+      let usd = |card: &CardValue| card.total.usd.unwrap_or(0.0);
+      usd(b).total_cmp(&usd(a)).then_with(|| a.name.cmp(&b.name))
+    });
+    missing.sort();
+    Value {
+      cards,
+      total,
+      missing,
+    }
+  }
+}
+
+/// Deserializes a price from a number, a string like Scryfall's "0.25", or null. Strings
+/// that parse to a non-finite number, like "NaN" or "inf", are rejected
+fn price<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let price = match serde_json::Value::deserialize(deserializer)? {
+    serde_json::Value::Null => None,
+    serde_json::Value::Number(n) => n.as_f64(),
+    serde_json::Value::String(s) => Some(s.parse().map_err(serde::de::Error::custom)?),
+    other => {
+      return Err(serde::de::Error::custom(format!(
+        "expected a price, got {}",
+        other
+      )))
+    }
+  };
+  match price {
+    Some(price) if !f64::is_finite(price) => Err(serde::de::Error::custom(format!(
+      "expected a finite price, got {}",
+      price
+    ))),
+    price => Ok(price),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::collection::Collection;
+  use crate::prices::*;
+
+  fn assert_close(expected: f64, actual: Option<f64>) {
+    let actual = actual.expect("a price");
+    assert!(
+      f64::abs(expected - actual) < 0.0001,
+      "expected {}, got {}",
+      expected,
+      actual
+    );
+  }
+
+  #[test]
+  fn scryfall_prices_take_cheapest_printing() {
+    let prices = PriceList::from_scryfall_json(
+      r#"[
+        {"name": "Opt", "prices": {"usd": "0.25", "usd_foil": "1.00", "eur": null, "tix": "0.03"}},
+        {"name": "Opt", "prices": {"usd": "0.10", "eur": "0.20", "tix": null}},
+        {"name": "Island", "prices": {"usd": null, "eur": null, "tix": null}}
+      ]"#,
+    )
+    .unwrap();
+    let opt = prices.card_prices("opt").unwrap();
+    assert_close(0.10, opt.usd);
+    assert_close(0.20, opt.eur);
+    assert_close(0.03, opt.tix);
+    assert!(prices.card_prices("Island").unwrap().usd.is_none());
+  }
+
+  #[test]
+  fn deck_and_collection_value() {
+    let prices = PriceList::from_json(
+      r#"{
+        "Opt": {"usd": 0.1, "tix": "0.02"},
+        "Thoughtseize": {"usd": 12.5, "eur": 10}
+      }"#,
+    )
+    .unwrap();
+    let deck = decklist!(
+      "
+      4 Opt
+      2 Thoughtseize
+      20 Island
+      "
+    );
+    let value = prices.deck_value(&deck);
+    assert_eq!(value.cards[0].name, "Thoughtseize");
+    assert_close(25.0, value.cards[0].total.usd);
+    assert_close(25.4, value.total.usd);
+    assert_close(20.0, value.total.eur);
+    assert_close(0.08, value.total.tix);
+    assert_eq!(value.missing, vec!["Island".to_string()]);
+    let collection = Collection::from_cards(vec![
+      card!("Opt").clone(),
+      card!("Opt").clone(),
+      card!("Thoughtseize").clone(),
+    ]);
+    let value = prices.collection_value(&collection);
+    assert_close(12.7, value.total.usd);
+    assert!(PriceList::from_json(r#"{"Opt": {"usd": true}}"#).is_err());
+    assert!(PriceList::from_json(r#"{"Opt": {"usd": "NaN"}}"#).is_err());
+  }
+}
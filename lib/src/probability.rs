@@ -0,0 +1,252 @@
+//! # Exact card draw probabilities
+//!
+//! Analytic hypergeometric and multivariate hypergeometric distributions for simple
+//! questions, like "what is the probability to see 3 lands by turn 3", that do not
+//! need a Monte Carlo simulation. See `Deck::p_at_least` and `Deck::p_at_least_each`
+//! to ask these questions of a deck.
+use crate::hand::PlayOrder;
+
+/// The number of cards in an opening hand without a mulligan
+pub const OPENING_HAND_SIZE: usize = 7;
+
+/// Returns the number of ways to choose `k` items from `n` items
+pub fn choose(n: usize, k: usize) -> f64 {
+  if k > n {
+    return 0.0;
+  }
+  let k = std::cmp::min(k, n - k);
+  (0..k).fold(1.0, |accum, i| accum * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Returns the number of cards seen by `turn` (the opening hand plus draws) when
+/// playing in `play_order`, assuming no mulligan. Turn 0 counts as turn 1, like the
+/// turn of a card without mana cost
+pub fn cards_seen_by_turn(turn: usize, play_order: PlayOrder) -> usize {
+  OPENING_HAND_SIZE + play_order.draw_count(std::cmp::max(1, turn), &[])
+}
+
+/// Returns the probability to draw exactly `k` of the `successes` cards
+/// when drawing `draws` cards from a `population` of cards
+///
+/// See [Hypergeometric distribution](https://en.wikipedia.org/wiki/Hypergeometric_distribution)
+pub fn hypergeometric(population: usize, successes: usize, draws: usize, k: usize) -> f64 {
+  multivariate_hypergeometric(population, &[successes], &[k], draws)
+}
+
+/// Returns the probability to draw at least `k` of the `successes` cards
+/// when drawing `draws` cards from a `population` of cards
+pub fn hypergeometric_at_least(population: usize, successes: usize, draws: usize, k: usize) -> f64 {
+  multivariate_hypergeometric_at_least(population, &[successes], &[k], draws)
+}
+
+/// Returns the probability to draw exactly `group_draws[i]` cards from each group of
+/// `group_sizes[i]` cards when drawing `draws` cards from a `population` of cards.
+/// Groups must not overlap, and cards in no group make up the rest of the population
+///
+/// See [Multivariate hypergeometric distribution](https://en.wikipedia.org/wiki/Hypergeometric_distribution#Multivariate_hypergeometric_distribution)
+pub fn multivariate_hypergeometric(
+  population: usize,
+  group_sizes: &[usize],
+  group_draws: &[usize],
+  draws: usize,
+) -> f64 {
+  assert_eq!(group_sizes.len(), group_draws.len());
+  let grouped: usize = group_sizes.iter().sum();
+  let grouped_draws: usize = group_draws.iter().sum();
+  assert!(
+    grouped <= population,
+    "groups are larger than the population"
+  );
+  if draws > population || grouped_draws > draws {
+    return 0.0;
+  }
+  let ways = group_sizes
+    .iter()
+    .zip(group_draws)
+    .fold(1.0, |accum, (n, k)| accum * choose(*n, *k));
+  ways * choose(population - grouped, draws - grouped_draws) / choose(population, draws)
+}
+
+/// Returns the probability to draw at least `minimums[i]` cards from each group of
+/// `group_sizes[i]` cards when drawing `draws` cards from a `population` of cards.
+/// Groups must not overlap, and cards in no group make up the rest of the population
+pub fn multivariate_hypergeometric_at_least(
+  population: usize,
+  group_sizes: &[usize],
+  minimums: &[usize],
+  draws: usize,
+) -> f64 {
+  assert_eq!(group_sizes.len(), minimums.len());
+  let mut group_draws = minimums.to_vec();
+  let mut p = 0.0;
+  sum_at_least(population, group_sizes, &mut group_draws, 0, draws, &mut p);
+  p
+}
+
+/// Returns the probability that the numbers of cards drawn from each group of
+/// `group_sizes[i]` cards satisfy `predicate` when drawing `draws` cards from a `population`
+/// of cards. Groups must not overlap, and cards in no group make up the rest of the population
+pub fn multivariate_hypergeometric_where<P>(
+  population: usize,
+  group_sizes: &[usize],
+  draws: usize,
+  predicate: P,
+) -> f64
+where
+  P: Fn(&[usize]) -> bool,
+{
+  let mut group_draws = vec![0; group_sizes.len()];
+  let mut p = 0.0;
+  sum_where(
+    population,
+    group_sizes,
+    &mut group_draws,
+    0,
+    draws,
+    &predicate,
+    &mut p,
+  );
+  p
+}
+
+/// Adds the probability of every outcome that satisfies `predicate` to `p`,
+/// by enumerating the draws of group `group` and beyond
+fn sum_where<P>(
+  population: usize,
+  group_sizes: &[usize],
+  group_draws: &mut Vec<usize>,
+  group: usize,
+  draws: usize,
+  predicate: &P,
+  p: &mut f64,
+) where
+  P: Fn(&[usize]) -> bool,
+{
+  if group == group_sizes.len() {
+    if predicate(group_draws) {
+      *p += multivariate_hypergeometric(population, group_sizes, group_draws, draws);
+    }
+    return;
+  }
+  let drawn_elsewhere: usize = group_draws[..group].iter().sum();
+  let maximum = std::cmp::min(group_sizes[group], draws.saturating_sub(drawn_elsewhere));
+  for k in 0..=maximum {
+    group_draws[group] = k;
+    sum_where(
+      population,
+      group_sizes,
+      group_draws,
+      group + 1,
+      draws,
+      predicate,
+      p,
+    );
+  }
+  group_draws[group] = 0;
+}
+
+/// Adds the probability of every outcome with `group_draws[i] >= minimums[i]` to `p`,
+/// by enumerating the draws of group `group` and beyond
+fn sum_at_least(
+  population: usize,
+  group_sizes: &[usize],
+  group_draws: &mut Vec<usize>,
+  group: usize,
+  draws: usize,
+  p: &mut f64,
+) {
+  if group == group_sizes.len() {
+    *p += multivariate_hypergeometric(population, group_sizes, group_draws, draws);
+    return;
+  }
+  let minimum = group_draws[group];
+  let drawn_elsewhere: usize = group_draws[..group].iter().sum();
+  let maximum = std::cmp::min(group_sizes[group], draws.saturating_sub(drawn_elsewhere));
+  for k in minimum..=maximum {
+    group_draws[group] = k;
+    sum_at_least(population, group_sizes, group_draws, group + 1, draws, p);
+  }
+  group_draws[group] = minimum;
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::PlayOrder;
+  use crate::probability::*;
+
+  fn assert_close(expected: f64, actual: f64) {
+    let difference = f64::abs(expected - actual);
+    assert!(difference < 0.001, "expected {}, got {}", expected, actual);
+  }
+
+  #[test]
+  fn choose_small() {
+    assert_eq!(choose(5, 2), 10.0);
+    assert_eq!(choose(5, 0), 1.0);
+    assert_eq!(choose(5, 5), 1.0);
+    assert_eq!(choose(2, 5), 0.0);
+    assert_close(choose(60, 7), 386_206_920.0);
+  }
+
+  #[test]
+  fn hypergeometric_sums_to_one() {
+    let total: f64 = (0..=7).map(|k| hypergeometric(60, 24, 7, k)).sum();
+    assert_close(1.0, total);
+  }
+
+  #[test]
+  fn hypergeometric_at_least_one() {
+    // Hypergeometric, 60, 17, 7, 1, see simulation::tests::hypergeometric_0
+    assert_close(0.917, hypergeometric_at_least(60, 17, 7, 1));
+    assert_close(1.0, hypergeometric_at_least(60, 17, 7, 0));
+    assert_eq!(hypergeometric_at_least(60, 2, 7, 3), 0.0);
+  }
+
+  #[test]
+  fn multivariate_hypergeometric_history_of_benalia() {
+    // 17 Plains, 9 Swamp, see simulation::tests::multi_hypergeometric_0
+    let seen = cards_seen_by_turn(3, PlayOrder::First);
+    let p = multivariate_hypergeometric_at_least(60, &[17, 9], &[2, 1], seen)
+      + hypergeometric_at_least(60, 17, seen, 3)
+      - multivariate_hypergeometric_at_least(60, &[17, 9], &[3, 1], seen);
+    assert_close(0.746, p);
+  }
+
+  #[test]
+  fn multivariate_hypergeometric_jadelight_ranger() {
+    // 16 Forest, 8 Swamp, see simulation::tests::multi_hypergeometric_1
+    let seen = cards_seen_by_turn(3, PlayOrder::First);
+    let p = multivariate_hypergeometric_at_least(60, &[16, 8], &[2, 1], seen)
+      + hypergeometric_at_least(60, 16, seen, 3)
+      - multivariate_hypergeometric_at_least(60, &[16, 8], &[3, 1], seen);
+    assert_close(0.692, p);
+  }
+
+  #[test]
+  fn multivariate_hypergeometric_where_matches_at_least() {
+    let seen = cards_seen_by_turn(3, PlayOrder::First);
+    let p =
+      multivariate_hypergeometric_where(60, &[17, 9], seen, |draws| draws[0] >= 2 && draws[1] >= 1);
+    assert_close(
+      multivariate_hypergeometric_at_least(60, &[17, 9], &[2, 1], seen),
+      p,
+    );
+    assert_close(
+      1.0,
+      multivariate_hypergeometric_where(40, &[17, 4], 7, |_| true),
+    );
+  }
+
+  #[test]
+  fn cards_seen() {
+    assert_eq!(cards_seen_by_turn(1, PlayOrder::First), 7);
+    assert_eq!(cards_seen_by_turn(1, PlayOrder::Second), 8);
+    assert_eq!(cards_seen_by_turn(5, PlayOrder::First), 11);
+  }
+
+  #[test]
+  fn cards_seen_turn_zero() {
+    assert_eq!(cards_seen_by_turn(0, PlayOrder::First), 7);
+    assert_eq!(cards_seen_by_turn(0, PlayOrder::Second), 8);
+  }
+}
@@ -0,0 +1,220 @@
+//! # Versioned simulation results
+//!
+//! The wire format of simulation results shared by the `wasm`, `ffi` and command line
+//! consumers. Unlike the internal types like `Observations`, these types only change
+//! along with `SIMULATION_RESULT_VERSION`, so consumers can check `schema_version` and
+//! rely on the field names and meanings documented here across releases.
+use crate::castability::castability_report;
+use crate::deck::Deck;
+use crate::simulation::Simulation;
+use crate::verification::verify;
+use std::ops::RangeInclusive;
+
+/// The version of the `SimulationResult` format. Renaming or removing a field, or changing
+/// its meaning, bumps the version, while adding a field does not
+pub const SIMULATION_RESULT_VERSION: u32 = 1;
+
+/// The turns of `TempoResult`, the early turns where stumbles are quoted
+const TEMPO_TURNS: RangeInclusive<usize> = 2..=5;
+
+/// SimulationResult is the result of a simulation, version 1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SimulationResult {
+  /// See `SIMULATION_RESULT_VERSION`
+  pub schema_version: u32,
+  /// The seed of the simulation, which reproduces it
+  pub seed: u64,
+  /// The number of simulated games
+  pub run_count: usize,
+  /// True if the games were simulated on the play
+  pub on_the_play: bool,
+  /// The nonland cards of the deck, in deck order
+  pub cards: Vec<CardResult>,
+  pub mulligans: MulliganResult,
+  /// True if the simulation agrees with the exact probabilities of its cards, false if it
+  /// does not, and null for decks where exact computation isn't feasible, see
+  /// `verification::verify`
+  #[serde(default)]
+  pub verified: Option<bool>,
+  /// Missed land drops and stumbles on turns 2 through 5
+  #[serde(default)]
+  pub tempo: TempoResult,
+}
+
+/// CardResult is the castability of a single nonland card in a `SimulationResult`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CardResult {
+  pub name: String,
+  /// The number of copies in the deck
+  pub copies: usize,
+  /// The turn on which the card is on curve
+  pub turn: usize,
+  /// The probability to have the mana for the card on curve
+  pub p_mana: f64,
+  /// The probability to have the mana for the card on curve, given enough lands
+  pub p_mana_given_cmc: f64,
+  /// The probability to have both the card and the mana for it on curve
+  pub p_play: f64,
+  /// The probability to have the mana for the card the turn after it is on curve
+  pub p_mana_next_turn: f64,
+  /// The color that most often keeps the card from being cast on curve,
+  /// one of "White", "Blue", "Black", "Red" or "Green", if any
+  pub blocking_color: Option<String>,
+}
+
+/// MulliganResult summarizes the hands kept and mulliganed in a `SimulationResult`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct MulliganResult {
+  /// The number of games that kept a hand of each size, indexed by hand size
+  pub kept_hand_sizes: Vec<usize>,
+  /// The number of hands mulliganed away across all games
+  pub mulligans: usize,
+  /// Mulliganed hands with fewer lands than the average hand of the same size
+  pub too_few_lands: usize,
+  /// Mulliganed hands with more lands than the average hand of the same size
+  pub too_many_lands: usize,
+  /// The average number of lands in the kept hands
+  pub average_kept_land_count: f64,
+}
+
+/// TempoResult summarizes the missed land drops and the turns without anything to cast
+/// in a `SimulationResult`, see `Simulation::tempo`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TempoResult {
+  /// The turns reached by the simulated draws
+  pub turns: Vec<TurnTempoResult>,
+  /// The probability to miss at least one land drop
+  pub p_missed_land_drop: f64,
+  /// The probability to cast nothing on at least one turn, the "stumble rate"
+  pub p_stumble: f64,
+}
+
+/// TurnTempoResult is a single turn of a `TempoResult`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TurnTempoResult {
+  pub turn: usize,
+  /// The probability to have no land to play on the turn
+  pub p_missed_land_drop: f64,
+  /// The probability to cast nothing on the turn
+  pub p_stumble: f64,
+  /// The average mana left unspent on the turn
+  pub average_mana_wasted: f64,
+}
+
+impl SimulationResult {
+  /// Returns the result of `sim`, which should be a simulation of `deck`
+  pub fn new(sim: &Simulation, deck: &Deck) -> Self {
+    let cards = castability_report(sim, deck)
+      .into_iter()
+      .map(|card| CardResult {
+        copies: deck
+          .card_count_from_name(&card.name)
+          .map_or(0, |cc| cc.count),
+        turn: card.turn,
+        p_mana: card.on_curve.p_mana(),
+        p_mana_given_cmc: card.on_curve.p_mana_given_cmc(),
+        p_play: card.on_curve.p_play(),
+        p_mana_next_turn: card.on_curve_plus_one.p_mana(),
+        blocking_color: card.blocking_color.map(|color| format!("{:?}", color)),
+        name: card.name,
+      })
+      .collect();
+    let stats = sim.mulligan_stats();
+    let tempo = sim.tempo(TEMPO_TURNS);
+    Self {
+      schema_version: SIMULATION_RESULT_VERSION,
+      seed: sim.seed,
+      run_count: sim.hands.len(),
+      on_the_play: sim.on_the_play,
+      cards,
+      mulligans: MulliganResult {
+        kept_hand_sizes: stats.kept_hand_sizes,
+        mulligans: stats.mulligans,
+        too_few_lands: stats.too_few_lands,
+        too_many_lands: stats.too_many_lands,
+        average_kept_land_count: stats.average_kept_land_count,
+      },
+      verified: verify(sim, deck).map(|verification| verification.verified),
+      tempo: TempoResult {
+        turns: tempo
+          .turns
+          .iter()
+          .filter(|turn| turn.total_runs > 0)
+          .map(|turn| TurnTempoResult {
+            turn: turn.turn,
+            p_missed_land_drop: turn.p_missed_land_drop(),
+            p_stumble: turn.p_stumble(),
+            average_mana_wasted: turn.average_mana_wasted(),
+          })
+          .collect(),
+        p_missed_land_drop: tempo.runs_missing_a_land_drop as f64 / tempo.total_runs as f64,
+        p_stumble: tempo.runs_stumbling as f64 / tempo.total_runs as f64,
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::London;
+  use crate::result::*;
+  use crate::simulation::*;
+
+  #[test]
+  fn simulation_result_v1_field_names() {
+    let deck = decklist!(
+      "
+      4 Opt
+      3 Vraska's Contempt
+      17 Island
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 5,
+      mulligan: &London::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(4),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let result = SimulationResult::new(&sim, &deck);
+    assert_eq!(result.cards.len(), 2);
+    assert_eq!(result.cards[0].copies, 4);
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["seed"], 4);
+    assert_eq!(json["run_count"], 100);
+    assert_eq!(json["on_the_play"], true);
+    let card = &json["cards"][1];
+    assert_eq!(card["name"], "Vraska's Contempt");
+    assert_eq!(card["copies"], 3);
+    assert_eq!(card["turn"], 4);
+    for field in &["p_mana", "p_mana_given_cmc", "p_play", "p_mana_next_turn"] {
+      assert!(card[field].is_number(), "{}", field);
+    }
+    assert_eq!(card["blocking_color"], "Black");
+    // Vraska's Contempt is never castable with Islands, exactly as computed
+    assert_eq!(json["verified"], true);
+    let mulligans = &json["mulligans"];
+    assert_eq!(mulligans["mulligans"], 0);
+    assert_eq!(mulligans["kept_hand_sizes"][7], 100);
+    for field in &["too_few_lands", "too_many_lands", "average_kept_land_count"] {
+      assert!(mulligans[field].is_number(), "{}", field);
+    }
+    let tempo = &json["tempo"];
+    assert_eq!(tempo["turns"].as_array().unwrap().len(), 4);
+    assert_eq!(tempo["turns"][0]["turn"], 2);
+    for field in &["p_missed_land_drop", "p_stumble", "average_mana_wasted"] {
+      assert!(tempo["turns"][3][field].is_number(), "{}", field);
+    }
+    assert!(tempo["p_stumble"].is_number());
+  }
+}
@@ -0,0 +1,116 @@
+//! # JSON simulation runs
+//!
+//! Runs a simulation from a deck list and a JSON configuration, shared by the
+//! `wasm` and `ffi` bindings and by manifests.
+use crate::card::{CardVersion, GameFormat};
+use crate::deck::Deck;
+use crate::hand::{CantripPolicy, ExtraDraw};
+use crate::mulligan::London;
+use crate::result::SimulationResult;
+use crate::simulation::{Simulation, SimulationConfig};
+
+/// RunConfig is the JSON configuration of `run_simulation`, see `SimulationConfig`
+#[derive(Debug, Deserialize)]
+pub(crate) struct RunConfig {
+  run_count: usize,
+  draw_count: usize,
+  #[serde(default)]
+  on_the_play: bool,
+  /// Defaults to never mulliganing
+  #[serde(default)]
+  mulligan: Option<London>,
+  #[serde(default)]
+  extra_draws: Vec<ExtraDraw>,
+  #[serde(default)]
+  seed: Option<u64>,
+  /// Defaults to ignoring cantrips. Otherwise the well known cantrips of the deck are
+  /// cast, see `Cantrip::known`
+  #[serde(default)]
+  cantrip_policy: CantripPolicy,
+  /// The version of the cards with an Arena Alchemy rebalanced version to simulate.
+  /// Defaults to the cards of the deck list as written
+  #[serde(default)]
+  card_version: Option<CardVersion>,
+  /// The format of the deck, e.g. "StandardBrawl" for a deck list with a "Commander"
+  /// section. The deck is validated against the construction rules of the format
+  #[serde(default)]
+  format: Option<GameFormat>,
+}
+
+/// Returns the result of simulating the deck list `deck_str` with the JSON `config_json`,
+/// or an error message
+pub(crate) fn run_simulation(
+  deck_str: &str,
+  config_json: &str,
+) -> Result<SimulationResult, String> {
+  let mut deck = Deck::from_list(deck_str).map_err(|e| format!("Error parsing deck: {}", e.0))?;
+  if deck.is_empty() {
+    return Err("Error parsing deck: the deck is empty".to_string());
+  }
+  let config: RunConfig = serde_json::from_str(config_json)
+    .map_err(|e| format!("Error deserializing simulation config: {}", e))?;
+  if config.run_count == 0 {
+    return Err("Error in simulation config: run_count must be positive".to_string());
+  }
+  if let Some(format) = config.format {
+    deck = deck.with_format(format);
+    deck
+      .validate()
+      .map_err(|e| format!("Error validating deck: {}", e.0))?;
+  }
+  if let Some(version) = config.card_version {
+    deck = deck.with_card_version(version);
+  }
+  if config.cantrip_policy != CantripPolicy::Off {
+    deck.add_known_cantrips();
+  }
+  let mulligan = config.mulligan.unwrap_or_else(London::never);
+  let sim = Simulation::from_config(&SimulationConfig {
+    on_the_play: config.on_the_play,
+    extra_draws: &config.extra_draws,
+    seed: config.seed,
+    cantrip_policy: config.cantrip_policy,
+    ..SimulationConfig::new(&deck, &mulligan, config.run_count, config.draw_count)
+  });
+  Ok(SimulationResult::new(&sim, &deck))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::run::*;
+
+  #[test]
+  fn run_simulation_from_json() {
+    let output = run_simulation(
+      "20 Island\n40 Opt",
+      r#"{"run_count": 100, "draw_count": 2, "on_the_play": true, "seed": 3}"#,
+    )
+    .unwrap();
+    assert_eq!(output.seed, 3);
+    assert_eq!(output.cards.len(), 1);
+    assert_eq!(output.cards[0].name, "Opt");
+    assert_eq!(output.run_count, 100);
+  }
+
+  #[test]
+  fn run_simulation_with_rebalanced_cards() {
+    let output = run_simulation(
+      "20 Island\n40 Alrund's Epiphany",
+      r#"{"run_count": 10, "draw_count": 6, "card_version": "Rebalanced"}"#,
+    )
+    .unwrap();
+    assert_eq!(output.cards[0].name, "A-Alrund's Epiphany");
+  }
+
+  #[test]
+  fn run_simulation_errors() {
+    assert!(run_simulation("20 Not A Card", r#"{"run_count": 1, "draw_count": 0}"#).is_err());
+    assert!(run_simulation("20 Island", "{}").is_err());
+    assert!(run_simulation("20 Island", r#"{"run_count": 0, "draw_count": 0}"#).is_err());
+    assert!(run_simulation(
+      "Commander\n1 Omnath, Locus of Mana\n\nDeck\n59 Forest",
+      r#"{"run_count": 1, "draw_count": 0, "format": "Brawl"}"#
+    )
+    .is_err());
+  }
+}
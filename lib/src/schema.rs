@@ -0,0 +1,55 @@
+//! # JSON Schemas
+//!
+//! JSON Schemas of the serialized types, so that consumers of the wasm and server
+//! outputs in other languages can validate them and generate their bindings.
+//! Requires the `schemars` feature.
+use crate::collection::Collection;
+use crate::deck::Deck;
+use crate::result::SimulationResult;
+use crate::simulation::SimulationReport;
+use schemars::schema::RootSchema;
+use std::collections::BTreeMap;
+
+/// Returns the JSON Schema of `Deck`
+pub fn deck_schema() -> RootSchema {
+  schema_for!(Deck)
+}
+
+/// Returns the JSON Schema of `Collection`
+pub fn collection_schema() -> RootSchema {
+  schema_for!(Collection)
+}
+
+/// Returns the JSON Schema of `SimulationReport`
+pub fn simulation_report_schema() -> RootSchema {
+  schema_for!(SimulationReport)
+}
+
+/// Returns the JSON Schema of `SimulationResult`
+pub fn simulation_result_schema() -> RootSchema {
+  schema_for!(SimulationResult)
+}
+
+/// Returns the JSON Schemas of the serialized types, keyed by type name
+pub fn schemas() -> BTreeMap<&'static str, RootSchema> {
+  let mut schemas = BTreeMap::new();
+  schemas.insert("Deck", deck_schema());
+  schemas.insert("Collection", collection_schema());
+  schemas.insert("SimulationReport", simulation_report_schema());
+  schemas.insert("SimulationResult", simulation_result_schema());
+  schemas
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::schema::*;
+
+  #[test]
+  fn schemas_serialize() {
+    let schemas = schemas();
+    assert_eq!(schemas.len(), 4);
+    let json = serde_json::to_string(&schemas).unwrap();
+    assert!(json.contains("\"DeckCard\""));
+    assert!(json.contains("\"Observations\""));
+  }
+}
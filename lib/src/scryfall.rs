@@ -37,6 +37,8 @@ pub struct ScryfallCard {
     #[serde(default)]
     pub set_type: String,
     #[serde(default)]
+    pub layout: String,
+    #[serde(default)]
     pub rarity: Rarity,
     pub object: Object,
     #[serde(with = "scryfall_date_format")]
@@ -72,12 +74,16 @@ pub enum Object {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename = "lowercase")]
 pub enum GameFormat {
     Future,
     Pioneer,
     Vintage,
+    /// Historic Brawl, the 100 card Brawl format on Arena
     Brawl,
+    /// Standard Brawl, the 60 card Brawl format with Standard legal cards
+    StandardBrawl,
     Historic,
     Pauper,
     Penny,
@@ -92,6 +98,7 @@ pub enum GameFormat {
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialOrd, PartialEq, Eq, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Rarity {
     Common,
@@ -106,6 +113,7 @@ pub enum Rarity {
 /// See [https://mtg.gamepedia.com/Template:List_of_Magic_sets](https://mtg.gamepedia.com/Template:List_of_Magic_sets)
 /// This listing only covers core and expansion sets from ~2015
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialOrd, PartialEq, Eq, Ord, Hash)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum SetCode {
     // DAR is MTGAA's code for DOM
@@ -193,6 +201,72 @@ impl SetCode {
             _ => false,
         }
     }
+
+    /// Returns true if the set leaves Standard at the next rotation
+    pub fn rotates_next(&self) -> bool {
+        matches!(self, Self::GRN | Self::RNA | Self::WAR | Self::M20)
+    }
+
+    /// Returns true if the set is in Standard after the next rotation
+    pub fn in_standard_after_rotation(&self) -> bool {
+        self.in_standard() && !self.rotates_next()
+    }
+
+    /// Returns the full English name of the set, or an empty string for unknown sets
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::IKO => "Ikoria: Lair of Behemoths",
+            Self::DAR | Self::DOM => "Dominaria",
+            Self::ORI => "Magic Origins",
+            Self::BFZ => "Battle for Zendikar",
+            Self::OGW => "Oath of the Gatewatch",
+            Self::SOI => "Shadows over Innistrad",
+            Self::EMN => "Eldritch Moon",
+            Self::KLD => "Kaladesh",
+            Self::AER => "Aether Revolt",
+            Self::AKH => "Amonkhet",
+            Self::HOU => "Hour of Devastation",
+            Self::XLN => "Ixalan",
+            Self::RIX => "Rivals of Ixalan",
+            Self::M19 => "Core Set 2019",
+            Self::GRN => "Guilds of Ravnica",
+            Self::RNA => "Ravnica Allegiance",
+            Self::WAR => "War of the Spark",
+            Self::M20 => "Core Set 2020",
+            Self::ELD => "Throne of Eldraine",
+            Self::THB => "Theros Beyond Death",
+            Self::M21 => "Core Set 2021",
+            Self::Unknown => "",
+        }
+    }
+}
+
+impl GameFormat {
+    /// Returns the maximum number of copies of a nonbasic card allowed in a deck of the format
+    pub fn max_copies(&self) -> usize {
+        match self {
+            GameFormat::Brawl
+            | GameFormat::StandardBrawl
+            | GameFormat::Commander
+            | GameFormat::Duel => 1,
+            _ => 4,
+        }
+    }
+
+    /// Returns the number of cards in a deck of the format, including the commanders,
+    /// or None if the format only has a minimum deck size
+    pub fn deck_size(&self) -> Option<usize> {
+        match self {
+            GameFormat::Brawl | GameFormat::Commander => Some(100),
+            GameFormat::StandardBrawl => Some(60),
+            _ => None,
+        }
+    }
+
+    /// Returns true if decks of the format are led by a commander
+    pub fn has_commander(&self) -> bool {
+        self.deck_size().is_some()
+    }
 }
 
 impl Default for SetCode {
@@ -506,6 +580,49 @@ lazy_static! {
     .collect();
 }
 
+/// The Scryfall legality keys of the formats played on Arena
+const ARENA_FORMATS: &[&str] = &[
+    "standard",
+    "alchemy",
+    "historic",
+    "timeless",
+    "explorer",
+    "brawl",
+    "standardbrawl",
+];
+
+impl ScryfallCard {
+    /// Returns true if the card can be played on Arena. Oracle cards are a single printing
+    /// of each card, which may not be the Arena printing, so Arena format legalities count too
+    pub fn is_on_arena(&self) -> bool {
+        self.arena_id != 0
+            || ARENA_FORMATS.iter().any(|format| {
+                self.legalities
+                    .get(*format)
+                    .is_some_and(|legality| legality != &Legality::NotLegal)
+            })
+    }
+
+    /// Returns the class of the card from its layout, type line, and legalities
+    pub fn class(&self) -> CardClass {
+        match self.layout.as_str() {
+            "token" | "double_faced_token" => return CardClass::Token,
+            "emblem" => return CardClass::Emblem,
+            "art_series" => return CardClass::ArtCard,
+            _ => {}
+        }
+        if self.type_line.starts_with("Emblem") {
+            CardClass::Emblem
+        } else if self.type_line.contains("Token") || self.set_type == "token" {
+            CardClass::Token
+        } else if self.legalities.values().all(|l| l == &Legality::NotLegal) {
+            CardClass::NonCollectible
+        } else {
+            CardClass::Playable
+        }
+    }
+}
+
 impl Into<Card> for ScryfallCard {
     fn into(self) -> Card {
         let kind;
@@ -559,7 +676,12 @@ impl Into<Card> for ScryfallCard {
             }
             all_mana_costs = vec![mana_cost];
         } else {
-            kind = CardKind::Unknown;
+            // Conditional lands like Cavern of Souls only pay colored mana for creatures
+            kind = if self.type_line.contains("Creature") {
+                CardKind::Creature
+            } else {
+                CardKind::Unknown
+            };
             all_mana_costs = mana_costs_from_str(&self.mana_cost).into_iter().collect();
             mana_cost = ManaCost::from_rgbuwc(
                 all_mana_costs[0].r,
@@ -0,0 +1,167 @@
+//! # Incremental simulation sessions
+//!
+//! Interactive deck building swaps a card or two at a time, and simulating the whole deck
+//! again for each edit is wasteful. A `SimulationSession` keeps the shuffled library order
+//! of every run, so swapping a card only rebuilds the runs that drew the swapped copy. The
+//! other runs keep their hands, which also makes the results before and after an edit a
+//! paired comparison.
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::{CantripPolicy, Hand};
+use crate::mulligan::Never;
+use crate::simulation::{run_rng, Simulation, SimulationConfig};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+/// SimulationSession is a simulation that can be updated card by card, see `swap`
+pub struct SimulationSession {
+  deck: Deck,
+  /// The cards of the library, one per copy, indexed by slot
+  slots: Vec<Card>,
+  /// The slots each run drew, opening hand first
+  orders: Vec<Vec<usize>>,
+  starting_hand_size: usize,
+  simulation: Simulation,
+}
+
+impl SimulationSession {
+  /// Returns a session simulating `config`, which draws the same hands as
+  /// `Simulation::from_config` with the same seed. Sessions keep every opening hand, and
+  /// Arena hand smoothing and cantrips are not supported
+  pub fn new(config: &SimulationConfig<Never>) -> Self {
+    assert!(config.run_count > 0);
+    assert!(
+      !config.mulligan.hand_smoothing,
+      "hand smoothing is not supported by simulation sessions"
+    );
+    assert!(
+      config.cantrip_policy == CantripPolicy::Off,
+      "cantrips are not supported by simulation sessions"
+    );
+    let seed = config
+      .seed
+      .unwrap_or_else(|| SmallRng::from_entropy().gen());
+    let slots: Vec<Card> = config.deck.flatten().into_iter().cloned().collect();
+    let window = std::cmp::min(
+      slots.len(),
+      config.mulligan.starting_hand_size + Simulation::simulated_draw_count(config),
+    );
+    let orders: Vec<Vec<usize>> = (0..config.run_count)
+      .map(|run| {
+        let mut rng = run_rng(seed, run);
+        let mut index_range: Vec<usize> = (0..slots.len()).collect();
+        index_range.partial_shuffle(&mut rng, window).0.to_vec()
+      })
+      .collect();
+    let starting_hand_size = std::cmp::min(slots.len(), config.mulligan.starting_hand_size);
+    let hands = orders
+      .iter()
+      .map(|order| (hand(&slots, order, starting_hand_size), None))
+      .collect();
+    Self {
+      deck: config.deck.clone(),
+      slots,
+      orders,
+      starting_hand_size,
+      simulation: Simulation::from_hands(config, seed, hands),
+    }
+  }
+
+  /// Returns the current simulation
+  pub fn simulation(&self) -> &Simulation {
+    &self.simulation
+  }
+
+  /// Returns the current deck
+  pub fn deck(&self) -> &Deck {
+    &self.deck
+  }
+
+  /// Replaces one copy of `card_out` with `card_in`, in the same library position of every
+  /// run, and returns the number of runs that drew the replaced copy and were rebuilt
+  pub fn swap(&mut self, card_out: &Card, card_in: &Card) -> Result<usize, String> {
+    let slot = self
+      .slots
+      .iter()
+      .rposition(|card| card.hash == card_out.hash)
+      .ok_or_else(|| format!("{} is not in the deck", card_out.name))?;
+    self.slots[slot] = card_in.clone();
+    self.deck.remove_count(card_out, 1);
+    self.deck.insert_count(card_in, 1);
+    let mut hands = std::mem::take(&mut self.simulation.hands);
+    let mut rebuilt = 0;
+    for (order, hand_of_run) in self.orders.iter().zip(hands.iter_mut()) {
+      if order.contains(&slot) {
+        *hand_of_run = hand(&self.slots, order, self.starting_hand_size);
+        rebuilt += 1;
+      }
+    }
+    // Rebuild the simulation for the statistics that depend on the deck
+    let extra_draws = self.simulation.extra_draws.clone();
+    let mulligan = Never::never();
+    let config = SimulationConfig {
+      on_the_play: self.simulation.on_the_play,
+      extra_draws: &extra_draws,
+      seed: Some(self.simulation.seed),
+      ..SimulationConfig::new(&self.deck, &mulligan, hands.len(), 0)
+    };
+    let runs = hands.into_iter().map(|hand| (hand, None)).collect();
+    self.simulation = Simulation::from_hands(&config, self.simulation.seed, runs);
+    Ok(rebuilt)
+  }
+}
+
+/// Returns the hand that drew the library `slots` in `order`
+fn hand(slots: &[Card], order: &[usize], starting_hand_size: usize) -> Hand {
+  let cards: Vec<&Card> = order.iter().map(|slot| &slots[*slot]).collect();
+  Hand::from_opening_and_draws(&cards[..starting_hand_size], &cards[starting_hand_size..])
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::mulligan::Never;
+  use crate::session::*;
+
+  #[test]
+  fn swap_rebuilds_only_affected_runs() {
+    let deck = decklist!(
+      "
+      18 Island
+      6 Swamp
+      30 Opt
+      6 Vraska's Contempt
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 2000,
+      draw_count: 4,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(9),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let mut session = SimulationSession::new(&config);
+    // The session draws the same hands as a simulation with the same seed
+    let sim = Simulation::from_config(&config);
+    let contempt = card!("Vraska's Contempt");
+    assert_eq!(
+      session.simulation().observations_for_card(contempt).mana,
+      sim.observations_for_card(contempt).mana
+    );
+    let before = session.simulation().observations_for_card(contempt);
+    let rebuilt = session.swap(card!("Island"), card!("Swamp")).unwrap();
+    // 11 of 60 cards are seen by turn 5 on the play
+    assert!(rebuilt > 0 && rebuilt < 2000 / 2);
+    assert_eq!(session.deck().len(), 60);
+    assert_eq!(
+      session.deck().card_count_from_name("Swamp").unwrap().count,
+      7
+    );
+    let after = session.simulation().observations_for_card(contempt);
+    assert!(after.mana > before.mana);
+    assert_eq!(after.total_runs, 2000);
+    assert!(session.swap(card!("Forest"), card!("Swamp")).is_err());
+  }
+}
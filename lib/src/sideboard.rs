@@ -0,0 +1,234 @@
+//! # Sideboard planning
+//!
+//! Checks whether a sideboard can hold the cards brought in by the plan of every expected
+//! matchup, and suggests the sideboard that covers the most important matchups when it
+//! can't. Matchups are weighted by how often they are expected, e.g. their metagame share.
+use crate::card::Card;
+use crate::deck::{Deck, DeckBuilder};
+
+/// The number of cards in a sideboard
+pub const SIDEBOARD_SIZE: usize = 15;
+
+/// The most matchups for which every combination of covered plans is tried, past which
+/// plans are covered greedily by weight
+const MAX_EXACT_MATCHUPS: usize = 16;
+
+/// MatchupPlan is the sideboard plan against a single matchup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupPlan {
+  pub name: String,
+  /// How often the matchup is expected, on any positive scale
+  pub weight: f64,
+  /// The cards brought in from the sideboard for the matchup
+  pub cards_in: Deck,
+}
+
+/// SideboardSuggestion is a sideboard suggested for a set of plans, see `suggest_sideboard`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideboardSuggestion {
+  pub sideboard: Deck,
+  /// The names of the matchups whose plans are fully covered, in plan order
+  pub covered: Vec<String>,
+  /// The fraction of the cards brought in that the sideboard holds, averaged over the
+  /// matchups by weight. 1.0 when every plan is covered
+  pub coverage: f64,
+}
+
+/// Returns the size of the smallest sideboard that covers every plan, i.e. the most copies
+/// of each card brought in by a single plan, summed over the cards
+pub fn required_sideboard_size(plans: &[MatchupPlan]) -> usize {
+  let (_, needs) = card_needs(plans);
+  needs
+    .iter()
+    .map(|need| need.iter().copied().max().unwrap_or(0))
+    .sum()
+}
+
+/// Returns the sideboard of at most `size` cards that fully covers the plans with the most
+/// total weight, with any remaining slots filled with the cards that cover the most of the
+/// other plans
+pub fn suggest_sideboard(plans: &[MatchupPlan], size: usize) -> SideboardSuggestion {
+  let (cards, needs) = card_needs(plans);
+  let covered_plans = if plans.len() <= MAX_EXACT_MATCHUPS {
+    best_covered_plans(plans, &needs, size)
+  } else {
+    greedy_covered_plans(plans, &needs, size)
+  };
+  let mut counts = vec![0; cards.len()];
+  for (count, need) in counts.iter_mut().zip(&needs) {
+    *count = covered_plans.iter().map(|i| need[*i]).max().unwrap_or(0);
+  }
+  // Fill the remaining slots one copy at a time, with the copy that covers the most weight
+  let plan_sizes: Vec<usize> = plans.iter().map(|plan| plan.cards_in.len()).collect();
+  while counts.iter().sum::<usize>() < size {
+    let best = counts
+      .iter()
+      .zip(&needs)
+      .enumerate()
+      .map(|(i, (count, need))| {
+        let value: f64 = (0..plans.len())
+          .filter(|m| need[*m] > *count)
+          .map(|m| plans[m].weight / plan_sizes[m] as f64)
+          .sum();
+        (i, value)
+      })
+      .filter(|(_, value)| *value > 0.0)
+      .fold(None, |best: Option<(usize, f64)>, (i, value)| match best {
+        Some((_, best_value)) if best_value >= value => best,
+        _ => Some((i, value)),
+      });
+    match best {
+      Some((i, _)) => counts[i] += 1,
+      None => break,
+    }
+  }
+  let mut builder = DeckBuilder::new();
+  for (card, count) in cards.iter().zip(&counts) {
+    if *count > 0 {
+      builder = builder.insert_count((*card).clone(), *count);
+    }
+  }
+  let covers = |m: usize| {
+    needs
+      .iter()
+      .zip(&counts)
+      .all(|(need, count)| need[m] <= *count)
+  };
+  let total_weight: f64 = plans.iter().map(|plan| plan.weight).sum();
+  let covered_weight: f64 = plans
+    .iter()
+    .enumerate()
+    .map(|(m, plan)| {
+      if plan_sizes[m] == 0 {
+        return plan.weight;
+      }
+      let held: usize = needs
+        .iter()
+        .zip(&counts)
+        .map(|(need, count)| std::cmp::min(need[m], *count))
+        .sum();
+      plan.weight * held as f64 / plan_sizes[m] as f64
+    })
+    .sum();
+  SideboardSuggestion {
+    sideboard: builder.build(),
+    covered: (0..plans.len())
+      .filter(|m| covers(*m))
+      .map(|m| plans[m].name.clone())
+      .collect(),
+    coverage: if total_weight > 0.0 {
+      covered_weight / total_weight
+    } else {
+      1.0
+    },
+  }
+}
+
+/// Returns the distinct cards brought in by `plans`, in order of first appearance, and the
+/// number of copies of each card brought in by each plan
+fn card_needs(plans: &[MatchupPlan]) -> (Vec<&Card>, Vec<Vec<usize>>) {
+  let mut cards: Vec<&Card> = Vec::new();
+  let mut needs: Vec<Vec<usize>> = Vec::new();
+  for (m, plan) in plans.iter().enumerate() {
+    for cc in &plan.cards_in.cards {
+      let i = match cards.iter().position(|card| card.hash == cc.card.hash) {
+        Some(i) => i,
+        None => {
+          cards.push(&cc.card);
+          needs.push(vec![0; plans.len()]);
+          cards.len() - 1
+        }
+      };
+      needs[i][m] += cc.count;
+    }
+  }
+  (cards, needs)
+}
+
+/// Returns the number of sideboard cards needed to cover the plans at `covered`
+fn covered_size(needs: &[Vec<usize>], covered: &[usize]) -> usize {
+  needs
+    .iter()
+    .map(|need| covered.iter().map(|m| need[*m]).max().unwrap_or(0))
+    .sum()
+}
+
+/// Returns the plans, by index, with the most total weight that fit in `size` cards,
+/// preferring fewer cards among equal weights
+fn best_covered_plans(plans: &[MatchupPlan], needs: &[Vec<usize>], size: usize) -> Vec<usize> {
+  let mut best = (Vec::new(), 0.0, 0);
+  for subset in 1..(1usize << plans.len()) {
+    let covered: Vec<usize> = (0..plans.len())
+      .filter(|m| subset & (1 << m) != 0)
+      .collect();
+    let cards = covered_size(needs, &covered);
+    if cards > size {
+      continue;
+    }
+    let weight: f64 = covered.iter().map(|m| plans[*m].weight).sum();
+    if weight > best.1 || (weight == best.1 && cards < best.2) {
+      best = (covered, weight, cards);
+    }
+  }
+  best.0
+}
+
+/// Returns the plans, by index, covered by adding each plan that still fits in `size`
+/// cards in order of decreasing weight
+fn greedy_covered_plans(plans: &[MatchupPlan], needs: &[Vec<usize>], size: usize) -> Vec<usize> {
+  let mut order: Vec<usize> = (0..plans.len()).collect();
+  order.sort_by(|a, b| plans[*b].weight.partial_cmp(&plans[*a].weight).unwrap());
+  let mut covered = Vec::new();
+  for m in order {
+    covered.push(m);
+    if covered_size(needs, &covered) > size {
+      covered.pop();
+    }
+  }
+  covered.sort_unstable();
+  covered
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::sideboard::*;
+
+  fn plan(name: &str, weight: f64, cards_in: &str) -> MatchupPlan {
+    MatchupPlan {
+      name: name.to_string(),
+      weight,
+      cards_in: Deck::from_list(cards_in).unwrap(),
+    }
+  }
+
+  #[test]
+  fn sideboard_covers_the_heaviest_plans() {
+    let plans = vec![
+      plan("Aggro", 0.4, "4 Shock\n4 Lava Coil"),
+      plan("Control", 0.3, "4 Duress\n3 Negate"),
+      plan("Ramp", 0.2, "4 Duress\n4 Negate"),
+      plan("Mirror", 0.1, "2 Lava Coil\n3 Mystical Dispute"),
+    ];
+    // 4 Shock, 4 Lava Coil, 4 Duress, 4 Negate and 3 Mystical Dispute
+    assert_eq!(required_sideboard_size(&plans), 19);
+    let suggestion = suggest_sideboard(&plans, SIDEBOARD_SIZE);
+    assert_eq!(suggestion.sideboard.len(), SIDEBOARD_SIZE);
+    assert_eq!(suggestion.covered, vec!["Aggro", "Control"]);
+    let count = |name: &str| {
+      suggestion
+        .sideboard
+        .card_count_from_name(name)
+        .map_or(0, |cc| cc.count)
+    };
+    assert_eq!(count("Negate"), 3);
+    assert_eq!(count("Mystical Dispute"), 0);
+    // Ramp holds 7 of its 8 cards and the mirror 2 of its 5
+    assert!((suggestion.coverage - 0.915).abs() < 1e-9);
+    // The extra slot goes to the copy that covers the most weight, the fourth Negate for Ramp
+    let suggestion = suggest_sideboard(&plans, 16);
+    assert_eq!(suggestion.covered, vec!["Aggro", "Control", "Ramp"]);
+    let everything = suggest_sideboard(&plans, 19);
+    assert_eq!(everything.covered.len(), 4);
+    assert!((everything.coverage - 1.0).abs() < 1e-9);
+  }
+}
@@ -1,28 +1,89 @@
 //! # Simulation engine and card observations
-use crate::card::Card;
+use crate::card::{Card, ManaColor};
 use crate::deck::Deck;
-use crate::hand::{AutoTapResult, Hand, PlayOrder, Scratch, SimCard};
-use crate::mulligan::Mulligan;
+use crate::hand::{
+  AutoTapResult, Cantrip, CantripPolicy, DrawEngine, ExtraDraw, Hand, ManaSource, PlayOrder,
+  Scratch, SimCard,
+};
+use crate::mulligan::{Mulligan, MulliganCause};
 use rand::prelude::*;
 use rand::rngs::SmallRng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+/// Runs are split into chunks of this size, and with the `rayon` feature each chunk
+/// is simulated on its own thread. Every run has its own RNG stream, sub-seeded from the
+/// simulation seed and the run index, so the result does not depend on the thread count
+const CHUNK_SIZE: usize = 1024;
+
+/// The number of runs between two progress reports of `Simulation::from_config_with_progress`,
+/// and in each batch of runs reported to `SimulationObserver::on_run`
+const PROGRESS_BATCH_SIZE: usize = 8 * CHUNK_SIZE;
+
+/// SimulationConfig describes a simulation run. It is `Send + Sync` whenever the
+/// mulligan strategy is, so simulations can be run on worker threads
 pub struct SimulationConfig<'a, 'b, M: Mulligan> {
   pub run_count: usize,
   pub draw_count: usize,
   pub deck: &'a Deck,
   pub mulligan: &'b M,
+  /// True to play first, false to draw first. The observations for either
+  /// play order can be queried with `Simulation::observations_for_card_by_turn_and_play_order`
   pub on_the_play: bool,
+  /// Extra cards drawn on specific turns, in addition to `draw_count`
+  pub extra_draws: &'a [ExtraDraw],
+  /// The seed used to shuffle the deck, or None to seed from entropy.
+  /// Simulations with the same seed and config draw the same hands
+  pub seed: Option<u64>,
+  /// The cards that the cantrips of the deck keep on top of the library,
+  /// see `Deck::add_cantrip`. `CantripPolicy::Off` ignores the cantrips
+  pub cantrip_policy: CantripPolicy,
+}
+
+impl<'a, 'b, M: Mulligan> SimulationConfig<'a, 'b, M> {
+  /// Returns a config for `run_count` runs of `deck` with `draw_count` draws, on the play,
+  /// seeded from entropy, without extra draws and with cantrips off. Use the struct update
+  /// syntax to change the other fields
+  pub fn new(deck: &'a Deck, mulligan: &'b M, run_count: usize, draw_count: usize) -> Self {
+    Self {
+      run_count,
+      draw_count,
+      deck,
+      mulligan,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    }
+  }
 }
 
 #[derive(Debug, Default)]
 pub struct Simulation {
   pub hands: Vec<Hand>,
+  /// The seed of the simulation, pass it as `SimulationConfig::seed` to reproduce the run
+  pub seed: u64,
   pub accumulated_opening_hand_size: usize,
   pub accumulated_opening_hand_land_count: usize,
   pub on_the_play: bool,
+  pub extra_draws: Vec<ExtraDraw>,
+  pub mana_sources: Vec<ManaSource>,
+  pub draw_engines: Vec<DrawEngine>,
+  /// The cantrips cast by the simulation, empty with `CantripPolicy::Off`
+  pub cantrips: Vec<Cantrip>,
+  /// The runs of `hands` with the cantrips cast for the other play order, empty when no
+  /// cantrips are cast. Cantrips reorder the draws by play order, see `hands_for`
+  pub other_order_hands: Vec<Hand>,
+  /// Hashes of the cards in the command zone, which are always available to cast
+  pub commanders: Vec<u64>,
 }
 
 #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Observations {
   pub mana: usize,
   pub cmc: usize,
@@ -35,227 +96,2360 @@ impl Observations {
   pub fn new() -> Self {
     Self::default()
   }
-  pub fn p_mana(&self) -> f64 {
-    self.mana as f64 / self.total_runs as f64
+  pub fn p_mana(&self) -> f64 {
+    self.mana as f64 / self.total_runs as f64
+  }
+
+  pub fn p_mana_given_cmc(&self) -> f64 {
+    self.mana as f64 / self.cmc as f64
+  }
+
+  pub fn p_play(&self) -> f64 {
+    self.play as f64 / self.total_runs as f64
+  }
+
+  /// Returns the standard error of `p_mana`
+  pub fn p_mana_standard_error(&self) -> f64 {
+    standard_error(self.p_mana(), self.total_runs)
+  }
+
+  /// Returns the standard error of `p_play`
+  pub fn p_play_standard_error(&self) -> f64 {
+    standard_error(self.p_play(), self.total_runs)
+  }
+
+  /// Returns the Wilson confidence interval of `p_mana`, see `wilson_interval`
+  pub fn p_mana_interval(&self, z: f64) -> (f64, f64) {
+    wilson_interval(self.mana, self.total_runs, z)
+  }
+
+  /// Returns the Wilson confidence interval of `p_mana_given_cmc`, see `wilson_interval`
+  pub fn p_mana_given_cmc_interval(&self, z: f64) -> (f64, f64) {
+    wilson_interval(self.mana, self.cmc, z)
+  }
+
+  /// Returns the Wilson confidence interval of `p_play`, see `wilson_interval`
+  pub fn p_play_interval(&self, z: f64) -> (f64, f64) {
+    wilson_interval(self.play, self.total_runs, z)
+  }
+
+  /// Returns the width of the wider of the confidence intervals of `p_mana` and `p_play`
+  pub fn interval_width(&self, z: f64) -> f64 {
+    let (mana_low, mana_high) = self.p_mana_interval(z);
+    let (play_low, play_high) = self.p_play_interval(z);
+    f64::max(mana_high - mana_low, play_high - play_low)
+  }
+
+  /// Returns the sum of the observations in `self` and `other`
+  pub fn merge(self, other: Self) -> Self {
+    Self {
+      mana: self.mana + other.mana,
+      cmc: self.cmc + other.cmc,
+      play: self.play + other.play,
+      in_opening_hand: self.in_opening_hand + other.in_opening_hand,
+      total_runs: self.total_runs + other.total_runs,
+    }
+  }
+}
+
+/// TagObservations are the observations of the cards with a deck tag, see `Simulation::report`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TagObservations {
+  pub tag: String,
+  /// The turn by which the cards with the tag should be castable
+  pub turn: usize,
+  pub card_names: Vec<String>,
+  /// See `Simulation::observations_for_cards_by_turn_and_play_order`
+  pub observations: Observations,
+}
+
+/// CardGoal is a card to have in hand by a turn, either a named card or any card with a deck
+/// tag, e.g. the combo kill or any sweeper. See `London::for_goal` to dig for it, and
+/// `Simulation::p_combo_by_turn` with the first card as the piece and the others as tutors
+/// for the fraction of runs that find it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum CardGoal {
+  Card(String),
+  Tag(String),
+}
+
+impl CardGoal {
+  /// Returns the cards of `deck` that meet the goal
+  pub fn cards<'a>(&self, deck: &'a Deck) -> Vec<&'a Card> {
+    match self {
+      CardGoal::Card(name) => deck.card_from_name(name).into_iter().collect(),
+      CardGoal::Tag(tag) => deck.cards_with_tag(tag),
+    }
+  }
+}
+
+/// SimulationReport groups the observations of a simulation by deck tag
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SimulationReport {
+  pub groups: Vec<TagObservations>,
+}
+
+/// LandDropMisses breaks down the runs that miss the land drop of a turn by cause,
+/// see `Simulation::land_drop_misses`
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct LandDropMisses {
+  pub turn: usize,
+  /// The number of runs with fewer than `turn` lands by `turn`
+  pub misses: usize,
+  /// Misses where the kept opening hand was low on lands, which a stricter
+  /// mulligan strategy would avoid
+  pub low_land_hand: usize,
+  /// Misses where the kept opening hand had enough lands but spells were drawn
+  /// instead of lands, which only more lands in the deck would avoid
+  pub drew_spells: usize,
+  /// The number of runs with enough simulated draws to reach the turn
+  pub total_runs: usize,
+}
+
+/// CastableSpells is the distribution of the number of spells in hand that can be cast
+/// on a turn, see `Simulation::castable_spells`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CastableSpells {
+  pub turn: usize,
+  /// The number of runs with each number of castable spells in hand, indexed by the
+  /// number of spells
+  pub runs_by_count: Vec<usize>,
+  /// The number of runs with enough simulated draws to reach the turn
+  pub total_runs: usize,
+}
+
+/// TurnTempo counts the runs that stumble on a turn, see `Simulation::tempo`
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TurnTempo {
+  pub turn: usize,
+  /// The number of runs without a land to play on the turn
+  pub missed_land_drops: usize,
+  /// The number of runs that can cast nothing on the turn
+  pub stumbles: usize,
+  /// The mana left unspent on the turn, summed across runs
+  pub mana_wasted: usize,
+  /// The number of runs with enough simulated draws to reach the turn
+  pub total_runs: usize,
+}
+
+impl TurnTempo {
+  pub fn p_missed_land_drop(&self) -> f64 {
+    self.missed_land_drops as f64 / self.total_runs as f64
+  }
+
+  /// Returns the probability to cast nothing on the turn, the "stumble rate"
+  pub fn p_stumble(&self) -> f64 {
+    self.stumbles as f64 / self.total_runs as f64
+  }
+
+  pub fn average_mana_wasted(&self) -> f64 {
+    self.mana_wasted as f64 / self.total_runs as f64
+  }
+}
+
+/// TempoReport is the result of `Simulation::tempo`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TempoReport {
+  pub turns: Vec<TurnTempo>,
+  /// The number of runs that miss at least one land drop on the turns of the report
+  pub runs_missing_a_land_drop: usize,
+  /// The number of runs that cast nothing on at least one turn of the report
+  pub runs_stumbling: usize,
+  pub total_runs: usize,
+}
+
+/// MulliganStats describes the hands kept and mulliganed in a simulation,
+/// see `Simulation::mulligan_stats`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MulliganStats {
+  /// The number of runs that kept a hand of each size, indexed by hand size
+  pub kept_hand_sizes: Vec<usize>,
+  /// The number of hands mulliganed away across all runs
+  pub mulligans: usize,
+  /// Hands mulliganed for a land count when the strategy keeps more lands
+  pub too_few_lands: usize,
+  /// Hands mulliganed for a land count when the strategy only keeps fewer lands
+  pub too_many_lands: usize,
+  /// Hands with a kept land count that missed the keep criteria
+  pub keep_criteria: usize,
+  /// Hands with a kept land count that lacked the cards the strategy looks for
+  pub missing_cards: usize,
+  /// Hands mulliganed for a cause the strategy doesn't report
+  pub other_causes: usize,
+  /// The average number of lands in the kept hands
+  pub average_kept_land_count: f64,
+  pub total_runs: usize,
+}
+
+impl MulliganStats {
+  /// Returns the fraction of runs that kept a hand of `size` cards
+  pub fn p_kept_hand_size(&self, size: usize) -> f64 {
+    self.kept_hand_sizes.get(size).copied().unwrap_or(0) as f64 / self.total_runs as f64
+  }
+
+  /// Returns the fraction of mulligans caused by too few lands, 0 without mulligans
+  pub fn p_too_few_lands(&self) -> f64 {
+    self.p_mulligans(self.too_few_lands)
+  }
+
+  /// Returns the fraction of mulligans caused by too many lands, 0 without mulligans
+  pub fn p_too_many_lands(&self) -> f64 {
+    self.p_mulligans(self.too_many_lands)
+  }
+
+  fn p_mulligans(&self, count: usize) -> f64 {
+    if self.mulligans == 0 {
+      0.0
+    } else {
+      count as f64 / self.mulligans as f64
+    }
+  }
+}
+
+impl CastableSpells {
+  /// Returns the fraction of runs with exactly `count` castable spells in hand
+  pub fn p_count(&self, count: usize) -> f64 {
+    self.runs_by_count.get(count).copied().unwrap_or(0) as f64 / self.total_runs as f64
+  }
+
+  /// Returns the average number of castable spells in hand
+  pub fn average_count(&self) -> f64 {
+    let total: usize = self
+      .runs_by_count
+      .iter()
+      .enumerate()
+      .map(|(count, runs)| count * runs)
+      .sum();
+    total as f64 / self.total_runs as f64
+  }
+}
+
+/// TurnTrace describes a single turn of a simulated game, see `Simulation::goldfish`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TurnTrace {
+  pub turn: usize,
+  /// The names of the cards drawn this turn, including extra draws
+  pub drawn: Vec<String>,
+  /// The number of lands on the battlefield after this turn's land drop
+  pub lands_played: usize,
+  /// The names of the nonland cards in hand that can each be cast with `mana_available`
+  pub castable: Vec<String>,
+  /// The mana of the lands played and of the mana sources that can tap this turn
+  pub mana_available: usize,
+}
+
+/// Goldfish iterates over the turns of a single simulated game, see `Simulation::goldfish`.
+/// It ends on the last turn with all of its draws simulated
+pub struct Goldfish<'a> {
+  simulation: &'a Simulation,
+  deck: &'a Deck,
+  hand: &'a Hand,
+  play_order: PlayOrder,
+  turn: usize,
+  scratch: Scratch<'a>,
+}
+
+impl<'a> Goldfish<'a> {
+  /// Returns the names of the cards in the kept opening hand
+  pub fn opening_hand(&self) -> Vec<String> {
+    self.names(self.hand.opening())
+  }
+
+  fn names(&self, cards: &[SimCard]) -> Vec<String> {
+    cards.iter().map(|card| self.name(card)).collect()
+  }
+
+  fn name(&self, card: &SimCard) -> String {
+    self
+      .deck
+      .cards
+      .iter()
+      .find(|cc| cc.card.hash == card.hash)
+      .map_or_else(String::new, |cc| cc.card.name.clone())
+  }
+
+  /// Returns the number of cards drawn by `turn`, including extra draws
+  fn draw_count(&self, turn: usize) -> usize {
+    let extra_draws = self
+      .simulation
+      .extra_draws_for_hand(self.hand, turn, self.play_order);
+    self.play_order.draw_count(turn, &extra_draws)
+  }
+}
+
+impl<'a> Iterator for Goldfish<'a> {
+  type Item = TurnTrace;
+
+  fn next(&mut self) -> Option<TurnTrace> {
+    let turn = self.turn + 1;
+    let draw_count = self.draw_count(turn);
+    if self.hand.opening_hand_size + draw_count > self.hand.len() {
+      return None;
+    }
+    self.turn = turn;
+    let previous_draw_count = if turn == 1 {
+      0
+    } else {
+      self.draw_count(turn - 1)
+    };
+    let drawn = &self.hand.draws(draw_count)[previous_draw_count..];
+    let land_count = self
+      .hand
+      .count_in_opening_with_draws(draw_count, |c| c.kind.is_land());
+    let lands_played = std::cmp::min(land_count, turn);
+    // Mana sources tap once their delay has passed since they were drawn
+    let sources_available: usize = self
+      .simulation
+      .mana_sources
+      .iter()
+      .filter(|source| source.delay < turn)
+      .map(|source| {
+        let source_draw_count = self.draw_count(turn - source.delay);
+        self
+          .hand
+          .count_in_opening_with_draws(source_draw_count, |c| c.hash == source.hash)
+      })
+      .sum();
+    let mana_available = lands_played + sources_available;
+    let castable = self.simulation.castable_in_hand(
+      self.hand,
+      turn,
+      self.play_order,
+      mana_available,
+      &mut self.scratch,
+    );
+    Some(TurnTrace {
+      turn,
+      drawn: self.names(drawn),
+      lands_played,
+      castable: castable.into_iter().map(|card| self.name(card)).collect(),
+      mana_available,
+    })
+  }
+}
+
+/// The z-score of a 95% confidence interval
+pub const Z_95: f64 = 1.96;
+
+/// Returns the standard error of a proportion `p` estimated from `trials` runs
+pub fn standard_error(p: f64, trials: usize) -> f64 {
+  (p * (1.0 - p) / trials as f64).sqrt()
+}
+
+/// Returns the lower and upper bounds of the
+/// [Wilson score interval](https://en.wikipedia.org/wiki/Binomial_proportion_confidence_interval#Wilson_score_interval)
+/// for `successes` out of `trials` runs, where `z` is the z-score of the confidence level,
+/// e.g. `Z_95`. Unlike the normal approximation, the interval stays within [0, 1] and
+/// has a non-zero width when the proportion is 0 or 1
+pub fn wilson_interval(successes: usize, trials: usize, z: f64) -> (f64, f64) {
+  if trials == 0 {
+    return (0.0, 1.0);
+  }
+  let n = trials as f64;
+  let p = successes as f64 / n;
+  let z2 = z * z;
+  let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+  let half_width = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+  (
+    f64::max(0.0, center - half_width),
+    f64::min(1.0, center + half_width),
+  )
+}
+
+/// AdaptiveConfig describes when `Simulation::from_config_adaptive` stops adding runs
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct AdaptiveConfig {
+  /// Stop once every confidence interval is at most this wide, e.g. 0.008 for ±0.4%
+  pub max_interval_width: f64,
+  /// The z-score of the confidence level, e.g. `Z_95`
+  pub z: f64,
+  /// Stop after this many runs, even if some intervals are still too wide
+  pub max_run_count: usize,
+}
+
+/// Returns the RNG stream for the zero based `run` of a simulation with `seed`
+pub(crate) fn run_rng(seed: u64, run: usize) -> SmallRng {
+  // Multiply by the 64-bit golden ratio to spread consecutive run indices across the seed space
+  SmallRng::seed_from_u64(seed ^ (run as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// SimulationObserver receives events from the simulation loop, so embedding
+/// applications can gather their own metrics (timings, convergence, ...)
+/// without changing the engine. Every event has an empty default implementation
+pub trait SimulationObserver {
+  /// Called once before the first run
+  fn on_start(&mut self, _run_count: usize) {}
+  /// Called for each run with the zero based `run` index and the simulated `hand`, in run
+  /// order. Runs are simulated in batches, on multiple threads with the `rayon` feature, and
+  /// the runs of a batch are reported as soon as the batch is done
+  fn on_run(&mut self, _run: usize, _hand: &Hand) {}
+  /// Called once after the last run with the aggregate results
+  fn on_complete(&mut self, _simulation: &Simulation) {}
+}
+
+/// ProgressSink receives the progress of a simulation, see `Simulation::from_config_with_progress`.
+/// It is implemented by closures taking the same arguments as `on_progress`
+pub trait ProgressSink {
+  /// Called after each batch of runs with the number of runs `completed` out of `total`
+  /// and the simulation of the completed runs
+  fn on_progress(&mut self, completed: usize, total: usize, partial: &Simulation);
+}
+
+impl<F: FnMut(usize, usize, &Simulation)> ProgressSink for F {
+  fn on_progress(&mut self, completed: usize, total: usize, partial: &Simulation) {
+    self(completed, total, partial)
+  }
+}
+
+/// CancellationToken stops a simulation, e.g. from a UI thread, see
+/// `Simulation::from_config_with_progress`. Clones of a token share its state
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Cancels the simulations checking the token
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+/// Cancelled is the error of a cancelled simulation, with the simulation
+/// of the runs completed before it stopped
+#[derive(Debug)]
+pub struct Cancelled(pub Box<Simulation>);
+
+/// Returns the simulations of each of `decks`, in order, with the settings of `config`
+/// other than its deck. Every deck is simulated with the same seed, so run `i` of each
+/// simulation draws from the same RNG stream and paired comparisons between decks, e.g.
+/// 24 against 25 lands, are not blurred by different shuffles. With the `rayon` feature,
+/// the decks share the thread pool
+pub fn simulate_many<M: Mulligan + Sync>(
+  decks: &[Deck],
+  config: &SimulationConfig<M>,
+) -> Vec<Simulation> {
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let simulate = |deck: &Deck| {
+    Simulation::from_config(&SimulationConfig {
+      deck,
+      seed: Some(seed),
+      ..*config
+    })
+  };
+  #[cfg(feature = "rayon")]
+  let simulations = decks.par_iter().map(simulate).collect();
+  #[cfg(not(feature = "rayon"))]
+  let simulations = decks.iter().map(simulate).collect();
+  simulations
+}
+
+/// The unit type is an observer that ignores every event
+impl SimulationObserver for () {}
+
+impl Simulation {
+  pub fn from_config<M: Mulligan + Sync>(config: &SimulationConfig<M>) -> Self {
+    Self::from_config_with_observer(config, &mut ())
+  }
+
+  /// Same as `from_config`, but reports simulation events to `observer`
+  pub fn from_config_with_observer<M: Mulligan + Sync>(
+    config: &SimulationConfig<M>,
+    observer: &mut dyn SimulationObserver,
+  ) -> Self {
+    assert!(config.run_count > 0);
+    let seed = config
+      .seed
+      .unwrap_or_else(|| SmallRng::from_entropy().gen());
+    observer.on_start(config.run_count);
+    let mut simulation = Self::from_hands(config, seed, Vec::new());
+    while simulation.hands.len() < config.run_count {
+      let start = simulation.hands.len();
+      let end = std::cmp::min(start + PROGRESS_BATCH_SIZE, config.run_count);
+      let runs = Self::simulate_runs(config, seed, start, end);
+      for (run, (hand, _)) in (start..end).zip(&runs) {
+        observer.on_run(run, hand);
+      }
+      simulation.add_hands(runs);
+    }
+    observer.on_complete(&simulation);
+    simulation
+  }
+
+  /// Same as `from_config`, but simulates the runs in batches, reporting the progress and the
+  /// partial results to `progress` after each batch, and stops before the next batch once
+  /// `cancel` is cancelled. The completed runs are the same as those of `from_config` with
+  /// the same seed
+  pub fn from_config_with_progress<M: Mulligan + Sync>(
+    config: &SimulationConfig<M>,
+    progress: &mut dyn ProgressSink,
+    cancel: &CancellationToken,
+  ) -> Result<Self, Cancelled> {
+    assert!(config.run_count > 0);
+    let seed = config
+      .seed
+      .unwrap_or_else(|| SmallRng::from_entropy().gen());
+    let mut simulation = Self::from_hands(config, seed, Vec::new());
+    while simulation.hands.len() < config.run_count {
+      if cancel.is_cancelled() {
+        return Err(Cancelled(Box::new(simulation)));
+      }
+      let start = simulation.hands.len();
+      let end = std::cmp::min(start + PROGRESS_BATCH_SIZE, config.run_count);
+      simulation.add_hands(Self::simulate_runs(config, seed, start, end));
+      progress.on_progress(end, config.run_count, &simulation);
+    }
+    Ok(simulation)
+  }
+
+  /// Returns the simulation of `config` with `seed` that drew `runs`, each a hand and
+  /// the same hand with the cantrips cast for the other play order, see `play_cantrips`
+  pub(crate) fn from_hands<M: Mulligan>(
+    config: &SimulationConfig<M>,
+    seed: u64,
+    runs: Vec<(Hand, Option<Hand>)>,
+  ) -> Self {
+    let mut simulation = Simulation {
+      hands: Vec::new(),
+      other_order_hands: Vec::new(),
+      seed,
+      accumulated_opening_hand_size: 0,
+      accumulated_opening_hand_land_count: 0,
+      on_the_play: config.on_the_play,
+      extra_draws: config.extra_draws.to_vec(),
+      mana_sources: config.deck.mana_sources.clone(),
+      draw_engines: config.deck.draw_engines.clone(),
+      cantrips: if config.cantrip_policy == CantripPolicy::Off {
+        Vec::new()
+      } else {
+        config.deck.cantrips.clone()
+      },
+      commanders: config.deck.commanders.iter().map(|c| c.hash).collect(),
+    };
+    simulation.add_hands(runs);
+    simulation
+  }
+
+  /// Same as `from_config`, but keeps adding batches of `config.run_count` runs until the
+  /// confidence intervals of `p_mana` and `p_play` for every nonland card in the deck,
+  /// on the turn of the card, are no wider than `adaptive.max_interval_width`
+  pub fn from_config_adaptive<M: Mulligan + Sync>(
+    config: &SimulationConfig<M>,
+    adaptive: &AdaptiveConfig,
+  ) -> Self {
+    let mut simulation = Self::from_config(config);
+    while simulation.hands.len() < adaptive.max_run_count
+      && simulation.max_interval_width(config.deck, adaptive.z) > adaptive.max_interval_width
+    {
+      let start = simulation.hands.len();
+      let end = std::cmp::min(start + config.run_count, adaptive.max_run_count);
+      let runs = Self::simulate_runs(config, simulation.seed, start, end);
+      simulation.add_hands(runs);
+    }
+    simulation
+  }
+
+  /// Returns the widest confidence interval of `p_mana` and `p_play` across
+  /// the nonland cards in `deck`, on the turn of each card
+  pub fn max_interval_width(&self, deck: &Deck, z: f64) -> f64 {
+    deck
+      .cards
+      .iter()
+      .filter(|cc| !cc.card.is_land())
+      .map(|cc| self.observations_for_card(&cc.card).interval_width(z))
+      .fold(0.0, f64::max)
+  }
+
+  /// Returns a simulation along with the observations of every nonland card in
+  /// `config.deck`, keyed by card hash, on the turn of each card. Unlike
+  /// `from_config_adaptive`, each card is only evaluated against as many runs as it needs:
+  /// cards whose confidence intervals are narrow enough after a batch of `config.run_count`
+  /// runs are done, and only the remaining cards are evaluated against the next batch.
+  /// Paying for each card in each hand dominates the cost of a report, so cards that
+  /// converge quickly, like those that are almost always castable, finish early
+  pub fn from_config_per_card<M: Mulligan + Sync>(
+    config: &SimulationConfig<M>,
+    adaptive: &AdaptiveConfig,
+  ) -> (Self, HashMap<u64, Observations>) {
+    let mut simulation = Self::from_config(config);
+    let play_order = if simulation.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let mut pending: Vec<&Card> = config
+      .deck
+      .cards
+      .iter()
+      .map(|cc| &cc.card)
+      .filter(|c| !c.is_land())
+      .collect();
+    let mut observations: HashMap<u64, Observations> = HashMap::new();
+    let mut start = 0;
+    loop {
+      let end = simulation.hands.len();
+      for card in &pending {
+        let batch = simulation.observations_in(
+          &simulation.hands[start..end],
+          &[card],
+          card.turn as usize,
+          play_order,
+        );
+        let entry = observations.entry(card.hash).or_default();
+        *entry = entry.merge(batch);
+      }
+      pending.retain(|card| {
+        observations[&card.hash].interval_width(adaptive.z) > adaptive.max_interval_width
+      });
+      if pending.is_empty() || end >= adaptive.max_run_count {
+        break;
+      }
+      let next = std::cmp::min(end + config.run_count, adaptive.max_run_count);
+      let runs = Self::simulate_runs(config, simulation.seed, end, next);
+      simulation.add_hands(runs);
+      start = end;
+    }
+    (simulation, observations)
+  }
+
+  /// Returns the runs in `start..end` of a simulation with `seed`, see `from_hands`
+  fn simulate_runs<M: Mulligan + Sync>(
+    config: &SimulationConfig<M>,
+    seed: u64,
+    start: usize,
+    end: usize,
+  ) -> Vec<(Hand, Option<Hand>)> {
+    let deck = config.deck.flatten();
+    let draw_count = Self::simulated_draw_count(config);
+    let chunk_starts: Vec<usize> = (start..end).step_by(CHUNK_SIZE).collect();
+    let simulate_chunk = |chunk_start: &usize| -> Vec<(Hand, Option<Hand>)> {
+      let chunk_end = std::cmp::min(chunk_start + CHUNK_SIZE, end);
+      (*chunk_start..chunk_end)
+        .map(|run| {
+          let mut rng = run_rng(seed, run);
+          let mut hand = Hand::from_mulligan(config.mulligan, &mut rng, &deck, draw_count);
+          let other_order_hand = Self::play_cantrips(config, &mut hand);
+          (hand, other_order_hand)
+        })
+        .collect()
+    };
+    #[cfg(feature = "rayon")]
+    let chunks: Vec<Vec<_>> = chunk_starts.par_iter().map(simulate_chunk).collect();
+    #[cfg(not(feature = "rayon"))]
+    let chunks: Vec<Vec<_>> = chunk_starts.iter().map(simulate_chunk).collect();
+    chunks.into_iter().flatten().collect()
+  }
+
+  /// Returns the number of cards each run draws after the opening hand, enough for the
+  /// extra draws of every turn a card can be on curve
+  pub(crate) fn simulated_draw_count<M: Mulligan>(config: &SimulationConfig<M>) -> usize {
+    let last_turn = config.draw_count + 1;
+    config.draw_count
+      + config
+        .extra_draws
+        .iter()
+        .map(|e| e.count_by_turn(last_turn))
+        .sum::<usize>()
+      + config
+        .deck
+        .draw_engines
+        .iter()
+        .map(|e| e.count * last_turn)
+        .sum::<usize>()
+      + Self::cantrip_draw_count(config)
+  }
+
+  /// Returns the number of cards the cantrips of `config` can draw or put on the bottom
+  fn cantrip_draw_count<M: Mulligan>(config: &SimulationConfig<M>) -> usize {
+    if config.cantrip_policy == CantripPolicy::Off {
+      return 0;
+    }
+    config
+      .deck
+      .cantrips
+      .iter()
+      .map(|cantrip| {
+        let copies = config
+          .deck
+          .cards
+          .iter()
+          .find(|cc| cc.card.hash == cantrip.hash)
+          .map_or(0, |cc| cc.count);
+        copies * (cantrip.look + cantrip.draws)
+      })
+      .sum()
+  }
+
+  /// Casts the cantrips of `config` in `hand` by the last turn of the simulation, for the
+  /// play order of `config`, and returns the hand with the cantrips cast for the other play
+  /// order instead, or None when no cantrips are cast. See `Hand::play_cantrips`
+  pub(crate) fn play_cantrips<M: Mulligan>(
+    config: &SimulationConfig<M>,
+    hand: &mut Hand,
+  ) -> Option<Hand> {
+    if config.cantrip_policy == CantripPolicy::Off || config.deck.cantrips.is_empty() {
+      return None;
+    }
+    let (play_order, other_order) = if config.on_the_play {
+      (PlayOrder::First, PlayOrder::Second)
+    } else {
+      (PlayOrder::Second, PlayOrder::First)
+    };
+    let mut other_order_hand = hand.clone();
+    for (hand, play_order) in [
+      (&mut *hand, play_order),
+      (&mut other_order_hand, other_order),
+    ] {
+      hand.play_cantrips(
+        play_order,
+        config.extra_draws,
+        &config.deck.cantrips,
+        config.cantrip_policy,
+        config.draw_count + 1,
+      );
+    }
+    Some(other_order_hand)
+  }
+
+  /// Appends the hands of `runs` to the simulation and updates the accumulated statistics,
+  /// see `from_hands`
+  fn add_hands(&mut self, runs: Vec<(Hand, Option<Hand>)>) {
+    let (hands, other_order_hands): (Vec<Hand>, Vec<Option<Hand>>) = runs.into_iter().unzip();
+    self
+      .other_order_hands
+      .extend(other_order_hands.into_iter().flatten());
+    self.accumulated_opening_hand_size +=
+      hands.iter().map(|hand| hand.opening().len()).sum::<usize>();
+    self.accumulated_opening_hand_land_count += hands
+      .iter()
+      .map(|hand| hand.count_in_opening_with_draws(0, |c| c.kind.is_land()))
+      .sum::<usize>();
+    self.hands.extend(hands);
+  }
+
+  pub fn observations_for_card(&self, card: &Card) -> Observations {
+    self.observations_for_card_by_turn(card, card.turn as usize)
+  }
+
+  pub fn observations_for_card_by_turn(&self, card: &Card, turn: usize) -> Observations {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    self.observations_for_card_by_turn_and_play_order(card, turn, play_order)
+  }
+
+  /// Same as `observations_for_card_by_turn`, but for the given `play_order` rather
+  /// than the one configured for the simulation. The simulated hands do not depend on
+  /// the play order, except for the cantrips cast, see `hands_for`, so both can be
+  /// reported from a single simulation
+  pub fn observations_for_card_by_turn_and_play_order(
+    &self,
+    card: &Card,
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> Observations {
+    self.observations_for_cards_by_turn_and_play_order(&[card], turn, play_order)
+  }
+
+  /// Returns the observations of a group of `cards` by `turn`. A run counts towards
+  /// `cmc` and `mana` when the hand can pay for every card in the group, each on its
+  /// own, and towards `play` and `in_opening_hand` when any card in the group can be
+  /// played or is in the opening hand. For a single card, these are the observations
+  /// of `observations_for_card_by_turn_and_play_order`
+  pub fn observations_for_cards_by_turn_and_play_order(
+    &self,
+    cards: &[&Card],
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> Observations {
+    self.observations_in(self.hands_for(play_order), cards, turn, play_order)
+  }
+
+  /// Returns the hands of the runs for `play_order`: `hands` for the configured play order,
+  /// or without cantrips, and `other_order_hands` otherwise
+  pub fn hands_for(&self, play_order: PlayOrder) -> &[Hand] {
+    let configured = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    if play_order == configured || self.other_order_hands.is_empty() {
+      &self.hands
+    } else {
+      &self.other_order_hands
+    }
+  }
+
+  /// Returns a report of the castability of each tag group in `goals`, given as
+  /// a tag of `deck` and the turn by which the cards with the tag should be castable,
+  /// e.g. `&[("removal", 3), ("wincons", 5)]`. Tags without cards are left out
+  pub fn report(&self, deck: &Deck, goals: &[(&str, usize)]) -> SimulationReport {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let groups = goals
+      .iter()
+      .filter_map(|(tag, turn)| {
+        let cards = deck.cards_with_tag(tag);
+        if cards.is_empty() {
+          return None;
+        }
+        Some(TagObservations {
+          tag: tag.to_string(),
+          turn: *turn,
+          card_names: cards.iter().map(|c| c.name.clone()).collect(),
+          observations: self
+            .observations_for_cards_by_turn_and_play_order(&cards, *turn, play_order),
+        })
+      })
+      .collect();
+    SimulationReport { groups }
+  }
+
+  /// Returns the kept hand sizes, the causes of the mulligans and the average
+  /// land count of the kept hands. The causes are the ones the mulligan strategy
+  /// recorded when it rejected each hand, see `MulliganCause`
+  pub fn mulligan_stats(&self) -> MulliganStats {
+    let mut stats = MulliganStats {
+      total_runs: self.hands.len(),
+      average_kept_land_count: self.accumulated_opening_hand_land_count as f64
+        / self.hands.len() as f64,
+      ..MulliganStats::default()
+    };
+    for hand in &self.hands {
+      let size = hand.opening_hand_size;
+      if stats.kept_hand_sizes.len() <= size {
+        stats.kept_hand_sizes.resize(size + 1, 0);
+      }
+      stats.kept_hand_sizes[size] += 1;
+      for cause in &hand.mulligans {
+        stats.mulligans += 1;
+        match cause {
+          MulliganCause::TooFewLands => stats.too_few_lands += 1,
+          MulliganCause::TooManyLands => stats.too_many_lands += 1,
+          MulliganCause::KeepCriteria => stats.keep_criteria += 1,
+          MulliganCause::MissingCards => stats.missing_cards += 1,
+          MulliganCause::Other => stats.other_causes += 1,
+        }
+      }
+    }
+    stats
+  }
+
+  /// Returns the land drop misses of turns 1 through `max_turn`. A run misses the land drop
+  /// of a turn when it has fewer lands than the turn number by then. A miss is attributed to
+  /// the kept opening hand when it has fewer than `low_land_count` lands, and to the
+  /// draws otherwise. Runs are counted on the turns their simulated draws reach, see
+  /// `LandDropMisses::total_runs`
+  pub fn land_drop_misses(&self, max_turn: usize, low_land_count: usize) -> Vec<LandDropMisses> {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let mut misses: Vec<LandDropMisses> = (1..=max_turn)
+      .map(|turn| LandDropMisses {
+        turn,
+        ..LandDropMisses::default()
+      })
+      .collect();
+    for hand in &self.hands {
+      let opening_land_count = hand.count_in_opening_with_draws(0, |c| c.kind.is_land());
+      let land_counts = self.land_counts_by_turn(hand, max_turn, play_order);
+      for (miss, land_count) in misses.iter_mut().zip(land_counts) {
+        miss.total_runs += 1;
+        if land_count >= miss.turn {
+          continue;
+        }
+        miss.misses += 1;
+        if opening_land_count < low_land_count {
+          miss.low_land_hand += 1;
+        } else {
+          miss.drew_spells += 1;
+        }
+      }
+    }
+    misses
+  }
+
+  /// Returns the number of lands in `hand` by each of turns 1 through `max_turn`, indexed by
+  /// turn - 1, and stopping at the last turn that the simulated draws of the hand reach
+  fn land_counts_by_turn(&self, hand: &Hand, max_turn: usize, play_order: PlayOrder) -> Vec<usize> {
+    (1..=max_turn)
+      .map(|turn| play_order.draw_count(turn, &self.extra_draws_for_hand(hand, turn, play_order)))
+      .take_while(|draws| hand.opening_hand_size + draws <= hand.len())
+      .map(|draws| hand.count_in_opening_with_draws(draws, |c| c.kind.is_land()))
+      .collect()
+  }
+
+  /// Returns the distribution of the number of nonland cards in hand that can each be
+  /// cast on turns 1 through `max_turn`, a measure of how often the deck has nothing to do
+  /// with its mana. A single land is played each turn, so cards with a mana value greater
+  /// than the turn are never castable. Cards cast on earlier turns are not removed from the
+  /// hand, and each card is considered on its own. Runs are counted on the turns their
+  /// simulated draws reach, see `CastableSpells::total_runs`
+  pub fn castable_spells(&self, max_turn: usize) -> Vec<CastableSpells> {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let mut castable: Vec<CastableSpells> = (1..=max_turn)
+      .map(|turn| CastableSpells {
+        turn,
+        ..CastableSpells::default()
+      })
+      .collect();
+    let mut scratch = Scratch::new(30, 10);
+    for hand in &self.hands {
+      for spells in castable.iter_mut() {
+        let turn = spells.turn;
+        let draws = play_order.draw_count(turn, &self.extra_draws_for_hand(hand, turn, play_order));
+        if hand.opening_hand_size + draws > hand.len() {
+          break;
+        }
+        spells.total_runs += 1;
+        let count = self
+          .castable_in_hand(hand, turn, play_order, turn, &mut scratch)
+          .len();
+        if spells.runs_by_count.len() <= count {
+          spells.runs_by_count.resize(count + 1, 0);
+        }
+        spells.runs_by_count[count] += 1;
+      }
+    }
+    castable
+  }
+
+  /// Returns the missed land drops, the turns without anything to cast, and the mana left
+  /// unspent on each of the `turns`, e.g. `2..=5`, as players quote them. Each run is played
+  /// out from turn 1: up to one land per turn is in play, and land drops are missed as in
+  /// `Simulation::land_drop_misses`, so the two agree. The castable spells in hand are cast,
+  /// the highest mana value first, while the mana lasts. Spells are checked
+  /// for castability each on its own, so two spells may share a colored source. Runs are
+  /// counted on the turns their simulated draws reach, see `TurnTempo::total_runs`
+  pub fn tempo(&self, turns: std::ops::RangeInclusive<usize>) -> TempoReport {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let mut report = TempoReport {
+      turns: turns
+        .clone()
+        .map(|turn| TurnTempo {
+          turn,
+          ..TurnTempo::default()
+        })
+        .collect(),
+      total_runs: self.hands.len(),
+      ..TempoReport::default()
+    };
+    let mut scratch = Scratch::new(30, 10);
+    for hand in &self.hands {
+      let mut cast: Vec<usize> = Vec::new();
+      let mut missed_land_drop = false;
+      let mut stumbled = false;
+      let land_counts = self.land_counts_by_turn(hand, *turns.end(), play_order);
+      for (turn, land_count) in (1..).zip(land_counts) {
+        let extra_draws = self.extra_draws_for_hand(hand, turn, play_order);
+        let draw_count = play_order.draw_count(turn, &extra_draws);
+        let cards = hand.opening_with_draws(draw_count);
+        let missed = land_count < turn;
+        let lands_played = std::cmp::min(land_count, turn);
+        let sources_available: usize = self
+          .mana_sources
+          .iter()
+          .filter(|source| source.delay < turn)
+          .map(|source| {
+            let source_draw_count = play_order.draw_count(
+              turn - source.delay,
+              &self.extra_draws_for_hand(hand, turn - source.delay, play_order),
+            );
+            hand.count_in_opening_with_draws(source_draw_count, |c| c.hash == source.hash)
+          })
+          .sum();
+        let mut mana = lands_played + sources_available;
+        let mut castable: Vec<usize> = (0..cards.len())
+          .filter(|i| !cards[*i].kind.is_land() && !cast.contains(i))
+          .filter(|i| {
+            let result = hand.auto_tap_with_sources(
+              &cards[*i],
+              turn,
+              play_order,
+              &extra_draws,
+              &self.mana_sources,
+              &mut scratch,
+            );
+            result.cmc && result.paid
+          })
+          .collect();
+        castable.sort_by_key(|i| std::cmp::Reverse(cards[*i].mana_cost.cmc()));
+        let mut cast_this_turn = 0;
+        for i in castable {
+          let cmc = cards[i].mana_cost.cmc() as usize;
+          if cmc <= mana {
+            mana -= cmc;
+            cast.push(i);
+            cast_this_turn += 1;
+          }
+        }
+        if !turns.contains(&turn) {
+          continue;
+        }
+        let tempo = &mut report.turns[turn - turns.start()];
+        tempo.total_runs += 1;
+        tempo.mana_wasted += mana;
+        if missed {
+          tempo.missed_land_drops += 1;
+          missed_land_drop = true;
+        }
+        if cast_this_turn == 0 {
+          tempo.stumbles += 1;
+          stumbled = true;
+        }
+      }
+      report.runs_missing_a_land_drop += missed_land_drop as usize;
+      report.runs_stumbling += stumbled as usize;
+    }
+    report
+  }
+
+  /// Returns the fraction of runs that assemble the combo of `pieces`, one copy of each of
+  /// these distinct cards, by each of turns 1 through `max_turn`, indexed by turn - 1.
+  /// Every card in `tutors` finds any one missing piece, as soon as it is drawn. Unlike
+  /// `Deck::combo_draws`, this accounts for mulligans, extra draws and draw engines
+  pub fn p_combo_by_turn(&self, pieces: &[&Card], tutors: &[&Card], max_turn: usize) -> Vec<f64> {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let tutors: Vec<u64> = tutors
+      .iter()
+      .map(|tutor| tutor.hash)
+      .filter(|hash| pieces.iter().all(|piece| piece.hash != *hash))
+      .collect();
+    let mut assembled = vec![0; max_turn];
+    for hand in &self.hands {
+      for (i, turn) in (1..=max_turn).enumerate() {
+        let draws = play_order.draw_count(turn, &self.extra_draws_for_hand(hand, turn, play_order));
+        let cards = hand.opening_with_draws(draws);
+        let missing = pieces
+          .iter()
+          .filter(|piece| cards.iter().all(|card| card.hash != piece.hash))
+          .count();
+        let tutors_seen = cards
+          .iter()
+          .filter(|card| tutors.contains(&card.hash))
+          .count();
+        if missing <= tutors_seen {
+          assembled[i] += 1;
+        }
+      }
+    }
+    assembled
+      .into_iter()
+      .map(|count| count as f64 / self.hands.len() as f64)
+      .collect()
+  }
+
+  /// Returns the turn by turn trace of the game of the zero based `run`, an example game
+  /// for UIs to show next to the aggregate statistics. `deck` should be the simulated deck,
+  /// and names the cards of the traces. For a single seeded game, simulate a single run
+  pub fn goldfish<'a>(&'a self, deck: &'a Deck, run: usize) -> Goldfish<'a> {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    Goldfish {
+      simulation: self,
+      deck,
+      hand: &self.hands[run],
+      play_order,
+      turn: 0,
+      scratch: Scratch::new(30, 10),
+    }
+  }
+
+  /// Returns how often each color requirement of `card` is what keeps it from being cast
+  /// by `turn`: for each color in the mana cost of the card, the number of runs with enough
+  /// lands but not the right colors, where paying for that color with any mana would pay
+  /// for the card. Colors the card does not require are left out
+  pub fn color_blocks_for_card_by_turn(&self, card: &Card, turn: usize) -> Vec<(ManaColor, usize)> {
+    let play_order = if self.on_the_play {
+      PlayOrder::First
+    } else {
+      PlayOrder::Second
+    };
+    let generic_cards: Vec<(ManaColor, Card)> = [
+      ManaColor::White,
+      ManaColor::Blue,
+      ManaColor::Black,
+      ManaColor::Red,
+      ManaColor::Green,
+    ]
+    .iter()
+    .filter(|color| {
+      card
+        .all_mana_costs
+        .iter()
+        .any(|cost| cost.count(**color) > 0)
+    })
+    .map(|color| {
+      let mut generic = card.clone();
+      for cost in generic.all_mana_costs.iter_mut() {
+        *cost = cost.with_generic(*color);
+      }
+      (*color, generic)
+    })
+    .collect();
+    let mut blocks: Vec<(ManaColor, usize)> =
+      generic_cards.iter().map(|(color, _)| (*color, 0)).collect();
+    let mut scratch = Scratch::new(30, 10);
+    for hand in &self.hands {
+      let result = self.auto_tap_card(hand, card, turn, play_order, &mut scratch);
+      if !result.cmc || result.paid {
+        continue;
+      }
+      for (i, (_, generic)) in generic_cards.iter().enumerate() {
+        if self
+          .auto_tap_card(hand, generic, turn, play_order, &mut scratch)
+          .paid
+        {
+          blocks[i].1 += 1;
+        }
+      }
+    }
+    blocks
+  }
+
+  /// Same as `observations_for_cards_by_turn_and_play_order`, but only for `hands`
+  fn observations_in(
+    &self,
+    hands: &[Hand],
+    cards: &[&Card],
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> Observations {
+    #[cfg(feature = "rayon")]
+    let observations = hands
+      .par_chunks(CHUNK_SIZE)
+      .map(|hands| self.observations_for_hands(hands, cards, turn, play_order))
+      .reduce(Observations::new, Observations::merge);
+    #[cfg(not(feature = "rayon"))]
+    let observations = self.observations_for_hands(hands, cards, turn, play_order);
+    assert!(observations.mana <= observations.cmc);
+    observations
+  }
+
+  fn observations_for_hands(
+    &self,
+    hands: &[Hand],
+    cards: &[&Card],
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> Observations {
+    let mut observations = Observations::new();
+    observations.total_runs = hands.len();
+    let mut scratch = Scratch::new(30, 10);
+    for hand in hands {
+      let mut all_cmc = true;
+      let mut all_paid = true;
+      let mut any_played = false;
+      let mut any_in_opening_hand = false;
+      for card in cards {
+        let result = self.auto_tap_card(hand, card, turn, play_order, &mut scratch);
+        // Commanders start in the command zone and never need to be drawn
+        let in_command_zone = self.commanders.contains(&card.hash);
+        all_cmc &= result.cmc;
+        all_paid &= result.cmc && result.paid;
+        any_in_opening_hand |= result.in_opening_hand;
+        // Was the card in question in our initial hand? Did we draw it on curve?
+        any_played |= result.cmc
+          && result.paid
+          && (result.in_opening_hand || result.in_draw_hand || in_command_zone);
+      }
+      if any_in_opening_hand {
+        observations.in_opening_hand += 1;
+      }
+      // Count a CMC lands on curve event
+      if all_cmc {
+        observations.cmc += 1;
+      }
+      // Count a mana on curve event
+      if all_paid {
+        observations.mana += 1;
+      }
+      if any_played {
+        observations.play += 1;
+      }
+    }
+    observations
+  }
+
+  /// Returns the result of paying for `card` by `turn` with `hand`, trying each of
+  /// the potential mana costs of the card
+  fn auto_tap_card<'a>(
+    &'a self,
+    hand: &'a Hand,
+    card: &Card,
+    turn: usize,
+    play_order: PlayOrder,
+    scratch: &mut Scratch<'a>,
+  ) -> AutoTapResult {
+    let mut result = AutoTapResult::new();
+    let extra_draws = self.extra_draws_for_hand(hand, turn, play_order);
+    for mana_cost in &card.all_mana_costs {
+      let goal = SimCard {
+        hash: card.hash,
+        mana_cost: *mana_cost,
+        kind: card.kind,
+      };
+      result = hand.auto_tap_with_sources(
+        &goal,
+        turn,
+        play_order,
+        &extra_draws,
+        &self.mana_sources,
+        scratch,
+      );
+      if result.paid {
+        break;
+      }
+    }
+    result
+  }
+
+  /// Returns the nonland cards in `hand` by `turn` with a mana value of at most `max_cmc`
+  /// that can each be paid for on their own
+  fn castable_in_hand<'a>(
+    &'a self,
+    hand: &'a Hand,
+    turn: usize,
+    play_order: PlayOrder,
+    max_cmc: usize,
+    scratch: &mut Scratch<'a>,
+  ) -> Vec<&'a SimCard> {
+    let extra_draws = self.extra_draws_for_hand(hand, turn, play_order);
+    let draws = play_order.draw_count(turn, &extra_draws);
+    hand
+      .opening_with_draws(draws)
+      .iter()
+      .filter(|card| !card.kind.is_land() && card.mana_cost.cmc() as usize <= max_cmc)
+      .filter(|card| {
+        let result = hand.auto_tap_with_sources(
+          card,
+          turn,
+          play_order,
+          &extra_draws,
+          &self.mana_sources,
+          scratch,
+        );
+        result.cmc && result.paid
+      })
+      .collect()
+  }
+
+  /// Returns the extra draws of `hand` by `turn`, including those of the cantrips and draw
+  /// engines it finds
+  fn extra_draws_for_hand(
+    &self,
+    hand: &Hand,
+    turn: usize,
+    play_order: PlayOrder,
+  ) -> Cow<'_, [ExtraDraw]> {
+    if self.draw_engines.is_empty() && self.cantrips.is_empty() {
+      return Cow::Borrowed(&self.extra_draws);
+    }
+    let extra_draws =
+      hand.extra_draws_with_cantrips(play_order, &self.extra_draws, &self.cantrips, turn);
+    Cow::Owned(hand.extra_draws_with_engines(play_order, &extra_draws, &self.draw_engines, turn))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::card::ManaCost;
+  use crate::deck::*;
+  use crate::mulligan::{KeepLands, London, Never};
+  use crate::simulation::*;
+
+  #[test]
+  fn deck_with_not_enough_cards_should_not_panic() {
+    let code = "
+      1 Discovery // Dispersal (GRN) 223
+      1 Unmoored Ego (GRN) 212
+      3 Vraska's Contempt (XLN) 129
+      1 Watery Grave (GRN) 259
+    ";
+    let deck = decklist!(code);
+    Simulation::from_config(&SimulationConfig {
+      run_count: 10,
+      draw_count: 10,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+  }
+
+  #[test]
+  fn deck_with_single_zero_mana_card() {
+    let card = card!("Ornithopter");
+    let deck = Deck::from_cards(vec![card.clone()]);
+    let runs = 10;
+    let draws = 0;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: runs,
+      draw_count: draws,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let obs = sim.observations_for_card(&card);
+    assert_eq!(obs.cmc, runs);
+    assert_eq!(obs.mana, runs);
+    assert_eq!(obs.play, runs);
+  }
+
+  #[test]
+  fn small_deck_1() {
+    let deck = decklist!(
+      "
+    1 Llanowar Elves
+    6 Forest
+    "
+    );
+    let runs = 10;
+    let draws = 0;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: runs,
+      draw_count: draws,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    assert_eq!(obs.cmc, runs);
+    assert_eq!(obs.mana, runs);
+    assert_eq!(obs.play, runs);
+  }
+
+  // on the draw, always draw all 8 cards
+  #[test]
+  fn small_deck_2() {
+    let deck = decklist!(
+      "
+    1 Llanowar Elves
+    7 Forest
+    "
+    );
+    let draws = 1;
+    let runs = 10;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: runs,
+      draw_count: draws,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: false,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    assert_eq!(obs.cmc, runs);
+    assert_eq!(obs.mana, runs);
+    assert_eq!(obs.play, runs);
+  }
+
+  #[test]
+  fn small_deck_3() {
+    let deck = decklist!(
+      "
+    6 Llanowar Elves
+    1 Forest
+    "
+    );
+    let draws = 1;
+    let runs = 10;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: runs,
+      draw_count: draws,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    assert_eq!(obs.cmc, runs);
+    assert_eq!(obs.mana, runs);
+    assert_eq!(obs.play, runs);
+    assert_eq!(obs.in_opening_hand, runs);
+  }
+
+  // on the draw
+  #[test]
+  fn small_deck_4() {
+    let deck_list = "
+    7 Llanowar Elves
+    1 Forest
+    ";
+    let deck = decklist!(deck_list);
+    let draws = 1;
+    let runs = 10;
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: runs,
+      draw_count: draws,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: false,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    assert_eq!(obs.cmc, runs);
+    assert_eq!(obs.mana, runs);
+    assert_eq!(obs.play, runs);
+    assert_eq!(obs.in_opening_hand, runs);
+  }
+
+  #[test]
+  fn play_and_draw_observations() {
+    let deck = decklist!(
+      "
+      20 Island
+      40 Opt
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 3,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let card = card!("Opt");
+    let play = sim.observations_for_card_by_turn_and_play_order(card, 3, PlayOrder::First);
+    let draw = sim.observations_for_card_by_turn_and_play_order(card, 3, PlayOrder::Second);
+    let default = sim.observations_for_card_by_turn(card, 3);
+    assert_eq!(play.mana, default.mana);
+    assert!(draw.mana >= play.mana);
+  }
+
+  #[test]
+  fn extra_draws_on_turn() {
+    let deck = decklist!(
+      "
+      1 Island
+      6 Opt
+      "
+    );
+    let card = card!("Opt");
+    // The only land is the 8th card of the deck at worst, which is drawn on turn 1 on the draw
+    // with a single extra draw
+    let extra_draws = [ExtraDraw {
+      turn: 1,
+      count: 1,
+      every_turn: false,
+    }];
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 10,
+      draw_count: 0,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: false,
+      extra_draws: &extra_draws,
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
+    });
+    assert_eq!(sim.hands[0].len(), 7);
+    let obs = sim.observations_for_card_by_turn(card, 1);
+    assert_eq!(obs.mana, 10);
+  }
+
+  #[test]
+  fn cantrips_find_lands() {
+    let mut deck = decklist!(
+      "
+      16 Island
+      16 Opt
+      28 Sinister Sabotage
+      "
+    );
+    deck.add_known_cantrips();
+    assert_eq!(deck.cantrips.len(), 1);
+    let mulligan = Never::never();
+    let simulate = |cantrip_policy| {
+      Simulation::from_config(&SimulationConfig {
+        seed: Some(11),
+        cantrip_policy,
+        ..SimulationConfig::new(&deck, &mulligan, 5000, 3)
+      })
+    };
+    let sabotage = card!("Sinister Sabotage");
+    let p_mana = |sim: &Simulation| sim.observations_for_card(sabotage).p_mana();
+    let off = simulate(CantripPolicy::Off);
+    assert!(off.cantrips.is_empty());
+    assert!(off.other_order_hands.is_empty());
+    let lands = simulate(CantripPolicy::Lands);
+    let spells = simulate(CantripPolicy::Spells);
+    let curve = simulate(CantripPolicy::Curve);
+    assert!(p_mana(&lands) > p_mana(&off) + 0.05);
+    assert!(p_mana(&curve) > p_mana(&off));
+    assert!(p_mana(&spells) < p_mana(&lands));
+    // The cantrips are cast for each play order, as if the simulation was on the draw
+    let on_the_draw = Simulation::from_config(&SimulationConfig {
+      on_the_play: false,
+      seed: Some(11),
+      cantrip_policy: CantripPolicy::Lands,
+      ..SimulationConfig::new(&deck, &mulligan, 5000, 3)
+    });
+    let observations = |sim: &Simulation, play_order| {
+      let o = sim.observations_for_card_by_turn_and_play_order(sabotage, 3, play_order);
+      (o.mana, o.play, o.cmc)
+    };
+    assert_eq!(lands.other_order_hands.len(), 5000);
+    assert_eq!(
+      observations(&lands, PlayOrder::Second),
+      observations(&on_the_draw, PlayOrder::Second)
+    );
+    assert_eq!(
+      observations(&lands, PlayOrder::First),
+      observations(&on_the_draw, PlayOrder::First)
+    );
+  }
+
+  #[test]
+  fn draw_engines_find_lands() {
+    let deck = decklist!(
+      "
+      16 Swamp
+      4 Phyrexian Arena
+      40 Gray Merchant of Asphodel
+      "
+    );
+    let mulligan = Never::never();
+    let config = |deck| SimulationConfig {
+      run_count: 5000,
+      draw_count: 8,
+      mulligan: &mulligan,
+      deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(9),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let arena = card!("Phyrexian Arena");
+    let merchant = card!("Gray Merchant of Asphodel");
+    let without = Simulation::from_config(&config(&deck));
+    let mut engine_deck = deck.clone();
+    engine_deck.add_draw_engine(arena, 1, 1);
+    let with = Simulation::from_config(&config(&engine_deck));
+    let p_without = without.observations_for_card(merchant).p_mana();
+    let p_with = with.observations_for_card(merchant).p_mana();
+    assert!(p_with > p_without, "{} <= {}", p_with, p_without);
+    let emblem = [ExtraDraw {
+      turn: 1,
+      count: 1,
+      every_turn: true,
+    }];
+    let sim = Simulation::from_config(&SimulationConfig {
+      extra_draws: &emblem,
+      ..config(&engine_deck)
+    });
+    assert!(sim.hands[0].len() > with.hands[0].len());
+  }
+
+  #[test]
+  fn seeded_simulations_are_reproducible() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let simulate = |seed| {
+      Simulation::from_config(&SimulationConfig {
+        run_count: 2500,
+        draw_count: 3,
+        mulligan: &London::always(5),
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed,
+        cantrip_policy: CantripPolicy::Off,
+      })
+    };
+    let hashes = |sim: &Simulation| -> Vec<u64> {
+      sim
+        .hands
+        .iter()
+        .flat_map(|hand| hand.opening_with_draws(3).iter().map(|c| c.hash))
+        .collect()
+    };
+    let a = simulate(Some(42));
+    let b = simulate(Some(42));
+    let c = simulate(Some(43));
+    assert_eq!(a.seed, 42);
+    assert_eq!(hashes(&a), hashes(&b));
+    assert_ne!(hashes(&a), hashes(&c));
+    let card = card!("Llanowar Elves");
+    assert_eq!(
+      a.observations_for_card(card).mana,
+      b.observations_for_card(card).mana
+    );
+    // An unseeded simulation reports the seed it used, which reproduces it
+    let d = simulate(None);
+    assert_eq!(hashes(&d), hashes(&simulate(Some(d.seed))));
+  }
+
+  #[test]
+  fn wilson_interval_bounds() {
+    let (low, high) = wilson_interval(50, 100, Z_95);
+    assert!(f64::abs(low - 0.4038) < 0.001, "{}", low);
+    assert!(f64::abs(high - 0.5962) < 0.001, "{}", high);
+    let (low, high) = wilson_interval(0, 10, Z_95);
+    assert_eq!(low, 0.0);
+    assert!(high > 0.0 && high < 0.35);
+    let (low, high) = wilson_interval(10, 10, Z_95);
+    assert!(low > 0.65 && low < 1.0);
+    assert!(f64::abs(high - 1.0) < 1e-12);
+    assert_eq!(wilson_interval(0, 0, Z_95), (0.0, 1.0));
+    assert!(f64::abs(standard_error(0.5, 100) - 0.05) < 1e-12);
+  }
+
+  #[test]
+  fn adaptive_simulation_converges() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 1000,
+      draw_count: 1,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(7),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let adaptive = AdaptiveConfig {
+      max_interval_width: 0.01,
+      z: Z_95,
+      max_run_count: 100_000,
+    };
+    let sim = Simulation::from_config_adaptive(&config, &adaptive);
+    assert!(sim.hands.len() > 1000);
+    assert!(sim.hands.len() < 100_000);
+    assert_eq!(sim.hands.len() % 1000, 0);
+    assert!(sim.max_interval_width(&deck, Z_95) <= 0.01);
+    // The first batch is the same as a plain simulation with the same seed
+    let plain = Simulation::from_config(&config);
+    assert_eq!(
+      plain.accumulated_opening_hand_land_count,
+      sim.hands[..1000]
+        .iter()
+        .map(|hand| hand.count_in_opening_with_draws(0, |c| c.kind.is_land()))
+        .sum::<usize>()
+    );
+    // The run limit stops the simulation early
+    let capped = Simulation::from_config_adaptive(
+      &config,
+      &AdaptiveConfig {
+        max_interval_width: 0.0,
+        z: Z_95,
+        max_run_count: 2500,
+      },
+    );
+    assert_eq!(capped.hands.len(), 2500);
+  }
+
+  #[test]
+  fn per_card_precision() {
+    let deck = decklist!(
+      "
+      24 Ornithopter
+      4 Glorybringer
+      32 Mountain
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 1000,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(11),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let adaptive = AdaptiveConfig {
+      max_interval_width: 0.03,
+      z: Z_95,
+      max_run_count: 50_000,
+    };
+    let (sim, observations) = Simulation::from_config_per_card(&config, &adaptive);
+    assert_eq!(observations.len(), 2);
+    // Ornithopter is almost always in hand and castable, so a single batch is enough
+    let ornithopter = observations[&card!("Ornithopter").hash];
+    assert_eq!(ornithopter.total_runs, 1000);
+    // Glorybringer on turn 5 is borderline and needs more runs
+    let glorybringer = observations[&card!("Glorybringer").hash];
+    assert!(glorybringer.total_runs > 1000);
+    assert!(glorybringer.total_runs < 50_000);
+    assert!(glorybringer.interval_width(Z_95) <= 0.03);
+    assert_eq!(sim.hands.len(), glorybringer.total_runs);
+    // The observations match those of the whole simulation
+    let expected = sim.observations_for_card(card!("Glorybringer"));
+    assert_eq!(glorybringer.mana, expected.mana);
+    assert_eq!(glorybringer.play, expected.play);
   }
 
-  pub fn p_mana_given_cmc(&self) -> f64 {
-    self.mana as f64 / self.cmc as f64
+  #[test]
+  fn mulligan_keep_rates() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 5000,
+      draw_count: 0,
+      mulligan: &KeepLands::new(&[2, 3, 4], 5),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(19),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let stats = sim.mulligan_stats();
+    assert_eq!(stats.total_runs, 5000);
+    assert_eq!(stats.kept_hand_sizes.len(), 8);
+    assert_eq!(stats.kept_hand_sizes.iter().sum::<usize>(), 5000);
+    assert_eq!(stats.kept_hand_sizes[..5].iter().sum::<usize>(), 0);
+    // About 74% of seven card hands from this deck have 2 to 4 lands
+    assert!(stats.p_kept_hand_size(7) > 0.7 && stats.p_kept_hand_size(7) < 0.78);
+    assert_eq!(
+      stats.mulligans,
+      sim
+        .hands
+        .iter()
+        .map(|hand| hand.mulligan_count)
+        .sum::<usize>()
+    );
+    // Every mulligan is for 0, 1, 5, 6 or 7 lands, and 0 or 1 lands is more common
+    assert_eq!(stats.too_few_lands + stats.too_many_lands, stats.mulligans);
+    assert!(stats.p_too_few_lands() > stats.p_too_many_lands());
+    assert_eq!(
+      stats.keep_criteria + stats.missing_cards + stats.other_causes,
+      0
+    );
+    assert_eq!(MulliganStats::default().p_too_few_lands(), 0.0);
+    assert!(stats.average_kept_land_count > 2.0 && stats.average_kept_land_count < 4.0);
   }
 
-  pub fn p_play(&self) -> f64 {
-    self.play as f64 / self.total_runs as f64
+  #[test]
+  fn land_drop_miss_causes() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let misses_with = |mulligan: &KeepLands| {
+      Simulation::from_config(&SimulationConfig {
+        run_count: 5000,
+        draw_count: 4,
+        mulligan,
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: Some(13),
+        cantrip_policy: CantripPolicy::Off,
+      })
+      .land_drop_misses(6, 2)
+    };
+    let keep_all = misses_with(&KeepLands::new(&[0, 1, 2, 3, 4, 5, 6, 7], 7));
+    assert_eq!(keep_all.len(), 6);
+    for miss in &keep_all[..5] {
+      assert_eq!(miss.misses, miss.low_land_hand + miss.drew_spells);
+      assert_eq!(miss.total_runs, 5000);
+    }
+    // 4 draws on the play reach turn 5, but not turn 6
+    assert_eq!((keep_all[5].misses, keep_all[5].total_runs), (0, 0));
+    // Missing the first land drop means the opening hand had no lands
+    assert!(keep_all[0].misses > 0);
+    assert_eq!(keep_all[0].drew_spells, 0);
+    assert!(keep_all[3].drew_spells > 0);
+    // Mulliganing hands with fewer than two lands removes that cause of misses
+    let keep_two = misses_with(&KeepLands::new(&[2, 3, 4, 5], 5));
+    assert!(keep_two[2].low_land_hand < keep_all[2].low_land_hand);
+    assert!(keep_two[2].misses < keep_all[2].misses);
   }
-}
 
-impl Simulation {
-  pub fn from_config<M: Mulligan>(config: &SimulationConfig<M>) -> Self {
-    assert!(config.run_count > 0);
-    let mut rng = SmallRng::from_entropy();
-    let deck = config.deck.flatten();
-    let hands: Vec<_> = (0..config.run_count)
-      .map(|_| Hand::from_mulligan(config.mulligan, &mut rng, &deck, config.draw_count))
-      .collect();
-    let accumulated_opening_hand_size =
-      hands.iter().map(|hand| hand.opening().len()).sum::<usize>();
-    let accumulated_opening_hand_land_count = hands
-      .iter()
-      .map(|hand| hand.count_in_opening_with_draws(0, |c| c.kind.is_land()))
-      .sum::<usize>();
-    Simulation {
-      hands,
-      accumulated_opening_hand_size,
-      accumulated_opening_hand_land_count,
-      on_the_play: config.on_the_play,
+  #[test]
+  fn tempo_missed_land_drops_and_stumbles() {
+    let simulate = |list: &str, draw_count: usize| {
+      Simulation::from_config(&SimulationConfig {
+        run_count: 200,
+        draw_count,
+        mulligan: &Never::never(),
+        deck: &decklist!(list),
+        on_the_play: true,
+        extra_draws: &[],
+        seed: Some(6),
+        cantrip_policy: CantripPolicy::Off,
+      })
+    };
+    // Lands every turn and nothing to cast
+    let report = simulate("60 Island", 4).tempo(2..=5);
+    assert_eq!(report.turns.len(), 4);
+    assert_eq!(report.runs_missing_a_land_drop, 0);
+    assert_eq!(report.runs_stumbling, 200);
+    for tempo in &report.turns {
+      assert_eq!(tempo.total_runs, 200);
+      assert_eq!(tempo.p_stumble(), 1.0);
+      assert_eq!(tempo.average_mana_wasted(), tempo.turn as f64);
+    }
+    // Spells every turn and no lands to cast them
+    let report = simulate("60 Opt", 4).tempo(1..=3);
+    assert_eq!(report.turns[2].p_missed_land_drop(), 1.0);
+    assert_eq!(report.turns[2].mana_wasted, 0);
+    // One land and a one mana spell to cast every turn
+    let report = simulate("4 Island\n1 Opt", 0).tempo(1..=1);
+    assert!(report.turns[0].stumbles < report.turns[0].total_runs);
+    // Runs only count on the turns their draws reach
+    let report = simulate("30 Island\n30 Opt", 1).tempo(2..=3);
+    assert_eq!(report.turns[0].total_runs, 200);
+    assert_eq!(report.turns[1].total_runs, 0);
+    // The missed land drops are those of `land_drop_misses`
+    let sim = simulate("24 Island\n36 Opt", 4);
+    let report = sim.tempo(2..=5);
+    let misses = sim.land_drop_misses(5, 2);
+    for tempo in &report.turns {
+      let miss = &misses[tempo.turn - 1];
+      assert_eq!(tempo.missed_land_drops, miss.misses);
+      assert_eq!(tempo.total_runs, miss.total_runs);
     }
   }
 
-  pub fn observations_for_card(&self, card: &Card) -> Observations {
-    self.observations_for_card_by_turn(card, card.turn as usize)
+  #[test]
+  fn castable_spells_by_turn() {
+    let deck = decklist!(
+      "
+      30 Island
+      30 Opt
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 1000,
+      draw_count: 3,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(8),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let castable = sim.castable_spells(3);
+    assert_eq!(castable.len(), 3);
+    for spells in &castable {
+      assert_eq!(spells.runs_by_count.iter().sum::<usize>(), 1000);
+      let p_total: f64 = (0..spells.runs_by_count.len())
+        .map(|count| spells.p_count(count))
+        .sum();
+      assert!(f64::abs(p_total - 1.0) < 0.0001);
+    }
+    // Every card costs one mana, so the spells in hand are castable once there is a land
+    let no_lands = sim
+      .hands
+      .iter()
+      .filter(|hand| hand.count_in_opening_with_draws(0, |c| c.kind.is_land()) == 0)
+      .count();
+    assert!(castable[0].runs_by_count[0] >= no_lands);
+    assert!(castable[2].average_count() > castable[0].average_count());
+    // Vraska's Contempt is never castable before turn 4
+    let deck = decklist!(
+      "
+      30 Swamp
+      30 Vraska's Contempt
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 3,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(8),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let castable = sim.castable_spells(5);
+    assert_eq!(castable[2].runs_by_count, vec![100]);
+    assert!(castable[3].average_count() > 0.0);
+    // 3 draws on the play reach turn 4, but not turn 5
+    assert_eq!(castable[4].total_runs, 0);
+    assert!(castable[4].runs_by_count.is_empty());
   }
 
-  pub fn observations_for_card_by_turn(&self, card: &Card, turn: usize) -> Observations {
-    let mut observations = Observations::new();
-    observations.total_runs = self.hands.len();
-    let mut scratch = Scratch::new(30, 10);
-    let play_order = if self.on_the_play {
-      PlayOrder::First
-    } else {
-      PlayOrder::Second
-    };
-    'next_hand: for hand in &self.hands {
-      // Check all potential mana costs of a card
-      let mut result = AutoTapResult::new();
-      for mana_cost in &card.all_mana_costs {
-        // NOTE Do not mutate observations in this loop
-        let goal = SimCard {
-          hash: card.hash,
-          mana_cost: *mana_cost,
-          kind: card.kind,
-        };
-        result = hand.auto_tap_with_scratch(&goal, turn, play_order, &mut scratch);
-        if result.paid {
-          break;
-        }
-      }
-      if result.in_opening_hand {
-        observations.in_opening_hand += 1;
-      }
-      if !result.cmc {
-        continue 'next_hand;
-      }
-      // Did we make it this far? Count a CMC lands on curve event
-      observations.cmc += 1;
-      // Can we pay? Count a mana on curve event
-      if result.paid {
-        observations.mana += 1;
-        // Was the card in question in our initial hand? Did we draw it on curve?
-        if result.in_opening_hand || result.in_draw_hand {
-          observations.play += 1;
-        }
+  #[test]
+  fn goldfish_traces_a_game() {
+    let deck = decklist!(
+      "
+      24 Island
+      30 Opt
+      6 Vraska's Contempt
+      "
+    );
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 1,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: false,
+      extra_draws: &[],
+      seed: Some(3),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let mut goldfish = sim.goldfish(&deck, 0);
+    assert_eq!(goldfish.opening_hand().len(), 7);
+    let traces: Vec<TurnTrace> = goldfish.by_ref().collect();
+    // On the draw, 5 draws cover turns 1 through 5
+    assert_eq!(traces.len(), 5);
+    let mut lands_seen = goldfish
+      .opening_hand()
+      .iter()
+      .filter(|name| *name == "Island")
+      .count();
+    for (i, trace) in traces.iter().enumerate() {
+      assert_eq!(trace.turn, i + 1);
+      assert_eq!(trace.drawn.len(), 1);
+      lands_seen += trace.drawn.iter().filter(|name| *name == "Island").count();
+      assert_eq!(trace.lands_played, std::cmp::min(lands_seen, trace.turn));
+      assert_eq!(trace.mana_available, trace.lands_played);
+      // Opt only needs an Island, Vraska's Contempt also needs black mana
+      assert!(trace.castable.iter().all(|name| name == "Opt"));
+      if trace.lands_played > 0 {
+        let opt_in_hand = goldfish.opening_hand().contains(&"Opt".to_string())
+          || traces[..=i]
+            .iter()
+            .any(|trace| trace.drawn.contains(&"Opt".to_string()));
+        assert_eq!(!trace.castable.is_empty(), opt_in_hand);
       }
     }
-    assert!(observations.mana <= observations.cmc);
-    observations
+    // The same seed traces the same game
+    let again = Simulation::from_config(&SimulationConfig {
+      run_count: 1,
+      draw_count: 5,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: false,
+      extra_draws: &[],
+      seed: Some(3),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    let again: Vec<TurnTrace> = again.goldfish(&deck, 0).collect();
+    assert_eq!(again[4].drawn, traces[4].drawn);
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use crate::deck::*;
-  use crate::mulligan::Never;
-  use crate::simulation::*;
+  #[test]
+  fn simulate_many_pairs_runs() {
+    let decks = vec![
+      decklist!(
+        "
+        24 Island
+        36 Opt
+        "
+      ),
+      decklist!(
+        "
+        25 Island
+        35 Opt
+        "
+      ),
+    ];
+    let sims = simulate_many(
+      &decks,
+      &SimulationConfig {
+        run_count: 500,
+        draw_count: 3,
+        mulligan: &Never::never(),
+        deck: &decks[0],
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      },
+    );
+    assert_eq!(sims.len(), 2);
+    assert_eq!(sims[0].seed, sims[1].seed);
+    // The shuffles are the same, so the extra Island only ever adds lands to a hand
+    let lands = |hand: &Hand| hand.count_in_opening_with_draws(3, |c| c.kind.is_land());
+    for (fewer, more) in sims[0].hands.iter().zip(&sims[1].hands) {
+      assert!(lands(more) >= lands(fewer));
+    }
+  }
 
   #[test]
-  fn deck_with_not_enough_cards_should_not_panic() {
-    let code = "
-      1 Discovery // Dispersal (GRN) 223
-      1 Unmoored Ego (GRN) 212
-      3 Vraska's Contempt (XLN) 129
-      1 Watery Grave (GRN) 259
-    ";
-    let deck = decklist!(code);
-    Simulation::from_config(&SimulationConfig {
-      run_count: 10,
-      draw_count: 10,
+  fn combo_pieces_by_turn() {
+    let deck = decklist!(
+      "
+      4 Opt
+      4 Shock
+      4 Lightning Strike [tutor]
+      24 Island
+      24 Mountain
+      "
+    );
+    let pieces = [card!("Opt"), card!("Shock")];
+    let exact = deck.combo_draws(&pieces, "none");
+    let with_tutors = deck.combo_draws(&pieces, "tutor");
+    assert_eq!(exact.p_assembled.len(), 54);
+    assert_eq!(*exact.p_assembled.last().unwrap(), 1.0);
+    for (p, p_tutors) in exact.p_assembled.iter().zip(&with_tutors.p_assembled) {
+      assert!(p <= p_tutors);
+    }
+    assert!(with_tutors.expected_draws.unwrap() < exact.expected_draws.unwrap());
+    // A missing piece can never be drawn
+    let missing = deck.combo_draws(&[card!("Opt"), card!("Duress")], "none");
+    assert_eq!(missing.expected_draws, None);
+    // The simulation agrees with the exact probabilities without a mulligan
+    let sim = Simulation::from_config(&SimulationConfig {
+      run_count: 20000,
+      draw_count: 4,
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: Some(2),
+      cantrip_policy: CantripPolicy::Off,
     });
+    let simulated = sim.p_combo_by_turn(&pieces, &[], 5);
+    let simulated_tutors = sim.p_combo_by_turn(&pieces, &deck.cards_with_tag("tutor"), 5);
+    for turn in 1..=5 {
+      // On the play, turn N sees N - 1 draws
+      let draws = turn - 1;
+      assert!(f64::abs(simulated[turn - 1] - exact.p_assembled[draws]) < 0.015);
+      assert!(f64::abs(simulated_tutors[turn - 1] - with_tutors.p_assembled[draws]) < 0.015);
+    }
   }
 
   #[test]
-  fn deck_with_single_zero_mana_card() {
-    let card = card!("Ornithopter");
-    let deck = Deck::from_cards(vec![card.clone()]);
-    let runs = 10;
-    let draws = 0;
+  fn report_tag_groups() {
+    let deck = decklist!(
+      "
+      4 Shock [removal]
+      4 Lightning Strike [removal]
+      4 Glorybringer [wincons]
+      24 Mountain
+      24 Opt
+      "
+    );
     let sim = Simulation::from_config(&SimulationConfig {
-      run_count: runs,
-      draw_count: draws,
+      run_count: 5000,
+      draw_count: 5,
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: Some(3),
+      cantrip_policy: CantripPolicy::Off,
     });
-    let obs = sim.observations_for_card(&card);
-    assert_eq!(obs.cmc, runs);
-    assert_eq!(obs.mana, runs);
-    assert_eq!(obs.play, runs);
+    let report = sim.report(&deck, &[("removal", 2), ("wincons", 5), ("ramp", 2)]);
+    assert_eq!(report.groups.len(), 2);
+    let removal = &report.groups[0];
+    assert_eq!(removal.tag, "removal");
+    assert_eq!(removal.card_names.len(), 2);
+    // Every removal spell is castable exactly when Lightning Strike is
+    let strike = sim.observations_for_card_by_turn(card!("Lightning Strike"), 2);
+    assert_eq!(removal.observations.mana, strike.mana);
+    assert_eq!(removal.observations.cmc, strike.cmc);
+    // Any removal spell is more likely to be in hand than either one
+    let shock = sim.observations_for_card_by_turn(card!("Shock"), 2);
+    assert!(removal.observations.play > shock.play);
+    assert!(removal.observations.play > strike.play);
+    let wincons = &report.groups[1];
+    let glorybringer = sim.observations_for_card_by_turn(card!("Glorybringer"), 5);
+    assert_eq!(wincons.observations.mana, glorybringer.mana);
+    assert_eq!(wincons.observations.play, glorybringer.play);
+  }
+
+  #[derive(Default)]
+  struct LandCountObserver {
+    started: usize,
+    runs: Vec<usize>,
+    land_counts: Vec<usize>,
+    completed_hands: usize,
+  }
+
+  impl SimulationObserver for LandCountObserver {
+    fn on_start(&mut self, run_count: usize) {
+      self.started = run_count;
+    }
+    fn on_run(&mut self, run: usize, hand: &Hand) {
+      self.runs.push(run);
+      self
+        .land_counts
+        .push(hand.count_in_opening_with_draws(0, |c| c.kind.is_land()));
+    }
+    fn on_complete(&mut self, simulation: &Simulation) {
+      self.completed_hands = simulation.hands.len();
+    }
   }
 
   #[test]
-  fn small_deck_1() {
+  fn simulation_observer_events() {
+    let deck = decklist!(
+      "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let mut observer = LandCountObserver::default();
+    let sim = Simulation::from_config_with_observer(
+      &SimulationConfig {
+        run_count: 50,
+        draw_count: 0,
+        mulligan: &Never::never(),
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      },
+      &mut observer,
+    );
+    assert_eq!(observer.started, 50);
+    assert_eq!(observer.runs, (0..50).collect::<Vec<_>>());
+    assert_eq!(observer.completed_hands, 50);
+    assert_eq!(
+      observer.land_counts.iter().sum::<usize>(),
+      sim.accumulated_opening_hand_land_count
+    );
+  }
+
+  #[test]
+  fn simulation_progress_and_cancellation() {
     let deck = decklist!(
       "
+      24 Forest
+      36 Llanowar Elves
+      "
+    );
+    let config = SimulationConfig {
+      run_count: 3 * PROGRESS_BATCH_SIZE - 1,
+      draw_count: 0,
+      mulligan: &Never::never(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(4),
+      cantrip_policy: CantripPolicy::Off,
+    };
+    let mut reports = Vec::new();
+    let mut progress = |completed: usize, total: usize, partial: &Simulation| {
+      assert_eq!(partial.hands.len(), completed);
+      reports.push((completed, total));
+    };
+    let sim =
+      Simulation::from_config_with_progress(&config, &mut progress, &CancellationToken::new())
+        .unwrap();
+    let total = config.run_count;
+    assert_eq!(
+      reports,
+      vec![
+        (PROGRESS_BATCH_SIZE, total),
+        (2 * PROGRESS_BATCH_SIZE, total),
+        (total, total)
+      ]
+    );
+    assert_eq!(
+      sim.accumulated_opening_hand_land_count,
+      Simulation::from_config(&config).accumulated_opening_hand_land_count
+    );
+    // Cancel from the progress sink after the first batch
+    let cancel = CancellationToken::new();
+    let mut progress = |_: usize, _: usize, _: &Simulation| cancel.cancel();
+    let Cancelled(partial) =
+      Simulation::from_config_with_progress(&config, &mut progress, &cancel.clone()).unwrap_err();
+    assert_eq!(partial.hands.len(), PROGRESS_BATCH_SIZE);
+  }
+
+  #[test]
+  fn small_deck_mana_source_0() {
+    let mut deck = decklist!(
+      "
     1 Llanowar Elves
-    6 Forest
+    1 Forest
+    1 Grizzly Bears
     "
     );
     let runs = 10;
     let draws = 0;
-    let sim = Simulation::from_config(&SimulationConfig {
-      run_count: runs,
-      draw_count: draws,
-      mulligan: &Never::never(),
-      deck: &deck,
-      on_the_play: true,
-    });
-    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
-    assert_eq!(obs.cmc, runs);
-    assert_eq!(obs.mana, runs);
-    assert_eq!(obs.play, runs);
+    let bears = card!("Grizzly Bears");
+    {
+      let sim = Simulation::from_config(&SimulationConfig {
+        run_count: runs,
+        draw_count: draws,
+        mulligan: &Never::never(),
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      });
+      let obs = sim.observations_for_card(bears);
+      assert_eq!(obs.cmc, 0);
+      assert_eq!(obs.mana, 0);
+    }
+    deck.add_mana_source(
+      card!("Llanowar Elves"),
+      ManaCost::from_rgbuwc(0, 1, 0, 0, 0, 0),
+      1,
+    );
+    {
+      let sim = Simulation::from_config(&SimulationConfig {
+        run_count: runs,
+        draw_count: draws,
+        mulligan: &Never::never(),
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      });
+      let obs = sim.observations_for_card(bears);
+      assert_eq!(obs.cmc, runs);
+      assert_eq!(obs.mana, runs);
+      assert_eq!(obs.play, runs);
+    }
   }
 
-  // on the draw, always draw all 8 cards
+  // A summoning sick mana source can not pay for a card on turn 1
   #[test]
-  fn small_deck_2() {
-    let deck = decklist!(
+  fn small_deck_mana_source_1() {
+    let mut deck = decklist!(
       "
     1 Llanowar Elves
-    7 Forest
+    1 Savannah Lions
     "
     );
-    let draws = 1;
+    deck.add_mana_source(
+      card!("Llanowar Elves"),
+      ManaCost::from_rgbuwc(1, 1, 1, 1, 1, 0),
+      1,
+    );
     let runs = 10;
     let sim = Simulation::from_config(&SimulationConfig {
       run_count: runs,
-      draw_count: draws,
+      draw_count: 0,
       mulligan: &Never::never(),
       deck: &deck,
-      on_the_play: false,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
-    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
-    assert_eq!(obs.cmc, runs);
-    assert_eq!(obs.mana, runs);
-    assert_eq!(obs.play, runs);
+    let obs = sim.observations_for_card(card!("Savannah Lions"));
+    assert_eq!(obs.cmc, 0);
+    assert_eq!(obs.mana, 0);
   }
 
+  // A mana source without delay can pay for a card the turn it is drawn
   #[test]
-  fn small_deck_3() {
-    let deck = decklist!(
+  fn small_deck_mana_source_2() {
+    let mut deck = decklist!(
       "
-    6 Llanowar Elves
-    1 Forest
+    1 Lotus Petal
+    1 Savannah Lions
     "
     );
-    let draws = 1;
+    deck.add_mana_source(
+      card!("Lotus Petal"),
+      ManaCost::from_rgbuwc(1, 1, 1, 1, 1, 0),
+      0,
+    );
     let runs = 10;
     let sim = Simulation::from_config(&SimulationConfig {
       run_count: runs,
-      draw_count: draws,
+      draw_count: 0,
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
-    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    let obs = sim.observations_for_card(card!("Savannah Lions"));
     assert_eq!(obs.cmc, runs);
     assert_eq!(obs.mana, runs);
-    assert_eq!(obs.play, runs);
-    assert_eq!(obs.in_opening_hand, runs);
   }
 
-  // on the draw
   #[test]
-  fn small_deck_4() {
-    let deck_list = "
-    7 Llanowar Elves
-    1 Forest
-    ";
-    let deck = decklist!(deck_list);
-    let draws = 1;
+  fn commander_in_command_zone() {
+    let mut deck = decklist!(
+      "
+    10 Forest
+    "
+    );
+    let commander = card!("Omnath, Locus of Mana");
+    deck.commanders.push(commander.clone());
     let runs = 10;
     let sim = Simulation::from_config(&SimulationConfig {
       run_count: runs,
-      draw_count: draws,
-      mulligan: &Never::never(),
+      draw_count: commander.turn as usize,
+      mulligan: &London::commander(),
       deck: &deck,
-      on_the_play: false,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
-    let obs = sim.observations_for_card(&card!("Llanowar Elves"));
+    let obs = sim.observations_for_card(commander);
+    assert_eq!(obs.in_opening_hand, 0);
     assert_eq!(obs.cmc, runs);
     assert_eq!(obs.mana, runs);
     assert_eq!(obs.play, runs);
-    assert_eq!(obs.in_opening_hand, runs);
   }
 
   #[test]
@@ -272,6 +2466,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(&card);
     assert_eq!(obs.cmc, runs);
@@ -293,6 +2490,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(&card);
     assert_eq!(obs.cmc, runs);
@@ -315,6 +2515,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let o = sim.observations_for_card(card!("Integrity"));
     assert!(o.mana == o.cmc);
@@ -356,6 +2559,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card!("Opt"));
     let actual = obs.p_mana();
@@ -398,6 +2604,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card!("Opt"));
     let actual = obs.p_mana();
@@ -423,6 +2632,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card!("History of Benalia"));
     let actual = obs.p_mana();
@@ -449,6 +2661,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card!("Jadelight Ranger"));
     let actual = obs.p_mana();
@@ -478,6 +2693,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.mana, runs);
@@ -506,6 +2724,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.mana, runs);
@@ -534,6 +2755,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.mana, runs);
@@ -562,6 +2786,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.mana, runs);
@@ -590,6 +2817,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.mana, runs);
@@ -614,6 +2844,9 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     assert_eq!(obs.cmc, runs);
@@ -652,9 +2885,47 @@ mod tests {
       mulligan: &Never::never(),
       deck: &deck,
       on_the_play: true,
+      extra_draws: &[],
+      seed: None,
+      cantrip_policy: CantripPolicy::Off,
     });
     let obs = sim.observations_for_card(card);
     dbg!(obs);
     assert_eq!(obs.mana, 0);
   }
+
+  #[test]
+  fn simulation_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SimulationConfig<London>>();
+    assert_send_sync::<SimulationConfig<Never>>();
+    assert_send_sync::<Simulation>();
+    assert_send_sync::<Observations>();
+  }
+
+  #[test]
+  fn simulation_on_worker_thread() {
+    let deck = decklist!(
+      "
+    1 Llanowar Elves
+    6 Forest
+    "
+    );
+    let runs = 10;
+    let handle = std::thread::spawn(move || {
+      let sim = Simulation::from_config(&SimulationConfig {
+        run_count: runs,
+        draw_count: 0,
+        mulligan: &Never::never(),
+        deck: &deck,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      });
+      sim.observations_for_card(card!("Llanowar Elves"))
+    });
+    let obs = handle.join().expect("worker thread panicked");
+    assert_eq!(obs.mana, runs);
+  }
 }
@@ -0,0 +1,225 @@
+//! # Collection history
+//!
+//! Persists the owned cards and wildcards of a collection as timestamped snapshots in a JSON
+//! file, so that trackers can merge each new log parse into the history and answer
+//! questions like "which cards were acquired this week" or "how many wildcards were spent
+//! this month". Timestamps are Unix times in seconds.
+use crate::deck::Deck;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The number of seconds in a day
+pub const DAY: i64 = 24 * 60 * 60;
+
+/// The number of seconds in a week
+pub const WEEK: i64 = 7 * DAY;
+
+/// Wildcards are the wildcards owned by rarity
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wildcards {
+  pub common: usize,
+  pub uncommon: usize,
+  pub rare: usize,
+  pub mythic: usize,
+}
+
+impl Wildcards {
+  /// Returns the wildcards of each rarity that `self` has and `later` doesn't
+  fn decrease(self, later: Self) -> Self {
+    Self {
+      common: self.common.saturating_sub(later.common),
+      uncommon: self.uncommon.saturating_sub(later.uncommon),
+      rare: self.rare.saturating_sub(later.rare),
+      mythic: self.mythic.saturating_sub(later.mythic),
+    }
+  }
+
+  fn plus(self, other: Self) -> Self {
+    Self {
+      common: self.common + other.common,
+      uncommon: self.uncommon + other.uncommon,
+      rare: self.rare + other.rare,
+      mythic: self.mythic + other.mythic,
+    }
+  }
+}
+
+/// Snapshot is the collection at a point in time
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+  pub timestamp: i64,
+  /// The number of owned copies keyed by card name
+  pub cards: BTreeMap<String, usize>,
+  #[serde(default)]
+  pub wildcards: Wildcards,
+}
+
+impl Snapshot {
+  /// Returns the snapshot of the `owned` cards and `wildcards` at `timestamp`
+  pub fn from_owned(timestamp: i64, owned: &Deck, wildcards: Wildcards) -> Self {
+    Self {
+      timestamp,
+      cards: owned
+        .cards
+        .iter()
+        .map(|cc| (cc.card.name.clone(), cc.count))
+        .collect(),
+      wildcards,
+    }
+  }
+}
+
+/// CollectionHistory is the snapshots of a collection, oldest first
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CollectionHistory {
+  pub snapshots: Vec<Snapshot>,
+}
+
+impl CollectionHistory {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the history saved at `path`, or an empty history if there is no file
+  pub fn load(path: &Path) -> Result<Self, String> {
+    if !path.exists() {
+      return Ok(Self::new());
+    }
+    let json = std::fs::read_to_string(path)
+      .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+  }
+
+  /// Saves the history to `path`. The file is written next to `path` first and then
+  /// renamed, so an interrupted save keeps the previous history
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, json)
+      .map_err(|e| format!("Error writing {}: {}", partial.display(), e))?;
+    std::fs::rename(&partial, path).map_err(|e| format!("Error writing {}: {}", path.display(), e))
+  }
+
+  /// Adds `snapshot` to the history, replacing any snapshot with the same timestamp
+  pub fn record(&mut self, snapshot: Snapshot) {
+    match self
+      .snapshots
+      .binary_search_by_key(&snapshot.timestamp, |s| s.timestamp)
+    {
+      Ok(i) => self.snapshots[i] = snapshot,
+      Err(i) => self.snapshots.insert(i, snapshot),
+    }
+  }
+
+  /// Merges a log parse at `timestamp` into the history. Log parses can miss cards, so the
+  /// cards of the snapshot before `timestamp` that `owned` doesn't list keep their counts
+  pub fn merge(&mut self, timestamp: i64, owned: &Deck, wildcards: Wildcards) {
+    let mut snapshot = Snapshot::from_owned(timestamp, owned, wildcards);
+    if let Some(previous) = self.at(timestamp) {
+      for (name, count) in &previous.cards {
+        snapshot.cards.entry(name.clone()).or_insert(*count);
+      }
+    }
+    self.record(snapshot);
+  }
+
+  /// Returns the latest snapshot
+  pub fn latest(&self) -> Option<&Snapshot> {
+    self.snapshots.last()
+  }
+
+  /// Returns the latest snapshot on or before `timestamp`
+  pub fn at(&self, timestamp: i64) -> Option<&Snapshot> {
+    self
+      .snapshots
+      .iter()
+      .take_while(|snapshot| snapshot.timestamp <= timestamp)
+      .last()
+  }
+
+  /// Returns the copies of each card acquired between the collection at `from` and the
+  /// collection at `to`, keyed by card name. Every card of `to` is acquired if there is no
+  /// snapshot at `from`
+  pub fn acquired(&self, from: i64, to: i64) -> BTreeMap<String, usize> {
+    let empty = BTreeMap::new();
+    let before = self.at(from).map_or(&empty, |snapshot| &snapshot.cards);
+    let after = self.at(to).map_or(&empty, |snapshot| &snapshot.cards);
+    after
+      .iter()
+      .filter_map(|(name, count)| {
+        let acquired = count.saturating_sub(*before.get(name).unwrap_or(&0));
+        if acquired > 0 {
+          Some((name.clone(), acquired))
+        } else {
+          None
+        }
+      })
+      .collect()
+  }
+
+  /// Returns the wildcards spent between `from` and `to`, the sum of the decreases between
+  /// consecutive snapshots. Wildcards opened in between don't offset the ones spent
+  pub fn wildcards_spent(&self, from: i64, to: i64) -> Wildcards {
+    let mut spent = Wildcards::default();
+    let mut previous = self.at(from).map(|snapshot| snapshot.wildcards);
+    for snapshot in self
+      .snapshots
+      .iter()
+      .filter(|snapshot| snapshot.timestamp > from && snapshot.timestamp <= to)
+    {
+      if let Some(previous) = previous {
+        spent = spent.plus(previous.decrease(snapshot.wildcards));
+      }
+      previous = Some(snapshot.wildcards);
+    }
+    spent
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::store::*;
+
+  fn wildcards(rare: usize, mythic: usize) -> Wildcards {
+    Wildcards {
+      common: 10,
+      uncommon: 10,
+      rare,
+      mythic,
+    }
+  }
+
+  #[test]
+  fn collection_history_queries() {
+    let mut history = CollectionHistory::new();
+    history.merge(0, &decklist!("4 Opt\n1 Thoughtseize"), wildcards(6, 2));
+    // The second parse misses Opt, which keeps its count
+    history.merge(2 * DAY, &decklist!("4 Thoughtseize"), wildcards(3, 2));
+    history.merge(9 * DAY, &decklist!("2 Shock"), wildcards(5, 1));
+    assert_eq!(history.snapshots.len(), 3);
+    assert_eq!(history.latest().unwrap().cards.len(), 3);
+    assert_eq!(history.at(DAY).unwrap().timestamp, 0);
+    let acquired = history.acquired(0, WEEK);
+    assert_eq!(acquired.len(), 1);
+    assert_eq!(acquired["Thoughtseize"], 3);
+    let acquired = history.acquired(WEEK, 2 * WEEK);
+    assert_eq!(acquired.keys().collect::<Vec<_>>(), vec!["Shock"]);
+    assert_eq!(history.acquired(-DAY, 0)["Opt"], 4);
+    // 3 rares spent on day 2, 1 mythic on day 9, and the rares opened on day 9 don't count
+    let spent = history.wildcards_spent(0, 30 * DAY);
+    assert_eq!((spent.rare, spent.mythic, spent.common), (3, 1, 0));
+    assert_eq!(history.wildcards_spent(WEEK, 30 * DAY).rare, 0);
+  }
+
+  #[test]
+  fn collection_history_save_and_load() {
+    let path = std::env::temp_dir().join(format!("landlord-history-{}.json", std::process::id()));
+    assert!(CollectionHistory::load(&path).unwrap().snapshots.is_empty());
+    let mut history = CollectionHistory::new();
+    history.merge(DAY, &decklist!("4 Opt"), wildcards(1, 0));
+    history.save(&path).unwrap();
+    let loaded = CollectionHistory::load(&path).unwrap();
+    assert_eq!(loaded.snapshots, history.snapshots);
+    std::fs::remove_file(&path).unwrap();
+  }
+}
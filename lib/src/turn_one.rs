@@ -0,0 +1,178 @@
+//! # Turn one play preset
+//!
+//! Answers the narrow but common question "how often can I cast this card on turn one?",
+//! e.g. a Thoughtseize deck that needs an untapped black source, and reports which
+//! single land swaps would raise that probability.
+use crate::card::Card;
+use crate::data::ALL_CARDS;
+use crate::deck::Deck;
+use crate::mulligan::Mulligan;
+use crate::simulation::{standard_error, Observations, Simulation, SimulationConfig};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+const BASIC_LAND_NAMES: [&str; 5] = ["Plains", "Island", "Swamp", "Mountain", "Forest"];
+
+/// LandSwap describes replacing one copy of the `remove` land with one copy of the `add` land
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LandSwap {
+  pub remove: String,
+  pub add: String,
+  /// The probability to have the mana for the card on turn one after the swap
+  pub p_mana: f64,
+}
+
+/// TurnOneReport is the result of a turn one play simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnOneReport {
+  /// The probability to have the mana for the card on turn one
+  pub p_mana: f64,
+  /// The probability to have both the card and the mana for it on turn one
+  pub p_play: f64,
+  /// Land swaps that raise `p_mana` beyond the simulation noise, best first
+  pub swaps: Vec<LandSwap>,
+}
+
+/// Returns how often `card` can be cast on turn one with the deck in `config`,
+/// along with the single land swaps that would raise that probability
+///
+/// Each candidate swap replaces one copy of a land in the deck with one copy of a
+/// different land in the deck or a basic land, and is simulated with the same
+/// `config` and seed, so that differences come from the lands rather than from the
+/// shuffles. Swaps are only reported when they beat the baseline by more than three
+/// standard errors, so larger `run_count` values surface smaller improvements.
+pub fn turn_one_play<M: Mulligan + Sync>(
+  config: &SimulationConfig<M>,
+  card: &Card,
+) -> TurnOneReport {
+  let seed = config
+    .seed
+    .unwrap_or_else(|| SmallRng::from_entropy().gen());
+  let turn_one = |deck: &Deck| -> Observations {
+    let sim = Simulation::from_config(&SimulationConfig {
+      deck,
+      seed: Some(seed),
+      ..*config
+    });
+    sim.observations_for_card_by_turn(card, 1)
+  };
+
+  let observations = turn_one(config.deck);
+  let p_mana = observations.p_mana();
+  let standard_error = standard_error(p_mana, config.run_count);
+
+  let lands: Vec<&Card> = config
+    .deck
+    .cards
+    .iter()
+    .filter(|cc| cc.card.is_land())
+    .map(|cc| &cc.card)
+    .collect();
+  let mut candidates = lands.clone();
+  for name in &BASIC_LAND_NAMES {
+    if let Some(basic) = ALL_CARDS.card_from_name(name) {
+      if !candidates.iter().any(|c| c.hash == basic.hash) {
+        candidates.push(basic);
+      }
+    }
+  }
+
+  let mut swaps = Vec::new();
+  for remove in &lands {
+    for add in &candidates {
+      if remove.hash == add.hash {
+        continue;
+      }
+      let p = turn_one(&swap_land(config.deck, remove, add)).p_mana();
+      if p > p_mana + 3.0 * standard_error {
+        swaps.push(LandSwap {
+          remove: remove.name.clone(),
+          add: add.name.clone(),
+          p_mana: p,
+        });
+      }
+    }
+  }
+  swaps.sort_by(|a, b| b.p_mana.partial_cmp(&a.p_mana).unwrap());
+
+  TurnOneReport {
+    p_mana,
+    p_play: observations.p_play(),
+    swaps,
+  }
+}
+
+/// Returns a copy of `deck` with one copy of `remove` replaced by one copy of `add`
+fn swap_land(deck: &Deck, remove: &Card, add: &Card) -> Deck {
+  let mut deck = deck.clone();
+  deck.remove_count(remove, 1);
+  deck.insert_count(add, 1);
+  deck
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::London;
+  use crate::simulation::*;
+  use crate::turn_one::*;
+
+  #[test]
+  fn turn_one_thoughtseize() {
+    let deck = decklist!(
+      "
+      4 Thoughtseize
+      12 Swamp
+      12 Island
+      32 Opt
+      "
+    );
+    let mulligan = London::never();
+    let report = turn_one_play(
+      &SimulationConfig {
+        run_count: 20000,
+        draw_count: 0,
+        deck: &deck,
+        mulligan: &mulligan,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      },
+      card!("Thoughtseize"),
+    );
+    // P(at least one of 12 Swamps in 7 cards from 60) ~ 0.81
+    assert!(report.p_mana > 0.75 && report.p_mana < 0.87);
+    assert!(report.p_play <= report.p_mana);
+    let best = &report.swaps[0];
+    assert_eq!(best.remove, "Island");
+    assert_eq!(best.add, "Swamp");
+    assert!(best.p_mana > report.p_mana);
+  }
+
+  #[test]
+  fn turn_one_no_swaps_when_every_land_works() {
+    let deck = decklist!(
+      "
+      4 Thoughtseize
+      24 Swamp
+      32 Opt
+      "
+    );
+    let mulligan = London::never();
+    let report = turn_one_play(
+      &SimulationConfig {
+        run_count: 10000,
+        draw_count: 0,
+        deck: &deck,
+        mulligan: &mulligan,
+        on_the_play: true,
+        extra_draws: &[],
+        seed: None,
+        cantrip_policy: CantripPolicy::Off,
+      },
+      card!("Thoughtseize"),
+    );
+    assert!(report.swaps.is_empty());
+  }
+}
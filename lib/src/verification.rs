@@ -0,0 +1,289 @@
+//! # Exact verification
+//!
+//! For small decks with simple manabases, where every land taps for at most one color,
+//! the probability to have the mana for a card by some turn has an exact solution: a sum of
+//! multivariate hypergeometric probabilities over the number of lands of each color seen.
+//! Comparing a simulation with these exact probabilities validates the Monte Carlo
+//! pipeline, and results that agree can be shown as verified.
+use crate::card::{Card, ManaColor, ManaCost};
+use crate::deck::Deck;
+use crate::hand::{Hand, PlayOrder};
+use crate::probability::{
+  cards_seen_by_turn, multivariate_hypergeometric_where, OPENING_HAND_SIZE,
+};
+use crate::simulation::{standard_error, Simulation};
+
+/// The size of the largest deck that is verified, the size of a limited deck
+pub const MAX_VERIFIED_DECK_SIZE: usize = 40;
+
+/// The number of standard errors a simulated probability can be from the exact probability
+/// and still agree with it
+pub const VERIFICATION_Z: f64 = 4.0;
+
+/// The colors of the land groups, followed by a group for colorless lands
+const COLORS: [ManaColor; 5] = [
+  ManaColor::White,
+  ManaColor::Blue,
+  ManaColor::Black,
+  ManaColor::Red,
+  ManaColor::Green,
+];
+
+/// The index of the colorless land group
+const COLORLESS: usize = COLORS.len();
+
+/// The index of the group of copies of the card
+const COPIES: usize = COLORS.len() + 1;
+
+/// ExactProbabilities are the exact probabilities of a card by a turn, without a mulligan
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub struct ExactProbabilities {
+  /// The probability to have the mana for the card, see `Observations::p_mana`
+  pub p_mana: f64,
+  /// The probability to have both the card and the mana for it, see `Observations::p_play`
+  pub p_play: f64,
+}
+
+/// CardVerification compares the simulated probabilities of a card on curve to the exact ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardVerification {
+  pub name: String,
+  pub turn: usize,
+  pub exact: ExactProbabilities,
+  /// The simulated probability to have the mana for the card
+  pub p_mana: f64,
+  /// The simulated probability to have both the card and the mana for it
+  pub p_play: f64,
+  /// True if both simulated probabilities are within `VERIFICATION_Z` standard errors of the
+  /// exact probabilities
+  pub agrees: bool,
+}
+
+/// Verification is the comparison of a simulation with exact probabilities, see `verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+  /// The verified nonland cards of the deck, in deck order
+  pub cards: Vec<CardVerification>,
+  /// True if every card agrees
+  pub verified: bool,
+}
+
+/// Returns the exact probabilities of `card` by `turn` for `deck`, without a mulligan, or
+/// `None` unless the card is a nonland card with a single mana cost and every land of the
+/// deck taps for at most one color
+pub fn exact_probabilities(
+  deck: &Deck,
+  card: &Card,
+  turn: usize,
+  play_order: PlayOrder,
+) -> Option<ExactProbabilities> {
+  if card.is_land() || card.all_mana_costs.len() != 1 {
+    return None;
+  }
+  let mut group_sizes = vec![0; COPIES + 1];
+  for cc in &deck.cards {
+    if cc.card.is_land() {
+      group_sizes[land_group(&cc.card.mana_cost)?] += cc.count;
+    } else if cc.card.hash == card.hash {
+      group_sizes[COPIES] += cc.count;
+    }
+  }
+  let cmc = card.mana_cost.cmc() as usize;
+  let pips: Vec<usize> = COLORS
+    .iter()
+    .map(|color| card.mana_cost.count(*color) as usize)
+    .collect();
+  let has_mana = |draws: &[usize]| {
+    draws[..COPIES].iter().sum::<usize>() >= cmc
+      && pips.iter().zip(draws).all(|(pips, lands)| lands >= pips)
+  };
+  let cards_seen = std::cmp::min(cards_seen_by_turn(turn, play_order), deck.len());
+  Some(ExactProbabilities {
+    p_mana: multivariate_hypergeometric_where(deck.len(), &group_sizes, cards_seen, has_mana),
+    p_play: multivariate_hypergeometric_where(deck.len(), &group_sizes, cards_seen, |draws| {
+      has_mana(draws) && draws[COPIES] > 0
+    }),
+  })
+}
+
+/// Returns the comparison of `sim`, which should be a simulation of `deck`, with the exact
+/// probabilities of the nonland cards of the deck on curve, or `None` if exact computation
+/// isn't feasible. It is feasible for decks of up to `MAX_VERIFIED_DECK_SIZE` cards where every
+/// land taps for at most one color, simulated without mulligans, extra draws, nonland mana
+/// sources, draw engines or commanders. Cards with several mana costs, and cards on curve
+/// after the simulated draws, are left out
+pub fn verify(sim: &Simulation, deck: &Deck) -> Option<Verification> {
+  if !is_verifiable(sim, deck) {
+    return None;
+  }
+  let play_order = if sim.on_the_play {
+    PlayOrder::First
+  } else {
+    PlayOrder::Second
+  };
+  let simulated_cards = sim.hands.iter().map(Hand::len).min().unwrap_or(0);
+  let runs = sim.hands.len();
+  let cards: Vec<CardVerification> = deck
+    .cards
+    .iter()
+    .map(|cc| &cc.card)
+    .filter(|card| !card.is_land())
+    .filter_map(|card| {
+      let turn = card.turn as usize;
+      if cards_seen_by_turn(turn, play_order) > simulated_cards {
+        return None;
+      }
+      let exact = exact_probabilities(deck, card, turn, play_order)?;
+      let observations = sim.observations_for_card_by_turn(card, turn);
+      let p_mana = observations.p_mana();
+      let p_play = observations.p_play();
+      Some(CardVerification {
+        name: card.name.clone(),
+        turn,
+        exact,
+        p_mana,
+        p_play,
+        agrees: agrees(p_mana, exact.p_mana, runs) && agrees(p_play, exact.p_play, runs),
+      })
+    })
+    .collect();
+  if cards.is_empty() {
+    return None;
+  }
+  let verified = cards.iter().all(|card| card.agrees);
+  Some(Verification { cards, verified })
+}
+
+/// Returns true if `sim` of `deck` can be compared with exact probabilities, see `verify`
+fn is_verifiable(sim: &Simulation, deck: &Deck) -> bool {
+  deck.len() <= MAX_VERIFIED_DECK_SIZE
+    && sim.extra_draws.is_empty()
+    && sim.mana_sources.is_empty()
+    && sim.draw_engines.is_empty()
+    && sim.cantrips.is_empty()
+    && sim.commanders.is_empty()
+    && sim
+      .hands
+      .iter()
+      .all(|hand| hand.mulligan_count == 0 && hand.opening_hand_size == OPENING_HAND_SIZE)
+    && deck
+      .cards
+      .iter()
+      .filter(|cc| cc.card.is_land())
+      .all(|cc| land_group(&cc.card.mana_cost).is_some())
+}
+
+/// Returns the group of a land that taps for `mana_cost`, its color or `COLORLESS`,
+/// or `None` if it taps for more than one color
+fn land_group(mana_cost: &ManaCost) -> Option<usize> {
+  let mut colors = COLORS
+    .iter()
+    .enumerate()
+    .filter(|(_, color)| mana_cost.count(**color) > 0)
+    .map(|(i, _)| i);
+  match (colors.next(), colors.next()) {
+    (None, _) => Some(COLORLESS),
+    (Some(i), None) => Some(i),
+    _ => None,
+  }
+}
+
+/// Returns true if the `simulated` probability of `runs` runs agrees with the `exact` one
+fn agrees(simulated: f64, exact: f64, runs: usize) -> bool {
+  // The margin covers rounding when the exact probability is 0 or 1
+  f64::abs(simulated - exact) <= VERIFICATION_Z * standard_error(exact, runs) + 1e-9
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::hand::CantripPolicy;
+  use crate::mulligan::{London, Never};
+  use crate::simulation::*;
+  use crate::verification::*;
+
+  fn simulate(deck: &Deck, run_count: usize) -> Simulation {
+    Simulation::from_config(&SimulationConfig {
+      run_count,
+      draw_count: 8,
+      mulligan: &Never::never(),
+      deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(21),
+      cantrip_policy: CantripPolicy::Off,
+    })
+  }
+
+  #[test]
+  fn limited_deck_is_verified() {
+    let deck = decklist!(
+      "
+      9 Island
+      8 Swamp
+      4 Opt
+      4 Vraska's Contempt
+      4 Moment of Craving
+      11 Thoughtseize
+      "
+    );
+    let verification = verify(&simulate(&deck, 20000), &deck).expect("a verifiable deck");
+    assert_eq!(verification.cards.len(), 4);
+    assert!(verification.verified, "{:#?}", verification);
+    // 9 Islands in 40 cards, 7 cards seen by turn 1 on the play
+    let card = |name: &str| {
+      verification
+        .cards
+        .iter()
+        .find(|card| card.name == name)
+        .unwrap()
+    };
+    let opt = card("Opt");
+    let p_no_island = (0..7)
+      .map(|i| (31 - i) as f64 / (40 - i) as f64)
+      .product::<f64>();
+    assert!(f64::abs(opt.exact.p_mana - (1.0 - p_no_island)) < 0.0001);
+    assert!(opt.exact.p_play < opt.exact.p_mana);
+    // Vraska's Contempt needs {2}{B}{B}, so Islands only pay for the generic mana
+    let contempt = card("Vraska's Contempt");
+    assert!(contempt.exact.p_mana > 0.0);
+    assert!(contempt.exact.p_mana < opt.exact.p_mana);
+  }
+
+  #[test]
+  fn complex_decks_are_not_verified() {
+    let dual_lands = decklist!(
+      "
+      8 Island
+      8 Swamp
+      4 Drowned Catacomb
+      20 Opt
+      "
+    );
+    assert!(verify(&simulate(&dual_lands, 100), &dual_lands).is_none());
+    let constructed = decklist!(
+      "
+      24 Island
+      36 Opt
+      "
+    );
+    assert!(verify(&simulate(&constructed, 100), &constructed).is_none());
+    let deck = decklist!(
+      "
+      17 Island
+      23 Opt
+      "
+    );
+    let mulligans = Simulation::from_config(&SimulationConfig {
+      run_count: 100,
+      draw_count: 8,
+      mulligan: &London::commander(),
+      deck: &deck,
+      on_the_play: true,
+      extra_draws: &[],
+      seed: Some(21),
+      cantrip_policy: CantripPolicy::Off,
+    });
+    assert!(verify(&simulate(&deck, 100), &deck).is_some());
+    assert!(verify(&mulligans, &deck).is_none());
+  }
+}
@@ -0,0 +1,35 @@
+//! # Crate version and embedded data dates
+use chrono::NaiveDate;
+use std::str::FromStr;
+
+/// VersionInfo describes this build of landlord and the card data embedded in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+  /// The crate version, e.g. "2024.2.0"
+  pub crate_version: String,
+  /// The date of the Scryfall snapshot used to build data/all_cards.landlord,
+  /// or None if the snapshot date was not recorded. The Arena ids are read from
+  /// the same snapshot
+  pub card_data_date: Option<NaiveDate>,
+}
+
+/// Returns the crate version and the dates of the embedded card data,
+/// e.g. to display "card data as of YYYY-MM-DD" or to decide when to prompt for an update
+pub fn version_info() -> VersionInfo {
+  VersionInfo {
+    crate_version: env!("CARGO_PKG_VERSION").to_string(),
+    card_data_date: NaiveDate::from_str(include_str!("../../data/all_cards.date").trim()).ok(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::version::*;
+
+  #[test]
+  fn version_info_has_crate_version() {
+    let info = version_info();
+    assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+    assert!(info.card_data_date.is_some());
+  }
+}
@@ -0,0 +1,87 @@
+//! # WebAssembly bindings
+//!
+//! The `wasm-bindgen` surface for web consumers, enabled with the `wasm` feature.
+//! Inputs and outputs are serialized with serde.
+use crate::card::Card;
+use crate::data::{CardLoader, ALL_CARDS};
+use crate::run;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+/// Simulates the deck list `deck_str` with the JSON `config_json`, and returns a
+/// `SimulationResult`, or an error string
+/// # Example
+///
+///  ```js
+///  const config = { run_count: 10000, draw_count: 10, on_the_play: true };
+///  const output = run_simulation("20 Island\n40 Opt", JSON.stringify(config));
+///  ```
+#[wasm_bindgen]
+pub fn run_simulation(deck_str: &str, config_json: &str) -> JsValue {
+  match run::run_simulation(deck_str, config_json) {
+    Ok(output) => JsValue::from_serde(&output).expect("this can't fail"),
+    Err(e) => JsValue::from_str(&e),
+  }
+}
+
+/// Returns up to `limit` cards whose name contains `query`, see `Collection::search`
+#[wasm_bindgen]
+pub fn search_cards(query: &str, limit: usize) -> JsValue {
+  let cards: Vec<&Card> = ALL_CARDS.search(query, limit);
+  JsValue::from_serde(&cards).expect("this can't fail")
+}
+
+/// CardDbLoader loads the card database in chunks, so pages can report progress and yield
+/// to the browser between chunks, and store each chunk, e.g. in IndexedDB
+/// # Example
+///
+///  ```js
+///  const loader = new CardDbLoader();
+///  while (!loader.is_done()) {
+///    const cardsBySet = loader.next_chunk(2000);
+///    onProgress(loader.loaded(), loader.total());
+///    await new Promise((resolve) => setTimeout(resolve));
+///  }
+///  loader.install();
+///  ```
+#[wasm_bindgen]
+pub struct CardDbLoader {
+  loader: CardLoader,
+}
+
+#[wasm_bindgen]
+impl CardDbLoader {
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> Result<CardDbLoader, JsValue> {
+    CardLoader::new()
+      .map(|loader| Self { loader })
+      .map_err(|e| JsValue::from_str(&e.to_string()))
+  }
+
+  /// Decodes up to `count` more cards, and returns them as an object of card arrays
+  /// keyed by set code, or an error string
+  pub fn next_chunk(&mut self, count: usize) -> JsValue {
+    match self.loader.next_chunk(count) {
+      Ok(chunk) => JsValue::from_serde(&chunk).expect("this can't fail"),
+      Err(e) => JsValue::from_str(&e.to_string()),
+    }
+  }
+
+  pub fn loaded(&self) -> usize {
+    self.loader.loaded()
+  }
+
+  pub fn total(&self) -> usize {
+    self.loader.total()
+  }
+
+  pub fn is_done(&self) -> bool {
+    self.loader.is_done()
+  }
+
+  /// Makes the loaded cards the card database of the other bindings, see
+  /// `CardLoader::install`
+  pub fn install(self) -> bool {
+    self.loader.install()
+  }
+}